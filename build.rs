@@ -0,0 +1,40 @@
+//! Puts the Tailwind stylesheet the desktop app inlines into `OUT_DIR`. It's
+//! generated, not checked in: `yarn run desktop` writes a fresh tailwind.css
+//! first, which is taken as it is. Without one, the tailwindcss CLI of
+//! `yarn install` or on the `PATH` builds it, and failing that the app is
+//! built unstyled with a warning instead of not at all.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const STYLESHEET: &str = "tailwind.css";
+
+fn main() {
+    println!("cargo:rerun-if-changed={STYLESHEET}");
+    println!("cargo:rerun-if-changed=tailwind.config.js");
+    println!("cargo:rerun-if-changed=src");
+    if env::var_os("CARGO_FEATURE_DESKTOP").is_none() {
+        return;
+    }
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR")).join(STYLESHEET);
+    if fs::copy(STYLESHEET, &out).is_ok() || run_tailwind(&out) {
+        return;
+    }
+
+    println!("cargo:warning={STYLESHEET} is missing and tailwindcss could not build it, the desktop app will be unstyled. Run `yarn install` and `yarn run build:css` to fix that.");
+    fs::write(&out, "").expect("OUT_DIR is writable");
+}
+
+fn run_tailwind(out: &Path) -> bool {
+    ["node_modules/.bin/tailwindcss", "tailwindcss"].iter().any(|cli| {
+        Command::new(cli)
+            .arg("-o")
+            .arg(out)
+            .arg("--minify")
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
@@ -0,0 +1,355 @@
+//! Command line companion to the dashboard. It works on the same JSON data
+//! file the dashboard exports, so the two can hand entries back and forth.
+
+mod webdav;
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+
+use nazad_core::data::{write_atomically, DataFile};
+use nazad_core::date::Date;
+use nazad_core::import::{parse_date, parse_duration_minutes};
+use nazad_core::log::{Entry, EntryType, LogData, ENTRY_TYPES};
+use nazad_core::review::{Change, Comparison, Review};
+use nazad_core::stats::{self, format_minutes, Summary};
+use nazad_core::user::{Goal, UserProfile};
+use nazad_core::wrapped::YearInReview;
+
+use webdav::WebDav;
+
+#[derive(Parser)]
+#[command(name = "nazad", about = "Log and review language learning time from the terminal")]
+struct Cli {
+    /// Data file to work on, defaults to nazad/nazad.json in the platform data directory
+    #[arg(long, global = true, env = "NAZAD_FILE")]
+    file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Log time spent, e.g. `nazad log 45m listening "Podcast ep. 12" --date yesterday`
+    Log {
+        /// Duration such as 45m, 1h30m or 1:30
+        duration: String,
+        /// One of FlashCard, Listening, Reading, Speaking, Writing, Other
+        entry_type: String,
+        title: String,
+        /// today, yesterday, a number of days ago like 3d, or a date like 2026-10-01
+        #[arg(long, default_value = "today")]
+        date: String,
+        /// Book, podcast, deck etc. the time was spent on
+        #[arg(long)]
+        resource: Option<String>,
+        /// Language studied, the first target language of the profile by default
+        #[arg(long)]
+        language: Option<String>,
+        /// The resource was finished in this session
+        #[arg(long, requires = "resource")]
+        finished: bool,
+    },
+    /// List goals, or add and complete them
+    Goals {
+        #[command(subcommand)]
+        action: Option<GoalAction>,
+    },
+    /// Show totals for a period, the current week by default
+    Stats(StatsPeriod),
+    /// Compare last week with the week before, or last month with the month before
+    Review(ReviewPeriod),
+    /// Sum up a year, the current one by default, and optionally export it
+    Year {
+        #[arg(value_parser = clap::value_parser!(i32).range(1..=9999))]
+        year: Option<i32>,
+        /// Save the summary as an SVG image
+        #[arg(long)]
+        svg: Option<PathBuf>,
+        /// Save the summary as an HTML page
+        #[arg(long)]
+        html: Option<PathBuf>,
+    },
+    /// Merge entries and goals from a dashboard export into the data file
+    Import { path: PathBuf },
+    /// Sync the data file with a copy on a WebDAV server such as Nextcloud
+    Sync {
+        /// URL of the file on the server, e.g. https://cloud.example.com/remote.php/dav/files/ana/nazad.json
+        #[arg(long, env = "NAZAD_WEBDAV_URL")]
+        url: String,
+        #[arg(long, env = "NAZAD_WEBDAV_USERNAME")]
+        username: Option<String>,
+        /// Better set through the environment, Nextcloud app passwords work as well
+        #[arg(long, env = "NAZAD_WEBDAV_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GoalAction {
+    Add {
+        title: String,
+        #[arg(long)]
+        due: Option<String>,
+    },
+    /// Toggle whether a goal is completed
+    Done { id: usize },
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+struct StatsPeriod {
+    #[arg(long)]
+    week: bool,
+    #[arg(long)]
+    month: bool,
+    #[arg(long)]
+    year: bool,
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+struct ReviewPeriod {
+    #[arg(long)]
+    month: bool,
+    /// This year so far against the same days of last year
+    #[arg(long)]
+    year: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    if let Err(error) = run(cli) {
+        eprintln!("error: {error}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let path = match cli.file {
+        Some(path) => path,
+        None => default_data_file()?,
+    };
+    let mut data = load(&path)?;
+    let today = data.user.settings().day_of(Local::now().naive_local());
+
+    match cli.command {
+        Command::Log { duration, entry_type, title, date, resource, language, finished } => {
+            let minutes = parse_duration_minutes(&duration).ok_or(format!("invalid duration \"{duration}\""))?;
+            let entry_type = EntryType::from_name(&entry_type)
+                .ok_or(format!("unknown type \"{entry_type}\", use one of {}", ENTRY_TYPES.join(", ")))?;
+            let day = parse_day(&date, today).ok_or(format!("invalid date \"{date}\""))?;
+
+            let entry = Entry::from_data(title, entry_type, minutes, Date::from_naive(day)).with_logged_at(Local::now().fixed_offset());
+            let entry = match resource {
+                Some(resource) => entry.with_resource(resource),
+                None => entry,
+            };
+            let entry = if finished { entry.with_finished() } else { entry };
+            let entry = match language.or(data.user.profile().target_languages.first().cloned()) {
+                Some(language) => entry.with_language(language),
+                None => entry,
+            };
+            // Two sessions that look the same are two sessions, unlike an imported row seen twice
+            data.log.add(entry);
+            save(&path, &data)?;
+            println!("Logged {} on {day}", format_minutes(minutes));
+        }
+        Command::Goals { action: None } => print_goals(&data.user),
+        Command::Goals { action: Some(GoalAction::Add { title, due }) } => {
+            let due = match due {
+                Some(due) => Some(parse_day(&due, today).ok_or(format!("invalid date \"{due}\""))?),
+                None => None,
+            };
+            data.user.import_goals(vec![Goal::from_data(title, false, due)]);
+            save(&path, &data)?;
+            print_goals(&data.user);
+        }
+        Command::Goals { action: Some(GoalAction::Done { id }) } => {
+            let goal = data
+                .user
+                .goals()
+                .iter()
+                .find(|goal| goal.id() == id)
+                .cloned()
+                .ok_or(format!("no goal with id {id}"))?;
+            data.user.mark_goal_as_completed_on(&goal, today);
+            save(&path, &data)?;
+            print_goals(&data.user);
+        }
+        Command::Stats(period) => {
+            let (from, to) = if period.month {
+                stats::month_of(today)
+            } else if period.year {
+                stats::year_of(today)
+            } else if period.all {
+                (first_day(&data.log).unwrap_or(today), today)
+            } else {
+                data.user.settings().week_of(today)
+            };
+            print_summary(&Summary::of(&data.log, from, to), &data.user);
+        }
+        Command::Review(period) => {
+            let comparison = if period.month {
+                Comparison::LastMonth
+            } else if period.year {
+                Comparison::ThisYear
+            } else {
+                Comparison::LastWeek
+            };
+            print_review(&Review::of(&data.log, &data.user, comparison, today));
+        }
+        Command::Year { year, svg, html } => {
+            let review = YearInReview::of(&data.log, &data.user, year.unwrap_or(today.year()));
+            print_year(&review);
+            if let Some(svg) = svg {
+                fs::write(&svg, review.to_svg(data.user.settings()))?;
+                println!("Saved the image to {}", svg.display());
+            }
+            if let Some(html) = html {
+                fs::write(&html, review.to_html(data.user.settings()))?;
+                println!("Saved the page to {}", html.display());
+            }
+        }
+        Command::Import { path: other } => {
+            let raw = fs::read_to_string(&other)?;
+            let (entries, goals) = data.merge(DataFile::from_json(&raw)?);
+            save(&path, &data)?;
+            println!(
+                "Imported {} entries and {} goals, skipped {} duplicates",
+                entries.added,
+                goals.added,
+                entries.duplicates + goals.duplicates
+            );
+        }
+        Command::Sync { url, username, password } => {
+            let state_path = path.with_extension("webdav.json");
+            let synced = WebDav::new(url.clone(), username, password).sync(&data, &webdav::load_state(&state_path)?)?;
+            if synced.pulled {
+                save(&path, &synced.data)?;
+            }
+            webdav::save_state(&synced.state, &state_path)?;
+            println!("{}", synced.describe(&url));
+        }
+    }
+
+    Ok(())
+}
+
+fn default_data_file() -> Result<PathBuf, Box<dyn Error>> {
+    let directory = dirs::data_dir().ok_or("no data directory on this platform, pass --file")?;
+    Ok(directory.join("nazad").join("nazad.json"))
+}
+
+fn load(path: &Path) -> Result<DataFile, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(DataFile::new(LogData::new(), UserProfile::new()));
+    }
+
+    let raw = fs::read_to_string(path)?;
+    Ok(DataFile::from_json(&raw)?)
+}
+
+// The app may read the file at any time, so it is never seen half written
+fn save(path: &Path, data: &DataFile) -> Result<(), Box<dyn Error>> {
+    write_atomically(path, &data.to_json())?;
+    Ok(())
+}
+
+fn parse_day(raw: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match raw.trim().to_lowercase().as_str() {
+        "today" => Some(today),
+        "yesterday" => Some(today - Duration::days(1)),
+        relative => match relative.strip_suffix('d').map(str::parse::<i64>) {
+            Some(Ok(days)) => Duration::try_days(days).and_then(|days| today.checked_sub_signed(days)),
+            _ => parse_date(raw, None),
+        },
+    }
+}
+
+fn first_day(log: &LogData) -> Option<NaiveDate> {
+    log.entries().iter().filter_map(|entry| entry.date().to_naive()).min()
+}
+
+fn print_goals(user: &UserProfile) {
+    for goal in user.goals().iter().filter(|goal| !goal.title().is_empty()) {
+        let check = if goal.completed() { "x" } else { " " };
+        let due = goal.date().to_naive().map(|due| format!(" (due {due})")).unwrap_or_default();
+        println!("[{check}] {:>3}  {}{due}", goal.id(), goal.title());
+    }
+}
+
+fn print_summary(summary: &Summary, user: &UserProfile) {
+    println!("{} to {}", summary.from, summary.to);
+    println!("  Total          {}", format_minutes(summary.total_minutes));
+    println!("  Daily average  {}", format_minutes(summary.daily_average()));
+    println!("  Active days    {} of {}", summary.active_days, summary.days());
+    for (entry_type, minutes) in summary.per_type.iter().filter(|(_, minutes)| *minutes > 0) {
+        println!("  {:<14} {}", entry_type.name(), format_minutes(*minutes));
+    }
+    let completed = user.goals().iter().filter(|goal| goal.completed()).count();
+    println!("  Goals done     {completed} of {}", user.goals().iter().filter(|goal| !goal.title().is_empty()).count());
+}
+
+fn print_review(review: &Review) {
+    let (current, previous) = (&review.current.summary, &review.previous.summary);
+    println!("{} to {} against {} to {}", current.from, current.to, previous.from, previous.to);
+
+    let row = |label: &str, change: Change, format: &dyn Fn(usize) -> String| {
+        let sign = if change.delta() < 0 { "-" } else { "+" };
+        let relative = change
+            .relative()
+            .map(|relative| format!(" ({sign}{:.0}%)", relative.abs() * 100.0))
+            .unwrap_or_default();
+        println!(
+            "  {label:<18} {:<10} {:<10} {sign}{}{relative}",
+            format(change.current),
+            format(change.previous),
+            format(change.delta().unsigned_abs() as usize)
+        );
+    };
+    let count = |value: usize| value.to_string();
+
+    row("Total", review.total_minutes(), &format_minutes);
+    for (entry_type, change) in review.per_type().into_iter().filter(|(_, change)| change.current + change.previous > 0) {
+        row(entry_type.name(), change, &format_minutes);
+    }
+    row("Active days", review.active_days(), &count);
+    row("Goals completed", review.goals_completed(), &count);
+    row("Resources finished", review.resources_finished(), &count);
+    for goal in &review.current.goals_completed {
+        println!("  Completed {goal}");
+    }
+    for resource in &review.current.resources_finished {
+        println!("  Finished {resource}");
+    }
+}
+
+fn print_year(review: &YearInReview) {
+    let summary = &review.summary;
+    println!("{} in review", review.year);
+    println!("  Total          {}", format_minutes(summary.total_minutes));
+    println!("  Active days    {}", summary.active_days);
+    if let Some((month, minutes)) = review.best_month() {
+        println!("  Best month     {} ({})", month.format("%B"), format_minutes(minutes));
+    }
+    if let Some(streak) = review.longest_streak {
+        println!("  Longest streak {} days, {} to {}", streak.days, streak.from, streak.to);
+    }
+    for (entry_type, minutes) in summary.per_type.iter().filter(|(_, minutes)| *minutes > 0) {
+        println!("  {:<14} {}", entry_type.name(), format_minutes(*minutes));
+    }
+    for total in &review.top_resources {
+        println!("  Used {} ({}) for {}", total.resource, total.entry_type.name(), format_minutes(total.minutes));
+    }
+    for goal in &review.goals_completed {
+        println!("  Achieved {goal}");
+    }
+}
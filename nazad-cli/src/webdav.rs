@@ -0,0 +1,93 @@
+//! The blocking HTTP side of syncing the data file through WebDAV, see
+//! `nazad_core::webdav` for how the two sides are merged.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH};
+use reqwest::Method;
+
+use nazad_core::data::{write_atomically, DataFile};
+use nazad_core::webdav::{self, Precondition, Response, SyncState, Synced, Transport};
+
+pub type WebDavError = webdav::WebDavError<reqwest::Error>;
+
+/// The state of the last sync, kept next to the data file.
+pub fn load_state(path: &Path) -> Result<SyncState, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(SyncState::default());
+    }
+    Ok(SyncState::from_json(&fs::read_to_string(path)?)?)
+}
+
+pub fn save_state(state: &SyncState, path: &Path) -> Result<(), Box<dyn Error>> {
+    write_atomically(path, &state.to_json())?;
+    Ok(())
+}
+
+pub struct WebDav {
+    client: Client,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl WebDav {
+    pub fn new(url: String, username: Option<String>, password: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            username,
+            password,
+        }
+    }
+
+    fn request(&self, method: Method) -> RequestBuilder {
+        let request = self.client.request(method, &self.url);
+        match &self.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+
+    /// Pulls what changed on the server, merges it with `local` and pushes
+    /// the result if the server doesn't have it yet.
+    pub fn sync(&self, local: &DataFile, state: &SyncState) -> Result<Synced, WebDavError> {
+        webdav::sync_blocking(self, &self.url, local, state)
+    }
+}
+
+fn response_of(response: reqwest::blocking::Response) -> Result<Response, reqwest::Error> {
+    let status = response.status();
+    let etag = response.headers().get(ETAG).and_then(|etag| etag.to_str().ok()).map(str::to_string);
+    let body = if status.is_success() { Some(response.text()?) } else { None };
+    Ok(Response {
+        status: status.as_u16(),
+        etag,
+        body,
+    })
+}
+
+// The requests block, so the futures are ready as soon as they are polled
+impl Transport for WebDav {
+    type Error = reqwest::Error;
+
+    async fn get(&self, known_etag: Option<&str>) -> Result<Response, reqwest::Error> {
+        let mut request = self.request(Method::GET);
+        if let Some(etag) = known_etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        response_of(request.send()?)
+    }
+
+    async fn put(&self, body: String, precondition: Precondition) -> Result<Response, reqwest::Error> {
+        let request = self.request(Method::PUT).header(CONTENT_TYPE, "application/json").body(body);
+        let request = match precondition {
+            Precondition::Matches(etag) => request.header(IF_MATCH, etag),
+            Precondition::Missing => request.header(IF_NONE_MATCH, "*"),
+        };
+        response_of(request.send()?)
+    }
+}
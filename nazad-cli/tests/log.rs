@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use nazad_core::data::DataFile;
+
+fn temp_file(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("nazad-log-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory.join("nazad.json")
+}
+
+fn nazad(file: &PathBuf, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_nazad")).args(args).env("NAZAD_FILE", file).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn the_same_session_can_be_logged_twice() {
+    let file = temp_file("twice");
+
+    for _ in 0..2 {
+        let logged = nazad(&file, &["log", "30m", "reading", "Chapter 3", "--date", "2026-10-01"]);
+        assert!(logged.starts_with("Logged 30m"), "{logged}");
+    }
+
+    let data = DataFile::from_json(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    let ids: Vec<usize> = data.log.entries().iter().map(|entry| entry.id()).collect();
+    assert_eq!(ids, [0, 1]);
+    std::fs::remove_dir_all(file.parent().unwrap()).unwrap();
+}
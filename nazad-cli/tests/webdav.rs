@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use dav_server::{fakels::FakeLs, memfs::MemFs, DavHandler};
+
+use nazad_core::data::DataFile;
+use nazad_core::log::{Entry, EntryType, LogData};
+use nazad_core::user::UserProfile;
+
+// An in-memory WebDAV server, returns the URL to sync with
+fn spawn_webdav() -> String {
+    let (sender, receiver) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async move {
+            let dav = DavHandler::builder()
+                .filesystem(MemFs::new())
+                .locksystem(FakeLs::new())
+                .build_handler();
+            let app = axum::Router::new().fallback(move |request: axum::extract::Request| {
+                let dav = dav.clone();
+                async move { dav.handle(request).await }
+            });
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            sender.send(listener.local_addr().unwrap()).unwrap();
+            axum::serve(listener, app).await.unwrap();
+        });
+    });
+
+    format!("http://{}/nazad.json", receiver.recv().unwrap())
+}
+
+struct Device {
+    file: PathBuf,
+    url: String,
+}
+
+impl Device {
+    fn new(directory: &Path, name: &str, url: &str) -> Self {
+        Self {
+            file: directory.join(format!("{name}.json")),
+            url: url.to_string(),
+        }
+    }
+
+    fn nazad(&self, args: &[&str]) -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_nazad"))
+            .args(args)
+            .env("NAZAD_FILE", &self.file)
+            .env("NAZAD_WEBDAV_URL", &self.url)
+            .env("NAZAD_WEBDAV_USERNAME", "ana")
+            .env("NAZAD_WEBDAV_PASSWORD", "secret")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    fn log(&self, title: &str) {
+        self.nazad(&["log", "30m", "reading", title, "--date", "2026-10-01"]);
+    }
+
+    fn sync(&self) -> String {
+        self.nazad(&["sync"]).trim().to_string()
+    }
+
+    fn titles(&self) -> Vec<String> {
+        let data = DataFile::from_json(&std::fs::read_to_string(&self.file).unwrap()).unwrap();
+        let mut titles: Vec<String> = data.log.entries().iter().map(|entry| entry.title().to_string()).collect();
+        titles.sort();
+        titles
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("nazad-webdav-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn entries_reach_the_other_device() {
+    let url = spawn_webdav();
+    let directory = temp_dir("reach");
+    let laptop = Device::new(&directory, "laptop", &url);
+    let phone = Device::new(&directory, "phone", &url);
+
+    laptop.log("Chapter 3");
+    assert!(laptop.sync().starts_with("Pushed changes"));
+    assert!(phone.sync().starts_with("Pulled changes"));
+    assert!(phone.sync().starts_with("Already in sync"));
+
+    assert_eq!(phone.titles(), ["Chapter 3"]);
+}
+
+#[test]
+fn changes_from_both_devices_are_merged() {
+    let url = spawn_webdav();
+    let directory = temp_dir("merge");
+    let laptop = Device::new(&directory, "laptop", &url);
+    let phone = Device::new(&directory, "phone", &url);
+    laptop.log("Chapter 3");
+    laptop.sync();
+    phone.sync();
+
+    laptop.log("Chapter 4");
+    phone.log("Podcast");
+    assert!(laptop.sync().starts_with("Pushed changes"));
+    assert!(phone.sync().starts_with("Merged changes"));
+    assert!(laptop.sync().starts_with("Pulled changes"));
+
+    assert_eq!(laptop.titles(), ["Chapter 3", "Chapter 4", "Podcast"]);
+    assert_eq!(phone.titles(), laptop.titles());
+}
+
+#[test]
+fn uploads_from_elsewhere_are_not_overwritten() {
+    let url = spawn_webdav();
+    let directory = temp_dir("etag");
+    let laptop = Device::new(&directory, "laptop", &url);
+    laptop.log("Chapter 3");
+    laptop.sync();
+
+    // Another client replaces the file behind the laptop's back
+    let mut log = LogData::new();
+    log.import_entries(vec![Entry::from_data(
+        "Interview".to_string(),
+        EntryType::Listening,
+        20,
+        Default::default(),
+    )]);
+    let client = reqwest::blocking::Client::new();
+    let stale = client.put(&url).header("If-Match", "\"outdated\"").body("{}").send().unwrap();
+    assert_eq!(stale.status(), reqwest::StatusCode::PRECONDITION_FAILED);
+    client.put(&url).body(DataFile::new(log, UserProfile::new()).to_json()).send().unwrap();
+
+    laptop.log("Chapter 4");
+    assert!(laptop.sync().starts_with("Merged changes"));
+
+    // The replaced file dropped Chapter 3, which counts as deleting it
+    assert_eq!(laptop.titles(), ["Chapter 4", "Interview"]);
+}
@@ -0,0 +1,74 @@
+//! The REST API of the self-hosted server, shared by the server and the
+//! dashboard's client mode. Entries and goals are addressed by their ids, the
+//! profile and settings are single documents.
+//!
+//! Registering or logging in returns a `Session`, whose token is sent as
+//! `Authorization: Bearer <token>` with every other request. All data is
+//! scoped to the account the token belongs to.
+//!
+//! | Method            | Path                  | Body          |
+//! |-------------------|-----------------------|---------------|
+//! | POST              | `/api/register`       | `Credentials` |
+//! | POST              | `/api/login`          | `Credentials` |
+//! | POST              | `/api/logout`         |               |
+//! | GET, POST         | `/api/entries`        | `Entry`       |
+//! | PUT, DELETE       | `/api/entries/{id}`   | `Entry`       |
+//! | GET, POST         | `/api/goals`          | `Goal`        |
+//! | PUT, DELETE       | `/api/goals/{id}`     | `Goal`        |
+//! | GET, PUT          | `/api/profile`        | `Profile`     |
+//! | GET, PUT          | `/api/settings`       | `Settings`    |
+//! | POST              | `/api/sync`           | `SyncRequest` |
+//!
+//! The dashboard itself only uses `/api/sync`, see `crate::sync`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::user::{User, UserDiagnostics, UserProfile};
+
+pub const REGISTER_PATH: &str = "/api/register";
+pub const LOGIN_PATH: &str = "/api/login";
+pub const LOGOUT_PATH: &str = "/api/logout";
+pub const ENTRIES_PATH: &str = "/api/entries";
+pub const GOALS_PATH: &str = "/api/goals";
+pub const PROFILE_PATH: &str = "/api/profile";
+pub const SETTINGS_PATH: &str = "/api/settings";
+pub const SYNC_PATH: &str = "/api/sync";
+
+pub fn entry_path(id: usize) -> String {
+    format!("{ENTRIES_PATH}/{id}")
+}
+
+pub fn goal_path(id: usize) -> String {
+    format!("{GOALS_PATH}/{id}")
+}
+
+/// Shorter passwords are refused when registering.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Session {
+    pub username: String,
+    pub token: String,
+}
+
+/// The parts of a `UserProfile` that are neither goals nor settings.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct Profile {
+    pub profile: User,
+    pub diagnostics: UserDiagnostics,
+}
+
+impl Profile {
+    pub fn of(user: &UserProfile) -> Self {
+        Self {
+            profile: user.profile().clone(),
+            diagnostics: user.diagnostics().clone(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Profile pictures. Uploaded images are shrunk and kept inside the profile
+//! as a `data:` URL, so they travel with the data file and sync like any
+//! other field. Without a picture the initials of the name are shown.
+
+use std::error::Error;
+use std::fmt;
+use std::io::Cursor;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::imageops::FilterType;
+use image::ImageOutputFormat;
+
+/// Width and height of stored pictures, in pixels.
+pub const AVATAR_SIZE: u32 = 128;
+
+#[derive(Debug)]
+pub enum AvatarError {
+    /// Not an image, or in a format other than PNG, JPEG, GIF or WebP.
+    Unreadable(String),
+}
+
+impl fmt::Display for AvatarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AvatarError::Unreadable(reason) => write!(f, "could not read the picture: {reason}"),
+        }
+    }
+}
+
+impl Error for AvatarError {}
+
+/// Crops the image to a square, shrinks it to `AVATAR_SIZE` and returns it
+/// as a PNG `data:` URL.
+pub fn avatar_data_url(bytes: &[u8]) -> Result<String, AvatarError> {
+    let image = image::load_from_memory(bytes).map_err(|error| AvatarError::Unreadable(error.to_string()))?;
+    let square = image.resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, FilterType::Triangle);
+
+    let mut png = Cursor::new(vec![]);
+    square
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|error| AvatarError::Unreadable(error.to_string()))?;
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png.into_inner())))
+}
+
+/// Up to two letters for the picture shown when there is none, e.g. `AK`
+/// for "Ana Kovač".
+pub fn initials(name: &str) -> String {
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+/// A background color derived from the name, so everyone keeps theirs.
+pub fn initials_color(name: &str) -> &'static str {
+    const COLORS: [&str; 6] = ["#2563eb", "#16a34a", "#db2777", "#ea580c", "#7c3aed", "#0891b2"];
+    let sum: usize = name.bytes().map(usize::from).sum();
+    COLORS[sum % COLORS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::{DynamicImage, Rgb, RgbImage};
+
+    #[test]
+    fn shrinks_pictures_to_squares() {
+        let photo = DynamicImage::ImageRgb8(RgbImage::from_pixel(640, 480, Rgb([200, 40, 40])));
+        let mut bytes = Cursor::new(vec![]);
+        photo.write_to(&mut bytes, ImageOutputFormat::Png).unwrap();
+
+        let url = avatar_data_url(bytes.get_ref()).unwrap();
+        let encoded = url.strip_prefix("data:image/png;base64,").unwrap();
+        let stored = image::load_from_memory(&STANDARD.decode(encoded).unwrap()).unwrap();
+
+        assert_eq!((stored.width(), stored.height()), (AVATAR_SIZE, AVATAR_SIZE));
+        assert!(avatar_data_url(b"not a picture").is_err());
+    }
+
+    #[test]
+    fn takes_initials_from_the_name() {
+        assert_eq!(initials("Ana Kovač"), "AK");
+        assert_eq!(initials("  ana maria kovač "), "AM");
+        assert_eq!(initials("Ágnes"), "Á");
+        assert_eq!(initials(""), "?");
+    }
+}
@@ -0,0 +1,145 @@
+//! How time is spread over the skills: the share of each entry type, input
+//! (listening and reading) against output (speaking and writing), and skills
+//! that haven't been practised for a while.
+
+use chrono::{Duration, NaiveDate};
+
+use crate::log::{EntryType, LogData};
+use crate::stats::Summary;
+
+pub const INPUT_SKILLS: [EntryType; 2] = [EntryType::Listening, EntryType::Reading];
+pub const OUTPUT_SKILLS: [EntryType; 2] = [EntryType::Speaking, EntryType::Writing];
+
+/// Skills checked for neglect. Flash cards and other time aren't skills one
+/// falls behind on.
+pub const PRACTISED_SKILLS: [EntryType; 4] = [EntryType::Listening, EntryType::Reading, EntryType::Speaking, EntryType::Writing];
+
+/// Share of each entry type in the time of a period.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SkillBalance {
+    /// Minutes per type, in the order of `EntryType::ALL`.
+    pub per_skill: Vec<(EntryType, usize)>,
+    pub input_minutes: usize,
+    pub output_minutes: usize,
+}
+
+impl SkillBalance {
+    pub fn of(summary: &Summary) -> Self {
+        let minutes_of = |skills: &[EntryType]| -> usize {
+            summary
+                .per_type
+                .iter()
+                .filter(|(entry_type, _)| skills.contains(entry_type))
+                .map(|(_, minutes)| minutes)
+                .sum()
+        };
+
+        Self {
+            per_skill: summary.per_type.clone(),
+            input_minutes: minutes_of(&INPUT_SKILLS),
+            output_minutes: minutes_of(&OUTPUT_SKILLS),
+        }
+    }
+
+    /// Share of the time per type, from 0 to 1. All zero for an empty period.
+    pub fn shares(&self) -> Vec<(EntryType, f64)> {
+        let total: usize = self.per_skill.iter().map(|(_, minutes)| minutes).sum();
+        self.per_skill
+            .iter()
+            .map(|(entry_type, minutes)| (entry_type.clone(), *minutes as f64 / total.max(1) as f64))
+            .collect()
+    }
+
+    /// Input's share of input and output together, `None` without either.
+    pub fn input_share(&self) -> Option<f64> {
+        match self.input_minutes + self.output_minutes {
+            0 => None,
+            both => Some(self.input_minutes as f64 / both as f64),
+        }
+    }
+}
+
+/// A skill without entries in the last days.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Neglected {
+    pub skill: EntryType,
+    /// `None` if it was never practised.
+    pub last_day: Option<NaiveDate>,
+}
+
+/// Practised skills last logged more than `days` days before today. Skills
+/// never logged count once the log itself is that old, so a new log doesn't
+/// start out with warnings.
+pub fn neglected(log: &LogData, today: NaiveDate, days: usize) -> Vec<Neglected> {
+    let cutoff = today - Duration::days(days as i64);
+    let last_day_of = |skill: &EntryType| {
+        log.entries()
+            .iter()
+            .filter(|entry| entry.entry_type() == skill)
+            .filter_map(|entry| entry.date().to_naive())
+            .filter(|day| *day <= today)
+            .max()
+    };
+    let first_day = log.entries().iter().filter_map(|entry| entry.date().to_naive()).min();
+    let Some(first_day) = first_day else {
+        return vec![];
+    };
+
+    PRACTISED_SKILLS
+        .into_iter()
+        .map(|skill| Neglected {
+            last_day: last_day_of(&skill),
+            skill,
+        })
+        .filter(|neglected| match neglected.last_day {
+            Some(last_day) => last_day < cutoff,
+            None => first_day < cutoff,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::log::Entry;
+
+    fn october(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn log(entries: &[(u32, EntryType, usize)]) -> LogData {
+        let mut log = LogData::new();
+        for (day, entry_type, minutes) in entries {
+            log.add(Entry::from_data("Session".to_string(), entry_type.clone(), *minutes, Date::from_naive(october(*day))));
+        }
+        log
+    }
+
+    #[test]
+    fn weighs_input_against_output() {
+        let log = log(&[
+            (1, EntryType::Listening, 90),
+            (2, EntryType::Reading, 30),
+            (3, EntryType::Speaking, 40),
+            (4, EntryType::FlashCard, 40),
+        ]);
+        let balance = SkillBalance::of(&Summary::of(&log, october(1), october(31)));
+
+        assert_eq!((balance.input_minutes, balance.output_minutes), (120, 40));
+        assert_eq!(balance.input_share(), Some(0.75));
+        assert_eq!(balance.shares()[1], (EntryType::Listening, 0.45));
+        assert_eq!(SkillBalance::of(&Summary::of(&log, october(10), october(31))).input_share(), None);
+    }
+
+    #[test]
+    fn warns_about_skills_left_alone() {
+        let log = log(&[(1, EntryType::Writing, 20), (12, EntryType::Listening, 30), (18, EntryType::Reading, 30)]);
+        let skills = |days| neglected(&log, october(19), days).into_iter().map(|neglected| neglected.skill).collect::<Vec<_>>();
+
+        assert_eq!(skills(14), [EntryType::Speaking, EntryType::Writing]);
+        assert_eq!(skills(5), [EntryType::Listening, EntryType::Speaking, EntryType::Writing]);
+        assert_eq!(skills(30), []);
+        assert!(neglected(&LogData::new(), october(19), 14).is_empty());
+    }
+}
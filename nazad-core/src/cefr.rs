@@ -0,0 +1,254 @@
+//! Self-assessment against the CEFR levels A1 to C2. For each skill and
+//! level there are a few "can do" statements, kept in `cefr/can-do.json`,
+//! which users tick as they become true, for each language they learn. Every
+//! tick is kept with its time, so the estimated level can be replayed for any
+//! moment and set against the hours logged until then.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::log::EntryType;
+
+const CAN_DO: &str = include_str!("../cefr/can-do.json");
+
+/// Share of a level's statements that have to be ticked to reach it.
+pub const PASS_SHARE: f64 = 0.8;
+
+/// Entry types the CEFR describes levels for.
+pub const SKILLS: [EntryType; 4] = [EntryType::Listening, EntryType::Reading, EntryType::Speaking, EntryType::Writing];
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum CefrLevel {
+    A1,
+    A2,
+    B1,
+    B2,
+    C1,
+    C2,
+}
+
+impl CefrLevel {
+    pub const ALL: [CefrLevel; 6] = [CefrLevel::A1, CefrLevel::A2, CefrLevel::B1, CefrLevel::B2, CefrLevel::C1, CefrLevel::C2];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CefrLevel::A1 => "A1",
+            CefrLevel::A2 => "A2",
+            CefrLevel::B1 => "B1",
+            CefrLevel::B2 => "B2",
+            CefrLevel::C1 => "C1",
+            CefrLevel::C2 => "C2",
+        }
+    }
+
+    /// 1 for A1 up to 6 for C2, for charts.
+    pub fn rank(&self) -> usize {
+        *self as usize + 1
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct CanDo {
+    /// Like `listening-b1-2`, made of the skill, level and position in the
+    /// list, so statements must only ever be added at the end.
+    pub id: String,
+    pub skill: EntryType,
+    pub level: CefrLevel,
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct StatementGroup {
+    skill: EntryType,
+    level: CefrLevel,
+    statements: Vec<String>,
+}
+
+/// All statements, by skill and level.
+pub fn statements() -> Vec<CanDo> {
+    let groups: Vec<StatementGroup> = serde_json::from_str(CAN_DO).expect("the built-in statements are valid");
+    groups
+        .into_iter()
+        .flat_map(|group| {
+            let prefix = format!("{}-{}", group.skill.name(), group.level.name()).to_lowercase();
+            group.statements.into_iter().enumerate().map(move |(index, text)| CanDo {
+                id: format!("{prefix}-{}", index + 1),
+                skill: group.skill.clone(),
+                level: group.level,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// A statement ticked or unticked.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct CanDoCheck {
+    pub id: String,
+    /// The language the statement was ticked for. Ticks from before
+    /// languages were kept have none and count for every language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub checked: bool,
+    pub at: DateTime<FixedOffset>,
+}
+
+impl CanDoCheck {
+    fn counts_for(&self, language: Option<&str>) -> bool {
+        language.is_none() || self.language.is_none() || self.language.as_deref() == language
+    }
+}
+
+/// Both histories in one, oldest first. Ticks are never removed, so every
+/// tick of either is kept and the same tick made on two devices once.
+pub fn merge_history(history: &[CanDoCheck], other: &[CanDoCheck]) -> Vec<CanDoCheck> {
+    let mut merged: Vec<CanDoCheck> = history.iter().chain(other).cloned().collect();
+    merged.sort_by(|a, b| (a.at, &a.id, &a.language, a.checked).cmp(&(b.at, &b.id, &b.language, b.checked)));
+    merged.dedup_by(|a, b| a.at == b.at && a.id == b.id && a.language == b.language);
+    merged
+}
+
+/// Ids of the statements ticked for `language` at `until`, or now when it's
+/// `None`. `history` is oldest first.
+pub fn ticked(history: &[CanDoCheck], language: Option<&str>, until: Option<DateTime<FixedOffset>>) -> HashSet<String> {
+    let mut ticked = HashSet::new();
+    let counted = history
+        .iter()
+        .filter(|check| check.counts_for(language))
+        .filter(|check| until.is_none_or(|until| check.at <= until));
+    for check in counted {
+        if check.checked {
+            ticked.insert(check.id.clone());
+        } else {
+            ticked.remove(&check.id);
+        }
+    }
+    ticked
+}
+
+/// The highest level for which this and every lower level have enough of
+/// their statements ticked.
+pub fn estimated_level(statements: &[CanDo], ticked: &HashSet<String>, skill: &EntryType) -> Option<CefrLevel> {
+    CefrLevel::ALL
+        .into_iter()
+        .take_while(|level| {
+            let of_level: Vec<&CanDo> = statements.iter().filter(|can_do| can_do.skill == *skill && can_do.level == *level).collect();
+            let done = of_level.iter().filter(|can_do| ticked.contains(&can_do.id)).count();
+            !of_level.is_empty() && done as f64 >= PASS_SHARE * of_level.len() as f64
+        })
+        .last()
+}
+
+/// The estimated level of `skill` in `language` after every change to it,
+/// oldest first.
+pub fn level_history(
+    statements: &[CanDo],
+    history: &[CanDoCheck],
+    language: Option<&str>,
+    skill: &EntryType,
+) -> Vec<(DateTime<FixedOffset>, Option<CefrLevel>)> {
+    let of_skill: HashSet<&str> = statements
+        .iter()
+        .filter(|can_do| can_do.skill == *skill)
+        .map(|can_do| can_do.id.as_str())
+        .collect();
+
+    let mut levels: Vec<(DateTime<FixedOffset>, Option<CefrLevel>)> = vec![];
+    for check in history.iter().filter(|check| check.counts_for(language) && of_skill.contains(check.id.as_str())) {
+        let level = estimated_level(statements, &ticked(history, language, Some(check.at)), skill);
+        if levels.last().map(|(_, last)| *last) != Some(level) {
+            levels.push((check.at, level));
+        }
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2026-10-{day:02}T20:00:00+02:00")).unwrap()
+    }
+
+    fn check(id: &str, checked: bool, day: u32) -> CanDoCheck {
+        CanDoCheck {
+            id: id.to_string(),
+            language: None,
+            checked,
+            at: at(day),
+        }
+    }
+
+    #[test]
+    fn every_skill_and_level_has_statements() {
+        let statements = statements();
+
+        for skill in SKILLS {
+            for level in CefrLevel::ALL {
+                assert!(statements.iter().any(|can_do| can_do.skill == skill && can_do.level == level));
+            }
+        }
+        assert_eq!(statements[0].id, "listening-a1-1");
+    }
+
+    #[test]
+    fn levels_follow_the_ticks_over_time() {
+        let statements = statements();
+        let history = [
+            check("listening-a1-1", true, 1),
+            check("listening-a1-2", true, 1),
+            check("listening-a1-3", true, 2),
+            check("reading-a1-1", true, 3),
+            check("listening-a2-1", true, 4),
+            check("listening-a2-2", true, 4),
+            check("listening-a2-3", true, 5),
+            check("listening-a1-3", false, 6),
+        ];
+
+        assert_eq!(estimated_level(&statements, &ticked(&history, None, Some(at(5))), &EntryType::Listening), Some(CefrLevel::A2));
+        // Two of three A1 statements are not enough, which also drops A2
+        assert_eq!(estimated_level(&statements, &ticked(&history, None, None), &EntryType::Listening), None);
+        assert_eq!(
+            level_history(&statements, &history, None, &EntryType::Listening),
+            [(at(1), None), (at(2), Some(CefrLevel::A1)), (at(5), Some(CefrLevel::A2)), (at(6), None)]
+        );
+    }
+
+    fn check_in(language: &str, id: &str, checked: bool, day: u32) -> CanDoCheck {
+        CanDoCheck {
+            language: Some(language.to_string()),
+            ..check(id, checked, day)
+        }
+    }
+
+    #[test]
+    fn ticks_count_for_their_language() {
+        let history = [
+            check("reading-a1-1", true, 1),
+            check_in("Spanish", "reading-a1-2", true, 2),
+            check_in("Russian", "reading-a1-3", true, 3),
+            check_in("Russian", "reading-a1-1", false, 4),
+        ];
+
+        let spanish = ticked(&history, Some("Spanish"), None);
+        assert!(spanish.contains("reading-a1-1") && spanish.contains("reading-a1-2"));
+        assert!(!spanish.contains("reading-a1-3"));
+        let russian = ticked(&history, Some("Russian"), None);
+        assert!(russian.contains("reading-a1-3"));
+        assert!(!russian.contains("reading-a1-1") && !russian.contains("reading-a1-2"));
+    }
+
+    #[test]
+    fn merging_histories_keeps_the_ticks_of_both() {
+        let laptop = [check("reading-a1-1", true, 1), check_in("Spanish", "reading-a1-2", true, 3)];
+        let phone = [check("reading-a1-1", true, 1), check("reading-a1-3", true, 2), check("reading-a1-1", false, 4)];
+
+        let merged = merge_history(&laptop, &phone);
+
+        assert_eq!(merged, [laptop[0].clone(), phone[1].clone(), laptop[1].clone(), phone[2].clone()]);
+        assert_eq!(merge_history(&phone, &laptop), merged);
+    }
+}
@@ -0,0 +1,411 @@
+//! The JSON data file shared by the dashboard's export, the command line
+//! client and backups. It holds the whole state, so writing one and reading
+//! it back gives the same log and profile.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::hash::Hash;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cefr;
+use crate::log::{Entry, ImportSummary, LogData};
+use crate::user::{Goal, UserProfile};
+
+/// Bumped whenever the layout changes in a way older readers can't handle.
+pub const DATA_FILE_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum DataFileError {
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for DataFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataFileError::Json(error) => write!(f, "invalid data file: {error}"),
+            DataFileError::UnsupportedVersion(version) => write!(
+                f,
+                "data file version {version} is newer than the supported version {DATA_FILE_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DataFileError {}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct DataFile {
+    pub version: u32,
+    pub log: LogData,
+    pub user: UserProfile,
+}
+
+impl DataFile {
+    pub fn new(log: LogData, user: UserProfile) -> Self {
+        Self {
+            version: DATA_FILE_VERSION,
+            log,
+            user,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("data file serializes to JSON")
+    }
+
+    pub fn from_json(raw: &str) -> Result<Self, DataFileError> {
+        let mut file: DataFile = serde_json::from_str(raw).map_err(DataFileError::Json)?;
+        if file.version > DATA_FILE_VERSION {
+            return Err(DataFileError::UnsupportedVersion(file.version));
+        }
+        file.log.ensure_unique_ids();
+
+        Ok(file)
+    }
+
+    /// Adds the entries and goals of `other` that are not part of this file yet.
+    pub fn merge(&mut self, other: DataFile) -> (ImportSummary, ImportSummary) {
+        let entries = self.log.import_entries(other.log.entries().to_vec());
+        let goals = self.user.import_goals(other.user.goals().to_vec());
+        (entries, goals)
+    }
+
+    /// Three-way merge for syncing through a plain file store like WebDAV.
+    /// `self` is this device's data, `remote` the stored file and `base` the
+    /// version both last agreed on, if they synced before. Entries and goals
+    /// added on either side are kept and ones removed on either side stay
+    /// removed; an edit removes the old version and adds the new one, so edits
+    /// of the same entry on both sides keep both. The profile and settings
+    /// changed on this device win, otherwise the remote ones are taken, except
+    /// for the can-do ticks of both which are all kept.
+    pub fn reconcile(&self, base: Option<&DataFile>, remote: &DataFile) -> DataFile {
+        // Without a base, what both sides hold was there before
+        let base_entries = match base {
+            Some(base) => base.log.entries().to_vec(),
+            None => common_items(self.log.entries(), remote.log.entries(), Entry::content),
+        };
+        let base_goals = match base {
+            Some(base) => base.user.goals().to_vec(),
+            None => common_items(self.user.goals(), remote.user.goals(), Goal::content),
+        };
+
+        let entries = merge_items(self.log.entries(), &base_entries, remote.log.entries(), Entry::content);
+
+        let mut goals = merge_items(self.user.goals(), &base_goals, remote.user.goals(), Goal::content);
+        let mut next_id = goals.iter().map(|goal| goal.id() + 1).max().unwrap_or_default();
+        let mut taken = HashSet::new();
+        for goal in &mut goals {
+            if !taken.insert(goal.id()) {
+                *goal = goal.clone().with_id(next_id);
+                next_id += 1;
+            }
+        }
+
+        let base_user = base.map(|base| &base.user);
+        let mut profile = pick(self.user.profile(), base_user.map(UserProfile::profile), remote.user.profile());
+        profile.can_do = cefr::merge_history(&self.user.profile().can_do, &remote.user.profile().can_do);
+        let user = UserProfile::from_parts(
+            profile,
+            pick(self.user.diagnostics(), base_user.map(UserProfile::diagnostics), remote.user.diagnostics()),
+            goals,
+            pick(self.user.settings(), base_user.map(UserProfile::settings), remote.user.settings()),
+        );
+
+        DataFile::new(LogData::from_entries(entries), user)
+    }
+}
+
+/// Replaces the file at `path` with `contents` so that a crash can't leave it
+/// cut short: they are written to a file next to it first, which is then
+/// renamed over it. The directory is created if needed.
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temporary = path.with_file_name(name);
+
+    let mut file = File::create(&temporary)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+// Everything remote that wasn't removed here, plus what was added here. Items
+// are counted, so two sessions that look the same stay two: removing one of
+// them here drops one copy and adding one adds one.
+fn merge_items<T: Clone, K: Hash + Eq>(local: &[T], base: &[T], remote: &[T], content: fn(&T) -> K) -> Vec<T> {
+    let local_counts = counts(local, content);
+    let base_counts = counts(base, content);
+    let count = |counts: &HashMap<K, usize>, key: &K| counts.get(key).copied().unwrap_or_default();
+
+    let mut merged: Vec<T> = vec![];
+    let mut seen = HashMap::new();
+    for item in remote {
+        let key = content(item);
+        let removed = count(&base_counts, &key).saturating_sub(count(&local_counts, &key));
+        let copy = seen.entry(key).or_insert(0);
+        if *copy >= removed {
+            merged.push(item.clone());
+        }
+        *copy += 1;
+    }
+    let mut seen = HashMap::new();
+    for item in local {
+        let key = content(item);
+        let kept = count(&local_counts, &key).min(count(&base_counts, &key));
+        let copy = seen.entry(key).or_insert(0);
+        if *copy >= kept {
+            merged.push(item.clone());
+        }
+        *copy += 1;
+    }
+
+    merged
+}
+
+// The items of `local` that `remote` holds as well, as often as both hold them
+fn common_items<T: Clone, K: Hash + Eq>(local: &[T], remote: &[T], content: fn(&T) -> K) -> Vec<T> {
+    let mut left = counts(remote, content);
+    local
+        .iter()
+        .filter(|item| match left.get_mut(&content(item)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
+// How often each content occurs among `items`
+fn counts<T, K: Hash + Eq>(items: &[T], content: fn(&T) -> K) -> HashMap<K, usize> {
+    let mut counts = HashMap::new();
+    for item in items {
+        *counts.entry(content(item)).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn pick<T: PartialEq + Clone>(local: &T, base: Option<&T>, remote: &T) -> T {
+    match base {
+        Some(base) if local != base => local.clone(),
+        _ => remote.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    use crate::date::Date;
+    use crate::log::EntryType;
+    use crate::user::User;
+
+    fn sample() -> DataFile {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+        let mut log = LogData::new();
+        log.import_entries(vec![
+            Entry::from_data("Podcast".to_string(), EntryType::Listening, 45, Date::from_naive(day))
+                .with_resource("Radio Ambulante".to_string())
+                .with_span(day.and_hms_opt(9, 0, 0).unwrap(), day.and_hms_opt(9, 45, 0).unwrap()),
+            Entry::from_data("Chapter 3".to_string(), EntryType::Reading, 30, Date::from_naive(day)),
+        ]);
+
+        let mut user = UserProfile::new();
+        user.import_goals(vec![Goal::from_data("Reach 100 hours".to_string(), false, Some(day))]);
+
+        DataFile::new(log, user)
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let file = sample();
+
+        assert_eq!(DataFile::from_json(&file.to_json()).unwrap(), file);
+    }
+
+    #[test]
+    fn writing_atomically_replaces_the_file() {
+        let path = std::env::temp_dir().join(format!("nazad-atomic-{}", std::process::id())).join("nazad.json");
+        write_atomically(&path, "{}").unwrap();
+        write_atomically(&path, &sample().to_json()).unwrap();
+
+        assert_eq!(DataFile::from_json(&fs::read_to_string(&path).unwrap()).unwrap(), sample());
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut file = sample();
+        file.version = DATA_FILE_VERSION + 1;
+
+        assert!(matches!(
+            DataFile::from_json(&file.to_json()),
+            Err(DataFileError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn merging_skips_what_is_already_there() {
+        let mut file = sample();
+
+        let (entries, goals) = file.merge(sample());
+
+        assert_eq!(entries.duplicates, 2);
+        // The blank goal every new profile starts with counts as well
+        assert_eq!(goals.duplicates, 2);
+        assert_eq!(file.log.entries().len(), 2);
+    }
+
+    fn entry(title: &str) -> Entry {
+        Entry::from_data(title.to_string(), EntryType::Reading, 30, Date::from_naive(NaiveDate::from_ymd_opt(2026, 10, 2).unwrap()))
+    }
+
+    fn titles(file: &DataFile) -> Vec<&str> {
+        file.log.entries().iter().map(Entry::title).collect()
+    }
+
+    #[test]
+    fn reconciling_keeps_additions_from_both_sides() {
+        let base = sample();
+        let mut laptop = base.clone();
+        laptop.log.import_entries(vec![entry("Chapter 4")]);
+        let mut phone = base.clone();
+        phone.log.import_entries(vec![entry("Chapter 5")]);
+
+        let merged = laptop.reconcile(Some(&base), &phone);
+
+        assert_eq!(titles(&merged), ["Podcast", "Chapter 3", "Chapter 5", "Chapter 4"]);
+        // Both sides gave their new entry the same id
+        assert_ne!(merged.log.entries()[2].id(), merged.log.entries()[3].id());
+    }
+
+    #[test]
+    fn reconciling_keeps_removals_from_both_sides() {
+        let base = sample();
+        let mut laptop = base.clone();
+        laptop.log.remove_entry(laptop.log.entries()[0].id());
+        let mut phone = base.clone();
+        phone.log.remove_entry(phone.log.entries()[1].id());
+
+        assert!(laptop.reconcile(Some(&base), &phone).log.entries().is_empty());
+        assert_eq!(titles(&laptop.reconcile(Some(&base), &base)), ["Chapter 3"]);
+    }
+
+    #[test]
+    fn reconciling_takes_edited_goals_and_renumbers_new_ones() {
+        let base = sample();
+        let mut laptop = base.clone();
+        let goal = laptop.user.goals()[1].clone();
+        laptop.user.mark_goal_as_completed(&goal);
+        let mut phone = base.clone();
+        phone.user.import_goals(vec![Goal::from_data("Read a novel".to_string(), false, None)]);
+        laptop.user.import_goals(vec![Goal::from_data("Watch a series".to_string(), false, None)]);
+
+        let merged = laptop.reconcile(Some(&base), &phone);
+        let goals = merged.user.goals();
+
+        assert_eq!(goals.iter().map(Goal::title).collect::<Vec<_>>(), ["", "Read a novel", "Reach 100 hours", "Watch a series"]);
+        assert!(goals[2].completed());
+        let mut ids: Vec<_> = goals.iter().map(Goal::id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), goals.len());
+    }
+
+    #[test]
+    fn goals_added_after_reconciling_get_a_free_id() {
+        let mut base = sample();
+        base.user.add_goal();
+        let mut laptop = base.clone();
+        laptop.user.add_goal();
+        let mut phone = base.clone();
+        let goal = phone.user.goals()[1].clone();
+        phone.user.mark_goal_as_completed(&goal);
+
+        // The edited goal comes last, after the one added on the laptop
+        let mut merged = phone.reconcile(Some(&base), &laptop);
+        let ids: Vec<usize> = merged.user.goals().iter().map(Goal::id).collect();
+        assert_eq!(ids, [0, 2, 3, 1]);
+
+        merged.user.add_goal();
+        let target = merged.user.goals()[1].clone();
+        merged.user.remove_goal(&target);
+
+        let ids: Vec<usize> = merged.user.goals().iter().map(Goal::id).collect();
+        assert_eq!(ids, [0, 3, 1, 4]);
+    }
+
+    #[test]
+    fn reconciling_prefers_local_profile_changes() {
+        let base = sample();
+        let mut laptop = base.clone();
+        laptop.user.set_vocab_size(1200);
+        let mut phone = base.clone();
+        phone.user.set_profile(User {
+            name: "Ana".to_string(),
+            ..User::default()
+        });
+
+        let merged = laptop.reconcile(Some(&base), &phone);
+
+        assert_eq!(merged.user.diagnostics().vocab_size, 1200);
+        assert_eq!(merged.user.profile().name, "Ana");
+    }
+
+    #[test]
+    fn reconciling_keeps_sessions_that_look_the_same() {
+        let base = sample();
+        let mut laptop = base.clone();
+        laptop.log.add(entry("Chapter 4"));
+        laptop.log.add(entry("Chapter 4"));
+        let mut phone = base.clone();
+        phone.log.add(entry("Chapter 4"));
+
+        let merged = laptop.reconcile(Some(&base), &phone);
+        assert_eq!(titles(&merged), ["Podcast", "Chapter 3", "Chapter 4", "Chapter 4", "Chapter 4"]);
+
+        // Removing one of the copies on the laptop leaves the others
+        let mut laptop = merged.clone();
+        let id = laptop.log.entries()[2].id();
+        laptop.log.remove_entry(id);
+        assert_eq!(titles(&laptop.reconcile(Some(&merged), &merged)), ["Podcast", "Chapter 3", "Chapter 4", "Chapter 4"]);
+    }
+
+    #[test]
+    fn reconciling_keeps_can_do_ticks_from_both_sides() {
+        let at = |day: u32| chrono::DateTime::parse_from_rfc3339(&format!("2026-10-{day:02}T20:00:00+02:00")).unwrap();
+        let base = sample();
+        let mut laptop = base.clone();
+        laptop.user.check_can_do("reading-a1-1", Some("Spanish"), true, at(3));
+        let mut phone = base.clone();
+        phone.user.check_can_do("reading-a1-2", Some("Spanish"), true, at(4));
+
+        let merged = laptop.reconcile(Some(&base), &phone);
+        let ticked: Vec<&str> = merged.user.profile().can_do.iter().map(|check| check.id.as_str()).collect();
+
+        assert_eq!(ticked, ["reading-a1-1", "reading-a1-2"]);
+    }
+
+    #[test]
+    fn reconciling_without_a_base_keeps_everything() {
+        let mut laptop = DataFile::new(LogData::new(), UserProfile::new());
+        laptop.log.import_entries(vec![entry("Chapter 4")]);
+
+        let merged = laptop.reconcile(None, &sample());
+
+        assert_eq!(titles(&merged), ["Podcast", "Chapter 3", "Chapter 4"]);
+        assert_eq!(merged.user.goals().len(), 2);
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// A calendar day as entered in the dashboard. Fields stay zero until the
+/// user fills them in, which is why this isn't a `NaiveDate`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug, Default)]
+pub struct Date {
+    pub day: usize,
+    pub month: usize,
+    pub year: usize,
+}
+
+impl Date {
+    pub fn from_naive(date: NaiveDate) -> Self {
+        Self {
+            day: date.day() as usize,
+            month: date.month() as usize,
+            year: date.year() as usize,
+        }
+    }
+
+    /// Returns `None` for dates that were never filled in.
+    pub fn to_naive(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_naive_dates() {
+        let naive = NaiveDate::from_ymd_opt(2023, 4, 3).unwrap();
+        let date = Date::from_naive(naive);
+
+        assert_eq!(date, Date { day: 3, month: 4, year: 2023 });
+        assert_eq!(date.to_naive(), Some(naive));
+    }
+
+    #[test]
+    fn unset_date_has_no_naive_date() {
+        assert_eq!(Date::default().to_naive(), None);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Cursor, Read};
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+use crate::date::Date;
+use crate::log::{Entry, EntryType};
+use crate::import::sqlite::{Database, SqliteError};
+
+/// Title given to the per-day entries, also used to recognise them on a re-run.
+pub const ANKI_ENTRY_TITLE: &str = "Anki Reviews";
+
+// Anki calls a card mature once its interval reaches three weeks
+const MATURE_INTERVAL_DAYS: i64 = 21;
+const REVIEW_CARD_TYPE: i64 = 2;
+
+// Newest collection format first, older exports ship a stub `collection.anki2`
+// next to the real data so that outdated clients show an upgrade notice
+const COLLECTION_FILES: [&str; 3] = ["collection.anki21b", "collection.anki21", "collection.anki2"];
+
+#[derive(Debug)]
+pub enum AnkiError {
+    Archive(String),
+    MissingCollection,
+    Database(SqliteError),
+}
+
+impl fmt::Display for AnkiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnkiError::Archive(reason) => write!(f, "could not unpack the export: {reason}"),
+            AnkiError::MissingCollection => write!(f, "the export contains no collection"),
+            AnkiError::Database(error) => write!(f, "could not read the collection: {error}"),
+        }
+    }
+}
+
+impl From<SqliteError> for AnkiError {
+    fn from(error: SqliteError) -> Self {
+        AnkiError::Database(error)
+    }
+}
+
+pub struct AnkiImport {
+    /// One `EntryType::FlashCard` entry per day with reviews.
+    pub entries: Vec<Entry>,
+    pub mature_cards: usize,
+}
+
+/// Reads an `.apkg` or `.colpkg` export and sums up its review log.
+/// `day_of` tells which day a review counts for, going by the user's time
+/// zone and day boundary.
+pub fn read_package(package: &[u8], day_of: impl Fn(DateTime<Utc>) -> NaiveDate) -> Result<AnkiImport, AnkiError> {
+    let collection = extract_collection(package)?;
+    let database = Database::open(&collection)?;
+
+    Ok(AnkiImport {
+        entries: review_entries(&database, day_of)?,
+        mature_cards: mature_cards(&database)?,
+    })
+}
+
+fn extract_collection(package: &[u8]) -> Result<Vec<u8>, AnkiError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(package))
+        .map_err(|error| AnkiError::Archive(error.to_string()))?;
+
+    for name in COLLECTION_FILES {
+        let Ok(mut file) = archive.by_name(name) else {
+            continue;
+        };
+
+        let mut data = vec![];
+        file.read_to_end(&mut data)
+            .map_err(|error| AnkiError::Archive(error.to_string()))?;
+
+        if name.ends_with('b') {
+            let mut decoder = ruzstd::StreamingDecoder::new(data.as_slice())
+                .map_err(|error| AnkiError::Archive(error.to_string()))?;
+            let mut decompressed = vec![];
+            decoder
+                .read_to_end(&mut decompressed)
+                .map_err(|error| AnkiError::Archive(error.to_string()))?;
+            return Ok(decompressed);
+        }
+
+        return Ok(data);
+    }
+
+    Err(AnkiError::MissingCollection)
+}
+
+fn review_entries(database: &Database, day_of: impl Fn(DateTime<Utc>) -> NaiveDate) -> Result<Vec<Entry>, AnkiError> {
+    let revlog = database.table("revlog")?;
+    let id = revlog.column("id").unwrap_or(0);
+    let time = revlog.column("time").unwrap_or(7);
+
+    let mut per_day: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    for row in revlog.rows.iter() {
+        // Review ids are the millisecond timestamps of the review
+        let reviewed_at = row.values.get(id).and_then(|value| value.as_i64()).unwrap_or(row.rowid);
+        let millis = row.values.get(time).and_then(|value| value.as_i64()).unwrap_or_default();
+
+        if let Some(reviewed_at) = Utc.timestamp_millis_opt(reviewed_at).single() {
+            *per_day.entry(day_of(reviewed_at)).or_default() += millis.max(0);
+        }
+    }
+
+    Ok(per_day
+        .into_iter()
+        .map(|(day, millis)| ((millis as f64 / 60_000.0).round() as usize, day))
+        .filter(|(minutes, _)| *minutes > 0)
+        .map(|(minutes, day)| {
+            Entry::from_data(ANKI_ENTRY_TITLE.to_string(), EntryType::FlashCard, minutes, Date::from_naive(day))
+        })
+        .collect())
+}
+
+fn mature_cards(database: &Database) -> Result<usize, AnkiError> {
+    let cards = database.table("cards")?;
+    let card_type = cards.column("type").unwrap_or(6);
+    let interval = cards.column("ivl").unwrap_or(9);
+
+    Ok(cards
+        .rows
+        .iter()
+        .filter(|row| {
+            let value = |column: usize| row.values.get(column).and_then(|value| value.as_i64());
+            value(card_type) == Some(REVIEW_CARD_TYPE)
+                && value(interval).unwrap_or_default() >= MATURE_INTERVAL_DAYS
+        })
+        .count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Three days of reviews (60, 60 and 50 minutes) and 30 cards of which 4 are mature.
+    // Both packages also contain an empty `collection.anki2` stub like Anki writes.
+    const APKG: &[u8] = include_bytes!("../../tests/fixtures/reviews.apkg");
+    const COLPKG: &[u8] = include_bytes!("../../tests/fixtures/reviews.colpkg");
+
+    fn utc_day(time: DateTime<Utc>) -> NaiveDate {
+        time.date_naive()
+    }
+
+    fn assert_reviews(imported: &AnkiImport) {
+        let days: Vec<(Option<NaiveDate>, usize)> = imported
+            .entries
+            .iter()
+            .map(|entry| (entry.date().to_naive(), entry.minutes()))
+            .collect();
+
+        assert_eq!(
+            days,
+            vec![
+                (NaiveDate::from_ymd_opt(2023, 10, 1), 60),
+                (NaiveDate::from_ymd_opt(2023, 10, 2), 60),
+                (NaiveDate::from_ymd_opt(2023, 10, 3), 50),
+            ]
+        );
+        assert!(imported.entries.iter().all(|entry| entry.entry_type() == &EntryType::FlashCard));
+        assert_eq!(imported.mature_cards, 4);
+    }
+
+    #[test]
+    fn reads_apkg_exports() {
+        assert_reviews(&read_package(APKG, utc_day).unwrap());
+    }
+
+    #[test]
+    fn reads_zstd_compressed_colpkg_exports() {
+        assert_reviews(&read_package(COLPKG, utc_day).unwrap());
+    }
+
+    #[test]
+    fn reviews_count_for_the_day_given() {
+        let first = NaiveDate::from_ymd_opt(2023, 10, 1).unwrap();
+        let imported = read_package(APKG, |_| first).unwrap();
+
+        assert_eq!(imported.entries.len(), 1);
+        assert_eq!(imported.entries[0].minutes(), 170);
+    }
+
+    #[test]
+    fn truncated_collections_are_errors() {
+        let collection = extract_collection(APKG).unwrap();
+        for length in (100..collection.len()).step_by(509) {
+            let truncated = &collection[..length];
+            let Ok(database) = Database::open(truncated) else {
+                continue;
+            };
+            assert!(review_entries(&database, utc_day).is_err() || mature_cards(&database).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_packages() {
+        assert!(matches!(read_package(b"not a zip", utc_day), Err(AnkiError::Archive(_))));
+    }
+}
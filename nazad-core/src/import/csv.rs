@@ -0,0 +1,272 @@
+use std::fmt;
+
+use crate::date::Date;
+use crate::log::{Entry, EntryType};
+use crate::import::{parse_date, parse_duration_minutes, to_minutes};
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Field {
+    Title,
+    Duration,
+    Date,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Title => write!(f, "Title"),
+            Field::Duration => write!(f, "Duration"),
+            Field::Date => write!(f, "Date"),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum RowError {
+    Unmapped(Field),
+    MissingValue(Field),
+    BadDate(String),
+    BadDuration(String),
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowError::Unmapped(field) => write!(f, "No column selected for {field}"),
+            RowError::MissingValue(field) => write!(f, "{field} is empty"),
+            RowError::BadDate(raw) => write!(f, "Unrecognised date \"{raw}\""),
+            RowError::BadDuration(raw) => write!(f, "Unrecognised duration \"{raw}\""),
+        }
+    }
+}
+
+/// What a bare number in the duration column counts. Durations with a unit,
+/// like `1h 30m` or `1:30`, are read the same either way.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum DurationUnit {
+    #[default]
+    Minutes,
+    Hours,
+}
+
+impl DurationUnit {
+    pub const ALL: [DurationUnit; 2] = [DurationUnit::Minutes, DurationUnit::Hours];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DurationUnit::Minutes => "Minutes",
+            DurationUnit::Hours => "Hours",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|unit| unit.name() == name)
+    }
+
+    fn parse(&self, raw: &str) -> Option<usize> {
+        match (self, raw.trim().replace(',', ".").parse::<f64>()) {
+            (DurationUnit::Hours, Ok(hours)) => to_minutes(hours * 60.0),
+            _ => parse_duration_minutes(raw),
+        }
+    }
+}
+
+/// Which column feeds which `Entry` field. The type column is optional,
+/// rows without one are logged as `EntryType::Other`.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ColumnMapping {
+    pub title: Option<usize>,
+    pub entry_type: Option<usize>,
+    pub duration: Option<usize>,
+    pub duration_unit: DurationUnit,
+    pub date: Option<usize>,
+    pub date_format: Option<&'static str>,
+}
+
+impl ColumnMapping {
+    /// Preselects columns whose header looks like one of the fields, and
+    /// hours as the unit of a duration column named like hours.
+    pub fn guess(headers: &[String]) -> Self {
+        let find = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|header| names.contains(&header.trim().to_lowercase().as_str()))
+        };
+
+        let duration = find(&["duration", "time", "minutes", "hours", "length"]);
+        let duration_unit = match duration.map(|column| headers[column].to_lowercase()) {
+            Some(header) if header.contains("hour") => DurationUnit::Hours,
+            _ => DurationUnit::Minutes,
+        };
+
+        Self {
+            title: find(&["title", "name", "description", "activity", "resource"]),
+            entry_type: find(&["type", "entry type", "category", "skill", "kind"]),
+            duration,
+            duration_unit,
+            date: find(&["date", "day", "start date", "when"]),
+            date_format: None,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct ParsedRow {
+    pub cells: Vec<String>,
+    pub result: Result<Entry, RowError>,
+}
+
+/// Reads a CSV file with a header row. The delimiter is picked from `,`, `;`
+/// and tabs by counting them in the header line.
+pub fn read_table(raw: &str) -> Result<CsvTable, csv::Error> {
+    let header_line = raw.lines().next().unwrap_or_default();
+    let delimiter = [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|delimiter| header_line.bytes().filter(|b| b == delimiter).count())
+        .unwrap_or(b',');
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(raw.as_bytes());
+
+    let headers = reader.headers()?.iter().map(String::from).collect();
+    let rows = reader
+        .records()
+        .map(|record| record.map(|record| record.iter().map(String::from).collect()))
+        .collect::<Result<Vec<Vec<String>>, csv::Error>>()?;
+
+    Ok(CsvTable { headers, rows })
+}
+
+pub fn parse_rows(table: &CsvTable, mapping: &ColumnMapping) -> Vec<ParsedRow> {
+    table
+        .rows
+        .iter()
+        .map(|cells| ParsedRow {
+            cells: cells.clone(),
+            result: parse_row(cells, mapping),
+        })
+        .collect()
+}
+
+fn parse_row(cells: &[String], mapping: &ColumnMapping) -> Result<Entry, RowError> {
+    let cell = |column: Option<usize>, field: Field| -> Result<&str, RowError> {
+        let column = column.ok_or(RowError::Unmapped(field))?;
+        match cells.get(column).map(|value| value.trim()) {
+            Some(value) if !value.is_empty() => Ok(value),
+            _ => Err(RowError::MissingValue(field)),
+        }
+    };
+
+    let title = cell(mapping.title, Field::Title)?;
+
+    let raw_date = cell(mapping.date, Field::Date)?;
+    let date = parse_date(raw_date, mapping.date_format)
+        .ok_or_else(|| RowError::BadDate(raw_date.to_string()))?;
+
+    let raw_duration = cell(mapping.duration, Field::Duration)?;
+    let minutes = mapping
+        .duration_unit
+        .parse(raw_duration)
+        .ok_or_else(|| RowError::BadDuration(raw_duration.to_string()))?;
+
+    let entry_type = mapping
+        .entry_type
+        .and_then(|column| cells.get(column))
+        .and_then(|value| EntryType::from_name(value))
+        .unwrap_or(EntryType::Other);
+
+    Ok(Entry::from_data(title.to_string(), entry_type, minutes, Date::from_naive(date)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const SPREADSHEET: &str = "Date;Activity;Type;Duration\n\
+        03.04.2023;El Principito;reading;1h 30m\n\
+        04.04.2023;Podcast;Listening;45\n\
+        someday;Podcast;Listening;45\n\
+        05.04.2023;Tutor;Speaking;\n";
+
+    #[test]
+    fn detects_the_delimiter() {
+        let table = read_table(SPREADSHEET).unwrap();
+        assert_eq!(table.headers, vec!["Date", "Activity", "Type", "Duration"]);
+        assert_eq!(table.rows.len(), 4);
+
+        let table = read_table("a,b\n1,2\n").unwrap();
+        assert_eq!(table.rows, vec![vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn guesses_the_mapping_from_headers() {
+        let table = read_table(SPREADSHEET).unwrap();
+        let mapping = ColumnMapping::guess(&table.headers);
+
+        assert_eq!(mapping.date, Some(0));
+        assert_eq!(mapping.title, Some(1));
+        assert_eq!(mapping.entry_type, Some(2));
+        assert_eq!(mapping.duration, Some(3));
+    }
+
+    #[test]
+    fn parses_rows_and_reports_errors() {
+        let table = read_table(SPREADSHEET).unwrap();
+        let rows = parse_rows(&table, &ColumnMapping::guess(&table.headers));
+
+        let first = rows[0].result.as_ref().unwrap();
+        assert_eq!(first.title(), "El Principito");
+        assert_eq!(first.entry_type(), &EntryType::Reading);
+        assert_eq!(first.minutes(), 90);
+        assert_eq!(first.date().to_naive(), NaiveDate::from_ymd_opt(2023, 4, 3));
+
+        assert!(rows[1].result.is_ok());
+        assert_eq!(rows[2].result, Err(RowError::BadDate("someday".to_string())));
+        assert_eq!(rows[3].result, Err(RowError::MissingValue(Field::Duration)));
+    }
+
+    #[test]
+    fn reads_an_hours_column_in_hours() {
+        let table = read_table("Date,Activity,Hours\n2023-04-03,Podcast,1.5\n2023-04-04,Tutor,45m\n").unwrap();
+        let mapping = ColumnMapping::guess(&table.headers);
+        assert_eq!(mapping.duration_unit, DurationUnit::Hours);
+
+        let rows = parse_rows(&table, &mapping);
+        assert_eq!(rows[0].result.as_ref().unwrap().minutes(), 90);
+        assert_eq!(rows[1].result.as_ref().unwrap().minutes(), 45);
+
+        let mapping = ColumnMapping {
+            duration_unit: DurationUnit::Minutes,
+            ..mapping
+        };
+        assert_eq!(parse_rows(&table, &mapping)[0].result.as_ref().unwrap().minutes(), 2);
+    }
+
+    #[test]
+    fn unmapped_fields_are_errors_except_the_type() {
+        let table = read_table(SPREADSHEET).unwrap();
+        let mapping = ColumnMapping {
+            entry_type: None,
+            ..ColumnMapping::guess(&table.headers)
+        };
+        let rows = parse_rows(&table, &mapping);
+        assert_eq!(rows[0].result.as_ref().unwrap().entry_type(), &EntryType::Other);
+
+        let mapping = ColumnMapping {
+            title: None,
+            ..ColumnMapping::guess(&table.headers)
+        };
+        let rows = parse_rows(&table, &mapping);
+        assert_eq!(rows[0].result, Err(RowError::Unmapped(Field::Title)));
+    }
+}
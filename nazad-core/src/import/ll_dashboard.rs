@@ -0,0 +1,255 @@
+//! Importer for data exported from Destaq's ll-dashboard.
+//!
+//! The export is a JSON document holding a list of activities and a list of
+//! goals. Field names differ between versions of ll-dashboard, so every field
+//! is looked up under each of its known spellings, and a bare list of
+//! activities is accepted as well. Rows are read one by one: an activity
+//! without a readable date or duration is skipped and counted instead of
+//! failing the whole file.
+//!
+//! The spellings haven't been checked against a real export yet, the fixture
+//! in `tests/fixtures` is written by hand. An anonymised export belongs next
+//! to it, and the spellings it doesn't use can go then.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de::Error;
+use serde_json::{Map, Value};
+
+use crate::date::Date;
+use crate::log::Entry;
+use crate::user::Goal;
+use crate::import::{guess_entry_type, parse_date, parse_duration_minutes};
+
+const ACTIVITY_LISTS: [&str; 5] = ["activities", "entries", "logs", "log", "data"];
+const GOAL_LISTS: [&str; 1] = ["goals"];
+
+const ACTIVITY_NAME: [&str; 5] = ["name", "title", "description", "activity", "resource"];
+const ACTIVITY_CATEGORY: [&str; 5] = ["category", "type", "activity_type", "skill", "kind"];
+const ACTIVITY_MINUTES: [&str; 5] = ["minutes", "duration", "time", "amount", "length"];
+const ACTIVITY_DATE: [&str; 5] = ["date", "day", "created", "timestamp", "created_at"];
+
+const GOAL_NAME: [&str; 5] = ["name", "title", "goal", "text", "description"];
+const GOAL_COMPLETED: [&str; 5] = ["completed", "done", "achieved", "finished", "complete"];
+const GOAL_DEADLINE: [&str; 5] = ["deadline", "date", "due", "due_date", "target_date"];
+
+// Timestamps above this are taken as milliseconds, below as seconds
+const MILLISECOND_TIMESTAMPS: i64 = 100_000_000_000;
+
+pub struct LlDashboardImport {
+    pub entries: Vec<Entry>,
+    pub goals: Vec<Goal>,
+    /// Activities that were dropped for lacking a readable date or duration.
+    pub skipped: usize,
+}
+
+/// `day_of` tells which day a moment given as a timestamp or with a UTC
+/// offset counts for, in the user's time zone and by their day boundary.
+/// Dates written without an offset are taken as they are.
+pub fn read_export(raw: &str, day_of: impl Fn(DateTime<Utc>) -> NaiveDate) -> serde_json::Result<LlDashboardImport> {
+    let (activities, goals) = match serde_json::from_str::<Value>(raw)? {
+        Value::Array(activities) => (activities, vec![]),
+        Value::Object(export) => {
+            let list = |names: &[&str]| match field(&export, names) {
+                Some(Value::Array(list)) => list.clone(),
+                _ => vec![],
+            };
+            let (activities, goals) = (list(&ACTIVITY_LISTS), list(&GOAL_LISTS));
+            let known = ACTIVITY_LISTS.iter().chain(&GOAL_LISTS).any(|name| export.contains_key(*name));
+            if !known {
+                return Err(serde_json::Error::custom("no activities or goals found"));
+            }
+            (activities, goals)
+        }
+        _ => return Err(serde_json::Error::custom("expected an object or a list of activities")),
+    };
+
+    let total = activities.len();
+    let entries: Vec<Entry> = activities.iter().filter_map(|activity| convert_activity(activity, &day_of)).collect();
+    let goals = goals.iter().filter_map(|goal| convert_goal(goal, &day_of)).collect();
+
+    Ok(LlDashboardImport {
+        skipped: total - entries.len(),
+        entries,
+        goals,
+    })
+}
+
+/// The value of the first of `names` present and not null.
+fn field<'a>(object: &'a Map<String, Value>, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| object.get(*name).filter(|value| !value.is_null()))
+}
+
+fn text(object: &Map<String, Value>, names: &[&str]) -> Option<String> {
+    match field(object, names)? {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// Dates are written out, or Unix timestamps in seconds or milliseconds.
+fn read_date(value: &Value, day_of: impl Fn(DateTime<Utc>) -> NaiveDate) -> Option<NaiveDate> {
+    match value {
+        Value::String(raw) => match DateTime::parse_from_rfc3339(raw.trim()) {
+            Ok(date_time) => Some(day_of(date_time.to_utc())),
+            Err(_) => parse_date(raw, None),
+        },
+        Value::Number(number) => {
+            let timestamp = number.as_i64().or_else(|| number.as_f64().map(|timestamp| timestamp as i64))?;
+            let date_time = if timestamp.abs() >= MILLISECOND_TIMESTAMPS {
+                DateTime::from_timestamp_millis(timestamp)
+            } else {
+                DateTime::from_timestamp(timestamp, 0)
+            };
+            date_time.map(day_of)
+        }
+        _ => None,
+    }
+}
+
+fn convert_activity(activity: &Value, day_of: impl Fn(DateTime<Utc>) -> NaiveDate) -> Option<Entry> {
+    let activity = activity.as_object()?;
+    let date = read_date(field(activity, &ACTIVITY_DATE)?, day_of)?;
+    // ll-dashboard counts time in minutes, so bare numbers are taken as such
+    let minutes = match field(activity, &ACTIVITY_MINUTES)? {
+        Value::Number(number) => number.as_f64().filter(|minutes| *minutes >= 0.0)?.round() as usize,
+        Value::String(raw) => parse_duration_minutes(raw)?,
+        _ => return None,
+    };
+    let category = text(activity, &ACTIVITY_CATEGORY).unwrap_or_default();
+    let title = text(activity, &ACTIVITY_NAME)
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| category.clone());
+
+    Some(Entry::from_data(title, guess_entry_type(&category), minutes, Date::from_naive(date)))
+}
+
+fn convert_goal(goal: &Value, day_of: impl Fn(DateTime<Utc>) -> NaiveDate) -> Option<Goal> {
+    let goal = goal.as_object()?;
+    let title = text(goal, &GOAL_NAME).filter(|name| !name.trim().is_empty())?;
+    let completed = match field(goal, &GOAL_COMPLETED) {
+        Some(Value::Bool(completed)) => *completed,
+        Some(Value::Number(number)) => number.as_i64() == Some(1),
+        Some(Value::String(raw)) => matches!(raw.to_lowercase().as_str(), "true" | "yes" | "1" | "done"),
+        _ => false,
+    };
+    let deadline = field(goal, &GOAL_DEADLINE).and_then(|deadline| read_date(deadline, day_of));
+
+    Some(Goal::from_data(title, completed, deadline))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, NaiveDate};
+
+    use crate::log::EntryType;
+    use crate::settings::Settings;
+
+    fn utc_day(time: DateTime<Utc>) -> NaiveDate {
+        time.date_naive()
+    }
+
+    #[test]
+    fn reads_activities_and_goals() {
+        let raw = r#"{
+            "activities": [
+                {"name": "Dreaming Spanish", "category": "Watching", "minutes": 40, "date": "2023-05-01"},
+                {"title": "", "type": "Reading", "duration": "1h", "day": "02/05/2023"},
+                {"name": "Broken", "category": "Anki", "minutes": "soon", "date": "2023-05-03"}
+            ],
+            "goals": [
+                {"name": "Reach 300 hours", "completed": true, "deadline": "2023-12-31"},
+                {"goal": "Read a novel", "done": 0},
+                {"name": " "}
+            ]
+        }"#;
+
+        let imported = read_export(raw, utc_day).unwrap();
+
+        assert_eq!(imported.entries.len(), 2);
+        assert_eq!(imported.skipped, 1);
+
+        let watching = &imported.entries[0];
+        assert_eq!(watching.title(), "Dreaming Spanish");
+        assert_eq!(watching.entry_type(), &EntryType::Listening);
+        assert_eq!(watching.minutes(), 40);
+
+        let reading = &imported.entries[1];
+        assert_eq!(reading.title(), "Reading");
+        assert_eq!(reading.minutes(), 60);
+        assert_eq!(reading.date().to_naive(), NaiveDate::from_ymd_opt(2023, 5, 2));
+
+        assert_eq!(imported.goals.len(), 2);
+        assert!(imported.goals[0].completed());
+        assert_eq!(imported.goals[0].date().year, 2023);
+        assert_eq!(imported.goals[1].title(), "Read a novel");
+        assert!(!imported.goals[1].completed());
+    }
+
+    #[test]
+    fn reads_a_bare_list_of_activities() {
+        let imported = read_export(r#"[{"activity": "Italki", "skill": "speaking", "time": 60, "date": "2023-05-01"}]"#, utc_day).unwrap();
+
+        assert_eq!(imported.entries.len(), 1);
+        assert_eq!(imported.entries[0].entry_type(), &EntryType::Speaking);
+        assert!(imported.goals.is_empty());
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(read_export("not json", utc_day).is_err());
+        assert!(read_export("42", utc_day).is_err());
+        assert!(read_export(r#"{"name": "Something else"}"#, utc_day).is_err());
+    }
+
+    #[test]
+    fn reads_the_fixture() {
+        let imported = read_export(include_str!("../../tests/fixtures/ll-dashboard.json"), utc_day).unwrap();
+
+        assert_eq!(imported.entries.len(), 4);
+        assert_eq!(imported.skipped, 2);
+        assert_eq!(imported.entries[0].entry_type(), &EntryType::Listening);
+        // Seconds and milliseconds since 1970
+        assert_eq!(imported.entries[2].date().to_naive(), NaiveDate::from_ymd_opt(2023, 5, 3));
+        assert_eq!(imported.entries[3].date().to_naive(), NaiveDate::from_ymd_opt(2023, 5, 4));
+        assert_eq!(imported.goals.len(), 2);
+        assert!(imported.goals[1].completed());
+    }
+
+    #[test]
+    fn timestamps_count_for_the_local_day() {
+        // 01:30 UTC is still the evening before in New York, and before a 4am day boundary in Berlin
+        let raw = r#"[
+            {"name": "Podcast", "category": "Listening", "minutes": 30, "date": 1683077400},
+            {"name": "Book", "category": "Reading", "minutes": 30, "date": "2023-05-03T01:30:00Z"}
+        ]"#;
+        let settings = &Settings {
+            day_boundary_hour: 4,
+            ..Default::default()
+        };
+        let in_zone = |hours: i32| {
+            let offset = FixedOffset::east_opt(hours * 3600).unwrap();
+            move |time: DateTime<Utc>| settings.day_of(time.with_timezone(&offset).naive_local())
+        };
+
+        let new_york = read_export(raw, in_zone(-4)).unwrap();
+        let berlin = read_export(raw, in_zone(2)).unwrap();
+        let tokyo = read_export(raw, in_zone(9)).unwrap();
+
+        let days = |imported: &LlDashboardImport| -> Vec<String> {
+            imported.entries.iter().map(|entry| entry.date().to_naive().unwrap().to_string()).collect()
+        };
+        assert_eq!(days(&new_york), ["2023-05-02", "2023-05-02"]);
+        assert_eq!(days(&berlin), ["2023-05-02", "2023-05-02"]);
+        assert_eq!(days(&tokyo), ["2023-05-03", "2023-05-03"]);
+    }
+
+    #[test]
+    fn takes_the_first_spelling_present() {
+        // Both `name` and `title` are set
+        let imported = read_export(r#"[{"name": "Podcast", "title": "Other", "type": "Listening", "minutes": 30, "date": "2023-05-01"}]"#, utc_day).unwrap();
+
+        assert_eq!(imported.entries[0].title(), "Podcast");
+    }
+}
@@ -0,0 +1,209 @@
+pub mod anki;
+pub mod csv;
+pub mod ll_dashboard;
+pub mod time_tracker;
+pub mod timeclock;
+pub mod sqlite;
+
+use chrono::NaiveDate;
+
+use crate::log::EntryType;
+
+/// Date formats tried in order when the user leaves the format on "Auto".
+/// Day-first formats come before month-first ones, so `03/04/2023` is read
+/// as the 3rd of April unless a format is picked explicitly.
+pub const DATE_FORMATS: [(&str, &str); 9] = [
+    ("YYYY-MM-DD", "%Y-%m-%d"),
+    ("DD.MM.YYYY", "%d.%m.%Y"),
+    ("DD/MM/YYYY", "%d/%m/%Y"),
+    ("MM/DD/YYYY", "%m/%d/%Y"),
+    ("YYYY/MM/DD", "%Y/%m/%d"),
+    ("DD-MM-YYYY", "%d-%m-%Y"),
+    ("Mon DD, YYYY", "%b %d, %Y"),
+    ("DD Mon YYYY", "%d %b %Y"),
+    ("YYYYMMDD", "%Y%m%d"),
+];
+
+/// Maps activity names used by other trackers onto an `EntryType`, falling
+/// back to `EntryType::Other` when nothing matches.
+pub fn guess_entry_type(raw: &str) -> EntryType {
+    if let Some(entry_type) = EntryType::from_name(raw) {
+        return entry_type;
+    }
+
+    let raw = raw.to_lowercase();
+    let matches = |keywords: &[&str]| keywords.iter().any(|keyword| raw.contains(keyword));
+
+    if matches(&["anki", "flashcard", "srs", "memrise", "vocab"]) {
+        EntryType::FlashCard
+    } else if matches(&["listen", "podcast", "watch", "video", "youtube", "tv", "audio", "film", "movie"]) {
+        EntryType::Listening
+    } else if matches(&["read", "book", "manga", "article", "novel"]) {
+        EntryType::Reading
+    } else if matches(&["speak", "tutor", "conversation", "italki", "talk"]) {
+        EntryType::Speaking
+    } else if matches(&["writ", "journal", "essay"]) {
+        EntryType::Writing
+    } else {
+        EntryType::Other
+    }
+}
+
+/// Parses `raw` with the given chrono format, or with each of `DATE_FORMATS`
+/// if none is given. Trailing times like `2023-04-03 12:30` are ignored.
+pub fn parse_date(raw: &str, format: Option<&str>) -> Option<NaiveDate> {
+    let raw = raw.trim();
+    let day = match raw.split_once(['T', ' ']) {
+        Some((day, rest)) if rest.contains(':') => day,
+        _ => raw,
+    };
+
+    match format {
+        Some(format) => NaiveDate::parse_from_str(day, format).ok(),
+        None => DATE_FORMATS
+            .iter()
+            .find_map(|(_, format)| NaiveDate::parse_from_str(day, format).ok()),
+    }
+}
+
+/// Parses a duration into whole minutes. Understands plain minutes (`45`),
+/// clock notation (`1:30`, `01:30:00`), unit suffixes (`1.5h`, `1h 30m`,
+/// `90 min`, `2 hours`) and decimal commas (`1,5 h`).
+pub fn parse_duration_minutes(raw: &str) -> Option<usize> {
+    let raw = raw.trim().to_lowercase().replace(',', ".");
+    if raw.is_empty() {
+        return None;
+    }
+
+    if raw.contains(':') {
+        return parse_clock_duration(&raw);
+    }
+
+    if let Ok(minutes) = raw.parse::<f64>() {
+        return to_minutes(minutes);
+    }
+
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut unit = String::new();
+    let mut found = false;
+
+    // Walk number/unit pairs such as `1h 30m`, flushing each pair once the next number starts
+    for c in raw.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_digit() || c == '.' {
+            if !unit.is_empty() {
+                total += unit_value(&number, &unit)?;
+                found = true;
+                number.clear();
+                unit.clear();
+            }
+            number.push(c);
+        } else if c.is_alphabetic() {
+            unit.push(c);
+        } else if !unit.is_empty() {
+            total += unit_value(&number, &unit)?;
+            found = true;
+            number.clear();
+            unit.clear();
+        }
+    }
+
+    if !number.is_empty() || !found {
+        return None;
+    }
+
+    to_minutes(total)
+}
+
+fn parse_clock_duration(raw: &str) -> Option<usize> {
+    let parts = raw
+        .split(':')
+        .map(|part| part.trim().parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()?;
+
+    match parts.as_slice() {
+        [hours, minutes] => to_minutes(hours * 60.0 + minutes),
+        [hours, minutes, seconds] => to_minutes(hours * 60.0 + minutes + seconds / 60.0),
+        _ => None,
+    }
+}
+
+fn unit_value(number: &str, unit: &str) -> Option<f64> {
+    let value = number.parse::<f64>().ok()?;
+
+    match unit {
+        "h" | "hr" | "hrs" | "hour" | "hours" | "std" => Some(value * 60.0),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(value),
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(value / 60.0),
+        _ => None,
+    }
+}
+
+pub(crate) fn to_minutes(minutes: f64) -> Option<usize> {
+    if minutes.is_finite() && minutes >= 0.0 {
+        Some(minutes.round() as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn parses_common_date_formats() {
+        assert_eq!(parse_date("2023-04-03", None), date(2023, 4, 3));
+        assert_eq!(parse_date("03.04.2023", None), date(2023, 4, 3));
+        assert_eq!(parse_date("Apr 03, 2023", None), date(2023, 4, 3));
+        assert_eq!(parse_date("20230403", None), date(2023, 4, 3));
+        assert_eq!(parse_date("2023-04-03 21:15", None), date(2023, 4, 3));
+        assert_eq!(parse_date("2023-04-03T21:15:00", None), date(2023, 4, 3));
+        assert_eq!(parse_date("yesterday", None), None);
+    }
+
+    #[test]
+    fn ambiguous_dates_are_day_first_unless_a_format_is_given() {
+        assert_eq!(parse_date("03/04/2023", None), date(2023, 4, 3));
+        assert_eq!(parse_date("03/04/2023", Some("%m/%d/%Y")), date(2023, 3, 4));
+        assert_eq!(parse_date("2023-04-03", Some("%m/%d/%Y")), None);
+    }
+
+    #[test]
+    fn parses_duration_notations() {
+        assert_eq!(parse_duration_minutes("45"), Some(45));
+        assert_eq!(parse_duration_minutes("1:30"), Some(90));
+        assert_eq!(parse_duration_minutes("01:30:30"), Some(91));
+        assert_eq!(parse_duration_minutes("1.5h"), Some(90));
+        assert_eq!(parse_duration_minutes("1,5 h"), Some(90));
+        assert_eq!(parse_duration_minutes("1h30m"), Some(90));
+        assert_eq!(parse_duration_minutes("1h 30m"), Some(90));
+        assert_eq!(parse_duration_minutes("90 min"), Some(90));
+        assert_eq!(parse_duration_minutes("2 hours"), Some(120));
+        assert_eq!(parse_duration_minutes("90s"), Some(2));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert_eq!(parse_duration_minutes(""), None);
+        assert_eq!(parse_duration_minutes("-5"), None);
+        assert_eq!(parse_duration_minutes("a while"), None);
+        assert_eq!(parse_duration_minutes("2 hours 15"), None);
+        assert_eq!(parse_duration_minutes("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn guesses_entry_types_from_activity_names() {
+        assert_eq!(guess_entry_type("Writing"), EntryType::Writing);
+        assert_eq!(guess_entry_type("Anki"), EntryType::FlashCard);
+        assert_eq!(guess_entry_type("Watching YouTube"), EntryType::Listening);
+        assert_eq!(guess_entry_type("Manga"), EntryType::Reading);
+        assert_eq!(guess_entry_type("italki lesson"), EntryType::Speaking);
+        assert_eq!(guess_entry_type("Journal"), EntryType::Writing);
+        assert_eq!(guess_entry_type("Grammar drills"), EntryType::Other);
+    }
+}
@@ -0,0 +1,478 @@
+//! Minimal read-only SQLite reader.
+//!
+//! Just enough of the file format to walk table b-trees of a database that
+//! was written out completely, like the collection inside an Anki export.
+//! Indexes, WAL files and writing are not supported. Pulling in a C SQLite
+//! is not an option for the wasm build.
+
+use std::collections::HashSet;
+use std::fmt;
+
+const HEADER: &[u8] = b"SQLite format 3\0";
+const LEAF_TABLE_PAGE: u8 = 0x0D;
+const INTERIOR_TABLE_PAGE: u8 = 0x05;
+// SQLite itself refuses databases with less usable space per page
+const MIN_USABLE_SIZE: usize = 480;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum SqliteError {
+    NotADatabase,
+    Corrupt(String),
+    MissingTable(String),
+}
+
+impl fmt::Display for SqliteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqliteError::NotADatabase => write!(f, "not an SQLite database"),
+            SqliteError::Corrupt(reason) => write!(f, "corrupt database: {reason}"),
+            SqliteError::MissingTable(name) => write!(f, "table \"{name}\" not found"),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, SqliteError>;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            Value::Real(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Row {
+    pub rowid: i64,
+    pub values: Vec<Value>,
+}
+
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column.eq_ignore_ascii_case(name))
+    }
+}
+
+pub struct Database<'a> {
+    data: &'a [u8],
+    page_size: usize,
+    usable_size: usize,
+}
+
+impl<'a> Database<'a> {
+    pub fn open(data: &'a [u8]) -> Result<Self> {
+        if data.len() < 100 || !data.starts_with(HEADER) {
+            return Err(SqliteError::NotADatabase);
+        }
+
+        let page_size = match read_u16(data, 16)? {
+            1 => 65536,
+            size if size >= 512 && size.is_power_of_two() => size as usize,
+            size => return Err(corrupt(format!("invalid page size {size}"))),
+        };
+        let usable_size = page_size
+            .checked_sub(data[20] as usize)
+            .filter(|usable| *usable >= MIN_USABLE_SIZE)
+            .ok_or_else(|| corrupt("too many reserved bytes per page"))?;
+
+        Ok(Self {
+            data,
+            page_size,
+            usable_size,
+        })
+    }
+
+    /// Reads every row of a table. An `INTEGER PRIMARY KEY` column is stored
+    /// as the rowid, so its `Value::Null` is swapped for the rowid here.
+    pub fn table(&self, name: &str) -> Result<Table> {
+        let schema = self.rows(1)?;
+        let definition = schema
+            .iter()
+            .find(|row| {
+                row.values.first().and_then(Value::as_str) == Some("table")
+                    && row.values.get(1).and_then(Value::as_str).map(|table| table.eq_ignore_ascii_case(name)) == Some(true)
+            })
+            .ok_or_else(|| SqliteError::MissingTable(name.to_string()))?;
+
+        let root = definition
+            .values
+            .get(3)
+            .and_then(Value::as_i64)
+            .ok_or_else(|| corrupt(format!("no root page for {name}")))?;
+        let sql = definition.values.get(4).and_then(Value::as_str).unwrap_or_default();
+        let (columns, primary_key) = parse_columns(sql);
+
+        let mut rows = self.rows(root as usize)?;
+        if let Some(key) = primary_key {
+            for row in rows.iter_mut() {
+                if let Some(value @ Value::Null) = row.values.get_mut(key) {
+                    *value = Value::Integer(row.rowid);
+                }
+            }
+        }
+
+        Ok(Table { columns, rows })
+    }
+
+    fn page(&self, number: usize) -> Result<&'a [u8]> {
+        let start = number
+            .checked_sub(1)
+            .and_then(|index| index.checked_mul(self.page_size))
+            .ok_or_else(|| corrupt(format!("page {number} referenced")))?;
+
+        start
+            .checked_add(self.page_size)
+            .and_then(|end| self.data.get(start..end))
+            .ok_or_else(|| corrupt(format!("page {number} out of bounds")))
+    }
+
+    /// Walks the b-tree from `root`. Every page may only be reached once, so
+    /// a page pointing back up the tree can't send this round in circles.
+    fn rows(&self, root: usize) -> Result<Vec<Row>> {
+        let mut rows = vec![];
+        let mut pending = vec![root];
+        let mut visited = HashSet::new();
+
+        while let Some(number) = pending.pop() {
+            if !visited.insert(number) {
+                return Err(corrupt(format!("page {number} referenced twice")));
+            }
+            let page = self.page(number)?;
+            // The first page starts with the 100 byte file header
+            let header = if number == 1 { 100 } else { 0 };
+            let kind = read_u8(page, header)?;
+            let cells = read_u16(page, header + 3)? as usize;
+
+            match kind {
+                LEAF_TABLE_PAGE => {
+                    for cell in 0..cells {
+                        let offset = read_u16(page, header + 8 + cell * 2)? as usize;
+                        rows.push(self.leaf_cell(page, offset)?);
+                    }
+                }
+                INTERIOR_TABLE_PAGE => {
+                    pending.push(read_u32(page, header + 8)? as usize);
+                    for cell in (0..cells).rev() {
+                        let offset = read_u16(page, header + 12 + cell * 2)? as usize;
+                        pending.push(read_u32(page, offset)? as usize);
+                    }
+                }
+                _ => return Err(corrupt(format!("page {number} is not a table page"))),
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn leaf_cell(&self, page: &[u8], offset: usize) -> Result<Row> {
+        let (payload_size, read) = read_varint(page, offset);
+        let (rowid, read_rowid) = read_varint(page, offset + read);
+        let start = offset + read + read_rowid;
+        // A payload can't be larger than the file it's stored in
+        let payload_size = usize::try_from(payload_size)
+            .ok()
+            .filter(|size| *size <= self.data.len())
+            .ok_or_else(|| corrupt("cell larger than the database"))?;
+
+        let local = self.local_payload_size(payload_size);
+        let mut payload = page
+            .get(start..start + local)
+            .ok_or_else(|| corrupt("cell out of bounds"))?
+            .to_vec();
+
+        if local < payload_size {
+            let mut overflow = read_u32(page, start + local)? as usize;
+            let mut visited = HashSet::new();
+            while overflow != 0 && payload.len() < payload_size {
+                if !visited.insert(overflow) {
+                    return Err(corrupt(format!("overflow page {overflow} referenced twice")));
+                }
+                let page = self.page(overflow)?;
+                let take = (payload_size - payload.len()).min(self.usable_size - 4);
+                payload.extend_from_slice(&page[4..4 + take]);
+                overflow = read_u32(page, 0)? as usize;
+            }
+        }
+
+        Ok(Row {
+            rowid: rowid as i64,
+            values: parse_record(&payload)?,
+        })
+    }
+
+    /// Bytes of a payload stored in the cell itself, the rest spills onto
+    /// overflow pages. `open` makes sure the usable size is large enough.
+    fn local_payload_size(&self, payload_size: usize) -> usize {
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        if payload_size <= max_local {
+            return payload_size;
+        }
+
+        let min_local = (usable - 12) * 32 / 255 - 23;
+        let local = min_local + (payload_size - min_local) % (usable - 4);
+        if local <= max_local {
+            local
+        } else {
+            min_local
+        }
+    }
+}
+
+fn parse_record(payload: &[u8]) -> Result<Vec<Value>> {
+    let (header_size, mut cursor) = read_varint(payload, 0);
+    let header_size = usize::try_from(header_size)
+        .ok()
+        .filter(|size| *size <= payload.len())
+        .ok_or_else(|| corrupt("record header out of bounds"))?;
+    let mut serial_types = vec![];
+
+    while cursor < header_size {
+        let (serial_type, read) = read_varint(payload, cursor);
+        serial_types.push(serial_type);
+        cursor += read;
+    }
+
+    let mut body = header_size;
+    let mut values = vec![];
+
+    for serial_type in serial_types {
+        let (value, size) = match serial_type {
+            0 => (Value::Null, 0),
+            1..=6 => {
+                let size = [1, 2, 3, 4, 6, 8][serial_type as usize - 1];
+                let bytes = payload
+                    .get(body..body.checked_add(size).ok_or_else(|| corrupt("record out of bounds"))?)
+                    .ok_or_else(|| corrupt("record out of bounds"))?;
+                (Value::Integer(read_signed(bytes)), size)
+            }
+            7 => {
+                let bytes = payload
+                    .get(body..body + 8)
+                    .ok_or_else(|| corrupt("record out of bounds"))?;
+                (Value::Real(f64::from_bits(read_signed(bytes) as u64)), 8)
+            }
+            8 => (Value::Integer(0), 0),
+            9 => (Value::Integer(1), 0),
+            serial_type if serial_type >= 12 => {
+                let size = ((serial_type - 12) / 2) as usize;
+                let bytes = payload
+                    .get(body..body.checked_add(size).ok_or_else(|| corrupt("record out of bounds"))?)
+                    .ok_or_else(|| corrupt("record out of bounds"))?;
+                if serial_type % 2 == 0 {
+                    (Value::Blob(bytes.to_vec()), size)
+                } else {
+                    (Value::Text(String::from_utf8_lossy(bytes).into_owned()), size)
+                }
+            }
+            _ => return Err(corrupt(format!("unknown serial type {serial_type}"))),
+        };
+
+        values.push(value);
+        body += size;
+    }
+
+    Ok(values)
+}
+
+/// Extracts the column names from a `CREATE TABLE` statement, along with the
+/// index of an `INTEGER PRIMARY KEY` column if there is one.
+fn parse_columns(sql: &str) -> (Vec<String>, Option<usize>) {
+    let Some(body) = sql.find('(').zip(sql.rfind(')')).map(|(open, close)| &sql[open + 1..close]) else {
+        return (vec![], None);
+    };
+
+    let mut definitions = vec![];
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in body.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                definitions.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    definitions.push(current);
+
+    let mut columns = vec![];
+    let mut primary_key = None;
+    for definition in definitions {
+        let definition = definition.trim();
+        let lowercase = definition.to_lowercase();
+        if ["primary key", "unique", "check", "foreign key", "constraint"]
+            .iter()
+            .any(|constraint| lowercase.starts_with(constraint))
+        {
+            continue;
+        }
+
+        let name = definition
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_matches(|c| c == '"' || c == '`' || c == '[' || c == ']');
+        if lowercase.contains("integer primary key") {
+            primary_key = Some(columns.len());
+        }
+        columns.push(name.to_string());
+    }
+
+    (columns, primary_key)
+}
+
+fn read_varint(data: &[u8], offset: usize) -> (u64, usize) {
+    let mut value = 0u64;
+    for index in 0..9 {
+        let byte = data.get(offset + index).copied().unwrap_or_default();
+        if index == 8 {
+            return ((value << 8) | byte as u64, 9);
+        }
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            return (value, index + 1);
+        }
+    }
+    (value, 9)
+}
+
+fn read_signed(bytes: &[u8]) -> i64 {
+    let negative = bytes.first().map(|byte| byte & 0x80 != 0).unwrap_or_default();
+    let start = if negative { -1i64 } else { 0 };
+    bytes.iter().fold(start, |acc, byte| (acc << 8) | *byte as i64)
+}
+
+fn corrupt(reason: impl Into<String>) -> SqliteError {
+    SqliteError::Corrupt(reason.into())
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    offset
+        .checked_add(N)
+        .and_then(|end| data.get(offset..end))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| corrupt(format!("read past the end of a page at {offset}")))
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8> {
+    read_bytes::<1>(data, offset).map(|[byte]| byte)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    read_bytes(data, offset).map(u16::from_be_bytes)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    read_bytes(data, offset).map(u32::from_be_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_varints() {
+        assert_eq!(read_varint(&[0x05], 0), (5, 1));
+        assert_eq!(read_varint(&[0x81, 0x00], 0), (128, 2));
+        assert_eq!(read_varint(&[0xFF; 9], 0), (u64::MAX, 9));
+    }
+
+    #[test]
+    fn reads_signed_integers() {
+        assert_eq!(read_signed(&[0x7F]), 127);
+        assert_eq!(read_signed(&[0xFF]), -1);
+        assert_eq!(read_signed(&[0xFF, 0x38]), -200);
+        assert_eq!(read_signed(&[0x00, 0x00, 0x01, 0x8A, 0xE8, 0x88, 0xE4, 0x00]), 1_696_118_400_000);
+    }
+
+    #[test]
+    fn parses_records() {
+        // Header of 5 bytes: its size, NULL, 1 byte int, text of length 2, constant one
+        let record = [5, 0, 1, 17, 9, 42, b'h', b'i'];
+        assert_eq!(
+            parse_record(&record).unwrap(),
+            vec![Value::Null, Value::Integer(42), Value::Text("hi".to_string()), Value::Integer(1)]
+        );
+    }
+
+    #[test]
+    fn parses_column_definitions() {
+        let (columns, primary_key) = parse_columns(
+            "CREATE TABLE revlog (id integer primary key, cid integer not null, \"time\" integer, CHECK (time > 0))",
+        );
+        assert_eq!(columns, vec!["id", "cid", "time"]);
+        assert_eq!(primary_key, Some(0));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(Database::open(b"PK\x03\x04"), Err(SqliteError::NotADatabase)));
+    }
+
+    /// A database of 512 byte pages whose first page holds `page` after the
+    /// file header.
+    fn database(page: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 512];
+        data[..HEADER.len()].copy_from_slice(HEADER);
+        data[16..18].copy_from_slice(&512u16.to_be_bytes());
+        data[100..100 + page.len()].copy_from_slice(page);
+        data
+    }
+
+    #[test]
+    fn rejects_invalid_page_sizes() {
+        let mut data = database(&[LEAF_TABLE_PAGE]);
+        data[20] = 100;
+        assert!(matches!(Database::open(&data), Err(SqliteError::Corrupt(_))));
+
+        data[16..18].copy_from_slice(&300u16.to_be_bytes());
+        assert!(matches!(Database::open(&data), Err(SqliteError::Corrupt(_))));
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        // One cell claimed, pointing at the last byte of the page
+        let data = database(&[LEAF_TABLE_PAGE, 0, 0, 0, 1, 0, 0, 0, 0x01, 0xFF]);
+        assert!(matches!(Database::open(&data).unwrap().table("revlog"), Err(SqliteError::Corrupt(_))));
+
+        let truncated = &data[..300];
+        assert!(matches!(Database::open(truncated).unwrap().table("revlog"), Err(SqliteError::Corrupt(_))));
+    }
+
+    #[test]
+    fn stops_at_pages_referencing_themselves() {
+        // An interior page without cells whose right-most child is itself
+        let data = database(&[INTERIOR_TABLE_PAGE, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(
+            Database::open(&data).unwrap().table("revlog").err(),
+            Some(SqliteError::Corrupt("page 1 referenced twice".to_string()))
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Importer for the detailed CSV reports of Toggl Track and Clockify.
+//!
+//! Both trackers know nothing about language learning, so the user sets up
+//! rules that turn projects, tags and descriptions into entry types and
+//! resources. They are kept in `Settings`, for the next report. Rows no rule
+//! matches fall back to guessing from the project.
+
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+use crate::log::{Entry, EntryType};
+use crate::import::{csv::{self, CsvTable}, guess_entry_type, parse_date, parse_duration_minutes};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Tracker {
+    Toggl,
+    Clockify,
+}
+
+impl Tracker {
+    /// Clockify writes `Start Date` and a `Duration (h)` or `Duration (decimal)`
+    /// column, Toggl has a plain `Duration`.
+    pub fn detect(headers: &[String]) -> Option<Self> {
+        let has = |name: &str| headers.iter().any(|header| header.trim() == name);
+
+        if has("Start Date") && (has("Duration (h)") || has("Duration (decimal)")) {
+            Some(Tracker::Clockify)
+        } else if has("Start date") && has("Duration") {
+            Some(Tracker::Toggl)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tracker::Toggl => "Toggl Track",
+            Tracker::Clockify => "Clockify",
+        }
+    }
+
+    // Toggl always exports ISO dates, Clockify uses the US format unless configured otherwise
+    fn date_format(&self) -> Option<&'static str> {
+        match self {
+            Tracker::Toggl => Some("%Y-%m-%d"),
+            Tracker::Clockify => Some("%m/%d/%Y"),
+        }
+    }
+
+    fn duration_column(&self) -> &'static str {
+        match self {
+            Tracker::Toggl => "duration",
+            Tracker::Clockify => "duration (h)",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum RuleField {
+    Project,
+    Tag,
+    Description,
+}
+
+pub const RULE_FIELDS: [RuleField; 3] = [RuleField::Project, RuleField::Tag, RuleField::Description];
+
+impl RuleField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleField::Project => "Project",
+            RuleField::Tag => "Tag",
+            RuleField::Description => "Description",
+        }
+    }
+}
+
+/// Applies `entry_type` and `resource` to rows whose project or description
+/// contains `pattern`, or that have a tag equal to it, ignoring case. Tags
+/// are short and match whole, so `read` doesn't catch `reading`.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct MappingRule {
+    pub field: RuleField,
+    pub pattern: String,
+    pub entry_type: EntryType,
+    pub resource: String,
+}
+
+impl MappingRule {
+    fn matches(&self, row: &TrackedRow) -> bool {
+        let pattern = self.pattern.trim().to_lowercase();
+        if pattern.is_empty() {
+            return false;
+        }
+
+        let contains = |value: &str| value.to_lowercase().contains(&pattern);
+        match self.field {
+            RuleField::Project => contains(&row.project),
+            RuleField::Description => contains(&row.description),
+            RuleField::Tag => row.tags.iter().any(|tag| tag.to_lowercase() == pattern),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TimeTrackerError {
+    Csv(::csv::Error),
+    UnknownFormat,
+}
+
+impl fmt::Display for TimeTrackerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeTrackerError::Csv(error) => write!(f, "{error}"),
+            TimeTrackerError::UnknownFormat => write!(f, "not a Toggl Track or Clockify detailed report"),
+        }
+    }
+}
+
+pub struct TimeTrackerImport {
+    pub entries: Vec<Entry>,
+    /// Rows without a readable start, end or duration.
+    pub skipped: usize,
+}
+
+struct TrackedRow {
+    project: String,
+    description: String,
+    tags: Vec<String>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    minutes: usize,
+}
+
+pub fn read_report(raw: &str) -> Result<(Tracker, CsvTable), TimeTrackerError> {
+    let table = csv::read_table(raw).map_err(TimeTrackerError::Csv)?;
+    let tracker = Tracker::detect(&table.headers).ok_or(TimeTrackerError::UnknownFormat)?;
+    Ok((tracker, table))
+}
+
+/// Converts every row of a report into an entry, using the first rule that matches.
+/// `day_of` tells which day a session counts for by its start, going by the
+/// user's day boundary.
+pub fn convert(tracker: Tracker, table: &CsvTable, rules: &[MappingRule], day_of: impl Fn(NaiveDateTime) -> NaiveDate) -> TimeTrackerImport {
+    let column = |name: &str| {
+        table
+            .headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    };
+    let columns = Columns {
+        project: column("project"),
+        description: column("description"),
+        tags: column("tags"),
+        start_date: column("start date"),
+        start_time: column("start time"),
+        end_date: column("end date"),
+        end_time: column("end time"),
+        duration: column(tracker.duration_column()),
+        decimal_duration: column("duration (decimal)"),
+    };
+
+    let rows: Vec<Option<TrackedRow>> = table
+        .rows
+        .iter()
+        .map(|cells| columns.read(tracker, cells))
+        .collect();
+    let skipped = rows.iter().filter(|row| row.is_none()).count();
+
+    let entries = rows
+        .into_iter()
+        .flatten()
+        .map(|row| {
+            let rule = rules.iter().find(|rule| rule.matches(&row));
+            let entry_type = match rule {
+                Some(rule) => rule.entry_type.clone(),
+                None => guess_entry_type(&format!("{} {}", row.project, row.tags.join(" "))),
+            };
+            let resource = rule
+                .map(|rule| rule.resource.trim().to_string())
+                .filter(|resource| !resource.is_empty())
+                .unwrap_or_else(|| row.project.clone());
+            let title = if row.description.is_empty() { row.project.clone() } else { row.description.clone() };
+
+            let entry = Entry::from_data(title, entry_type, row.minutes, Date::from_naive(day_of(row.start)))
+                .with_span(row.start, row.end);
+            if resource.is_empty() {
+                entry
+            } else {
+                entry.with_resource(resource)
+            }
+        })
+        .collect();
+
+    TimeTrackerImport {
+        entries,
+        skipped,
+    }
+}
+
+struct Columns {
+    project: Option<usize>,
+    description: Option<usize>,
+    tags: Option<usize>,
+    start_date: Option<usize>,
+    start_time: Option<usize>,
+    end_date: Option<usize>,
+    end_time: Option<usize>,
+    duration: Option<usize>,
+    decimal_duration: Option<usize>,
+}
+
+impl Columns {
+    fn read(&self, tracker: Tracker, cells: &[String]) -> Option<TrackedRow> {
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|column| cells.get(column))
+                .map(|value| value.trim())
+                .unwrap_or_default()
+        };
+        let date = |column: Option<usize>| {
+            let raw = cell(column);
+            parse_date(raw, tracker.date_format()).or_else(|| parse_date(raw, None))
+        };
+
+        let start = date(self.start_date)?.and_time(parse_time(cell(self.start_time))?);
+        let end = date(self.end_date)?.and_time(parse_time(cell(self.end_time))?);
+        let minutes = parse_report_duration(cell(self.duration))
+            .or_else(|| parse_report_duration(cell(self.decimal_duration)))
+            .unwrap_or_else(|| (end - start).num_minutes().max(0) as usize);
+
+        Some(TrackedRow {
+            project: cell(self.project).to_string(),
+            description: cell(self.description).to_string(),
+            tags: cell(self.tags)
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+            start,
+            end,
+            minutes,
+        })
+    }
+}
+
+/// Durations are `01:30:00`, or decimal hours like `1.50` in Clockify's
+/// decimal column and with its decimal duration format. A plain number is
+/// hours here, unlike in the CSV import where it's minutes.
+fn parse_report_duration(raw: &str) -> Option<usize> {
+    match raw.replace(',', ".").parse::<f64>() {
+        Ok(hours) if hours.is_finite() && hours >= 0.0 => Some((hours * 60.0).round() as usize),
+        Ok(_) => None,
+        Err(_) => parse_duration_minutes(raw),
+    }
+}
+
+fn parse_time(raw: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(raw.trim(), format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOGGL: &str = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount ()\n\
+        Me,me@example.com,,Spanish,,Podcast ep. 12,No,2023-10-01,09:00:00,2023-10-01,09:45:00,00:45:00,\"listening, podcast\",\n\
+        Me,me@example.com,,Spanish,,El Principito,No,2023-10-01,21:00:00,2023-10-01,21:30:00,00:30:00,,\n\
+        Me,me@example.com,,Spanish,,Broken,No,,,,,,,\n";
+
+    const CLOCKIFY: &str = "Project,Client,Description,Task,User,Group,Email,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h),Duration (decimal)\n\
+        Japanese Reading,,Manga,,Me,,me@example.com,,No,10/02/2023,11:00:00 PM,10/03/2023,12:30:00 AM,01:30:00,1.50\n\
+        Japanese Listening,,Anime,,Me,,me@example.com,,No,10/04/2023,08:00:00 PM,10/04/2023,09:00:00 PM,0.75,0.75\n";
+
+    // Exported with only the decimal duration column
+    const CLOCKIFY_DECIMAL: &str = "Project,Description,Start Date,Start Time,End Date,End Time,Duration (decimal)\n\
+        Japanese Reading,Manga,10/02/2023,09:00:00 PM,10/02/2023,11:00:00 PM,1.50\n";
+
+    fn podcast_rule() -> MappingRule {
+        MappingRule {
+            field: RuleField::Tag,
+            pattern: "Podcast".to_string(),
+            entry_type: EntryType::Listening,
+            resource: "Radio Ambulante".to_string(),
+        }
+    }
+
+    #[test]
+    fn detects_the_tracker() {
+        assert_eq!(read_report(TOGGL).unwrap().0, Tracker::Toggl);
+        assert_eq!(read_report(CLOCKIFY).unwrap().0, Tracker::Clockify);
+        assert!(matches!(read_report("Date,Title\n"), Err(TimeTrackerError::UnknownFormat)));
+    }
+
+    #[test]
+    fn applies_the_first_matching_rule() {
+        let (tracker, table) = read_report(TOGGL).unwrap();
+        let rules = vec![
+            podcast_rule(),
+            MappingRule {
+                field: RuleField::Description,
+                pattern: "principito".to_string(),
+                entry_type: EntryType::Reading,
+                resource: String::new(),
+            },
+        ];
+
+        let imported = convert(tracker, &table, &rules, |start| start.date());
+
+        assert_eq!(imported.skipped, 1);
+        let podcast = &imported.entries[0];
+        assert_eq!(podcast.title(), "Podcast ep. 12");
+        assert_eq!(podcast.entry_type(), &EntryType::Listening);
+        assert_eq!(podcast.resource(), Some("Radio Ambulante"));
+        assert_eq!(podcast.minutes(), 45);
+
+        let book = &imported.entries[1];
+        assert_eq!(book.entry_type(), &EntryType::Reading);
+        assert_eq!(book.resource(), Some("Spanish"));
+    }
+
+    #[test]
+    fn tags_match_whole() {
+        let (tracker, table) = read_report(TOGGL).unwrap();
+        let rule = |pattern: &str| MappingRule {
+            pattern: pattern.to_string(),
+            ..podcast_rule()
+        };
+
+        let partial = convert(tracker, &table, &[rule("pod")], |start| start.date());
+        let whole = convert(tracker, &table, &[rule(" PODCAST ")], |start| start.date());
+
+        assert_eq!(partial.entries[0].resource(), Some("Spanish"));
+        assert_eq!(whole.entries[0].resource(), Some("Radio Ambulante"));
+    }
+
+    #[test]
+    fn keeps_start_and_end_times_across_midnight() {
+        let (tracker, table) = read_report(CLOCKIFY).unwrap();
+
+        let imported = convert(tracker, &table, &[podcast_rule()], |start| start.date());
+
+        let manga = &imported.entries[0];
+        let (start, end) = manga.span().unwrap();
+        assert_eq!(start.to_string(), "2023-10-02 23:00:00");
+        assert_eq!(end.to_string(), "2023-10-03 00:30:00");
+        assert_eq!(manga.date().day, 2);
+        assert_eq!(manga.minutes(), 90);
+        // No rule matched, so the type is guessed from the project
+        assert_eq!(manga.entry_type(), &EntryType::Reading);
+    }
+
+    #[test]
+    fn late_sessions_count_for_the_day_before() {
+        let (tracker, table) = read_report(CLOCKIFY).unwrap();
+        let settings = crate::settings::Settings {
+            day_boundary_hour: 4,
+            ..Default::default()
+        };
+
+        let imported = convert(tracker, &table, &[], |start| settings.day_of(start));
+
+        // Started at 23:00 on the 2nd, still that day with or without the boundary
+        assert_eq!(imported.entries[0].date().day, 2);
+
+        let late = CLOCKIFY.replace("10/02/2023,11:00:00 PM", "10/03/2023,01:30:00 AM").replace("12:30:00 AM", "03:00:00 AM");
+        let (tracker, table) = read_report(&late).unwrap();
+        let imported = convert(tracker, &table, &[], |start| settings.day_of(start));
+
+        assert_eq!(imported.entries[0].date().day, 2);
+        assert_eq!(imported.entries[0].span().unwrap().0.to_string(), "2023-10-03 01:30:00");
+    }
+
+    #[test]
+    fn reads_decimal_hours() {
+        let (tracker, table) = read_report(CLOCKIFY).unwrap();
+        let imported = convert(tracker, &table, &[], |start| start.date());
+        assert_eq!(imported.entries[1].minutes(), 45);
+
+        let (tracker, table) = read_report(CLOCKIFY_DECIMAL).unwrap();
+        assert_eq!(tracker, Tracker::Clockify);
+        let imported = convert(tracker, &table, &[], |start| start.date());
+        assert_eq!(imported.entries[0].minutes(), 90);
+    }
+}
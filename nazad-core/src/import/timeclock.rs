@@ -0,0 +1,419 @@
+//! Reads and writes the timeclock format understood by hledger and ledger.
+//!
+//! Every entry becomes a check-in/check-out pair whose account is the entry
+//! type, followed by the resource if there is one:
+//!
+//! ```text
+//! i 2026-10-01 09:00:00 Listening:Podcast  Podcast ep. 12  ; id:4, language:Spanish, finished:
+//! o 2026-10-01 09:45:00
+//! ```
+//!
+//! What the format has no place for goes into hledger tags in the comment:
+//! the id, language, when the entry was logged and whether it finished the
+//! resource. Entries logged without a time of day start at midnight and are
+//! tagged `untimed`, so reading an exported file gives back the same log.
+//! Accounts that don't start with an entry type, like `Gardening`, are read
+//! as `Other` entries with the account as the resource.
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime};
+
+use crate::date::Date;
+use crate::log::{Entry, EntryType};
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const COMMENT: &str = "  ;";
+const ID_TAG: &str = "id";
+const LANGUAGE_TAG: &str = "language";
+const LOGGED_TAG: &str = "logged";
+const FINISHED_TAG: &str = "finished";
+const UNTIMED_TAG: &str = "untimed";
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum TimeclockError {
+    Syntax { line: usize, reason: String },
+    UnclosedCheckIn(usize),
+    CheckOutWithoutCheckIn(usize),
+}
+
+impl fmt::Display for TimeclockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeclockError::Syntax { line, reason } => write!(f, "line {line}: {reason}"),
+            TimeclockError::UnclosedCheckIn(line) => write!(f, "line {line}: check-in is never checked out"),
+            TimeclockError::CheckOutWithoutCheckIn(line) => write!(f, "line {line}: check-out without check-in"),
+        }
+    }
+}
+
+/// Writes entries as timeclock pairs. Entries without a valid date cannot be
+/// placed on the clock and are left out, their number is returned alongside.
+pub fn write(entries: &[Entry]) -> (String, usize) {
+    let mut output = String::new();
+    let mut skipped = 0;
+
+    for entry in entries {
+        let (start, end, untimed) = match entry.span() {
+            Some((start, end)) => (start, end, false),
+            None => match entry.date().to_naive() {
+                Some(day) => {
+                    let start = day.and_hms_opt(0, 0, 0).unwrap();
+                    (start, start + Duration::minutes(entry.minutes() as i64), true)
+                }
+                None => {
+                    skipped += 1;
+                    continue;
+                }
+            },
+        };
+
+        let mut account = entry.entry_type().name().to_string();
+        if let Some(resource) = entry.resource() {
+            account.push(':');
+            account.push_str(&single_spaced(resource));
+        }
+
+        output.push_str(&format!("i {} {}", start.format(DATE_TIME_FORMAT), account));
+        let title = single_line(entry.title());
+        if !title.is_empty() {
+            output.push_str("  ");
+            output.push_str(&title);
+        }
+        output.push_str(COMMENT);
+        output.push(' ');
+        output.push_str(&tags(entry, untimed).join(", "));
+        output.push('\n');
+        output.push_str(&format!("o {}\n", end.format(DATE_TIME_FORMAT)));
+    }
+
+    (output, skipped)
+}
+
+// A comma ends a tag's value, so languages lose theirs
+fn tags(entry: &Entry, untimed: bool) -> Vec<String> {
+    let mut tags = vec![format!("{ID_TAG}:{}", entry.id())];
+    if let Some(language) = entry.language() {
+        tags.push(format!("{LANGUAGE_TAG}:{}", single_spaced(&language.replace(',', " "))));
+    }
+    if let Some(logged_at) = entry.logged_at() {
+        tags.push(format!("{LOGGED_TAG}:{}", logged_at.to_rfc3339()));
+    }
+    if entry.finished() {
+        tags.push(format!("{FINISHED_TAG}:"));
+    }
+    if untimed {
+        tags.push(format!("{UNTIMED_TAG}:"));
+    }
+    tags
+}
+
+/// Reads check-in/check-out pairs as entries. `day_of` tells which day a
+/// session counts for by its check-in, going by the user's day boundary.
+/// Untimed entries keep the day they were written with.
+pub fn read(raw: &str, day_of: impl Fn(NaiveDateTime) -> NaiveDate) -> Result<Vec<Entry>, TimeclockError> {
+    let mut entries = vec![];
+    let mut open: Option<(usize, CheckIn)> = None;
+
+    for (index, line) in raw.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim_end();
+
+        let Some(kind) = line.chars().next() else {
+            continue;
+        };
+
+        match kind {
+            'i' | 'I' => {
+                if let Some((opened, _)) = open {
+                    return Err(TimeclockError::UnclosedCheckIn(opened));
+                }
+                open = Some((number, parse_check_in(number, &line[1..])?));
+            }
+            'o' | 'O' => {
+                let (_, check_in) = open.take().ok_or(TimeclockError::CheckOutWithoutCheckIn(number))?;
+                let end = parse_date_time(number, line[1..].trim_start())?.0;
+                entries.push(check_in.into_entry(end, &day_of));
+            }
+            // Comments and directives timeclock readers ignore as well
+            ';' | '#' | '*' => continue,
+            _ if line.trim().is_empty() => continue,
+            _ => {
+                return Err(TimeclockError::Syntax {
+                    line: number,
+                    reason: format!("unknown line \"{line}\""),
+                })
+            }
+        }
+    }
+
+    match open {
+        Some((opened, _)) => Err(TimeclockError::UnclosedCheckIn(opened)),
+        None => Ok(entries),
+    }
+}
+
+#[derive(Default)]
+struct Tags {
+    id: usize,
+    language: Option<String>,
+    logged_at: Option<DateTime<FixedOffset>>,
+    finished: bool,
+    untimed: bool,
+}
+
+struct CheckIn {
+    start: NaiveDateTime,
+    entry_type: EntryType,
+    resource: Option<String>,
+    title: String,
+    tags: Tags,
+}
+
+impl CheckIn {
+    fn into_entry(self, end: NaiveDateTime, day_of: impl Fn(NaiveDateTime) -> NaiveDate) -> Entry {
+        let minutes = (end - self.start).num_minutes().max(0) as usize;
+        let tags = self.tags;
+        let day = if tags.untimed { self.start.date() } else { day_of(self.start) };
+        let mut entry = Entry::from_data(self.title, self.entry_type, minutes, Date::from_naive(day)).with_id(tags.id);
+        if let Some(resource) = self.resource {
+            entry = entry.with_resource(resource);
+        }
+        if let Some(language) = tags.language {
+            entry = entry.with_language(language);
+        }
+        if let Some(logged_at) = tags.logged_at {
+            entry = entry.with_logged_at(logged_at);
+        }
+        if tags.finished {
+            entry = entry.with_finished();
+        }
+
+        if tags.untimed {
+            entry
+        } else {
+            entry.with_span(self.start, end)
+        }
+    }
+}
+
+// Tags are `name:value` pairs separated by commas, other comments are ignored.
+// Files written before tags were used carry a bare `untimed`.
+fn parse_tags(line: usize, comment: &str) -> Result<Tags, TimeclockError> {
+    let mut tags = Tags::default();
+    let invalid = |name: &str, value: &str| TimeclockError::Syntax {
+        line,
+        reason: format!("invalid {name} tag \"{value}\""),
+    };
+
+    for tag in comment.split(',') {
+        let (name, value) = tag.split_once(':').unwrap_or((tag, ""));
+        let value = value.trim();
+        match name.trim() {
+            ID_TAG => tags.id = value.parse().map_err(|_| invalid(ID_TAG, value))?,
+            LANGUAGE_TAG if !value.is_empty() => tags.language = Some(value.to_string()),
+            LOGGED_TAG => tags.logged_at = Some(DateTime::parse_from_rfc3339(value).map_err(|_| invalid(LOGGED_TAG, value))?),
+            FINISHED_TAG => tags.finished = true,
+            UNTIMED_TAG => tags.untimed = true,
+            _ => {}
+        }
+    }
+
+    Ok(tags)
+}
+
+fn parse_check_in(line: usize, rest: &str) -> Result<CheckIn, TimeclockError> {
+    let (start, rest) = parse_date_time(line, rest.trim_start())?;
+
+    // The comment is the last thing written, titles may hold a `  ;` of their own
+    let (rest, tags) = match rest.trim_end().rfind(COMMENT) {
+        Some(index) => (&rest[..index], parse_tags(line, &rest[index + COMMENT.len()..])?),
+        None => (rest, Tags::default()),
+    };
+
+    // The account ends at the first double space, everything after it is the description
+    let (account, title) = match rest.trim_start().split_once("  ") {
+        Some((account, title)) => (account, title.trim()),
+        None => (rest.trim(), ""),
+    };
+
+    // Accounts that aren't an entry type are kept whole as the resource of an
+    // `Other` entry, so nothing of them is lost
+    let (entry_type, resource) = match account.split_once(':') {
+        Some((entry_type, resource)) => (entry_type, Some(resource)),
+        None => (account, None),
+    };
+    let (entry_type, resource) = match EntryType::from_name(entry_type) {
+        Some(entry_type) => (entry_type, resource),
+        None => (EntryType::Other, Some(account).filter(|account| !account.is_empty())),
+    };
+
+    Ok(CheckIn {
+        start,
+        entry_type,
+        resource: resource.map(str::to_string),
+        title: title.to_string(),
+        tags,
+    })
+}
+
+fn parse_date_time(line: usize, rest: &str) -> Result<(NaiveDateTime, &str), TimeclockError> {
+    let mut parts = rest.splitn(3, ' ');
+    let date = parts.next().unwrap_or_default().replace('/', "-");
+    let time = parts.next().unwrap_or_default();
+    let remainder = parts.next().unwrap_or_default();
+
+    let raw = format!("{date} {time}");
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&raw, format).ok())
+        .map(|date_time| (date_time, remainder))
+        .ok_or_else(|| TimeclockError::Syntax {
+            line,
+            reason: format!("invalid date and time \"{raw}\""),
+        })
+}
+
+// Two spaces would end the account name early
+fn single_spaced(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn single_line(value: &str) -> String {
+    value.lines().collect::<Vec<&str>>().join(" ").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    fn date_of(start: NaiveDateTime) -> NaiveDate {
+        start.date()
+    }
+
+    fn october(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn round_trips_timed_and_untimed_entries() {
+        let podcast = Entry::from_data("Podcast ep. 12".to_string(), EntryType::Listening, 45, Date::from_naive(october(1)))
+            .with_resource("Radio  Ambulante:Season 2".to_string())
+            .with_span(october(1).and_hms_opt(9, 0, 0).unwrap(), october(1).and_hms_opt(9, 45, 0).unwrap());
+        let book = Entry::from_data("Chapter 3".to_string(), EntryType::Reading, 30, Date::from_naive(october(2)));
+        let marathon = Entry::from_data(String::new(), EntryType::Other, 1500, Date::from_naive(october(3)));
+
+        let (written, skipped) = write(&[podcast.clone(), book.clone(), marathon.clone()]);
+
+        assert_eq!(skipped, 0);
+        assert_eq!(
+            written.lines().take(2).collect::<Vec<&str>>(),
+            vec![
+                "i 2026-10-01 09:00:00 Listening:Radio Ambulante:Season 2  Podcast ep. 12  ; id:0",
+                "o 2026-10-01 09:45:00",
+            ]
+        );
+
+        let read_back = read(&written, date_of).unwrap();
+        assert_eq!(read_back[0], podcast.with_resource("Radio Ambulante:Season 2".to_string()));
+        assert_eq!(read_back[1], book);
+        assert_eq!(read_back[2], marathon);
+    }
+
+    #[test]
+    fn round_trips_every_field() {
+        let logged_at = DateTime::parse_from_rfc3339("2026-10-01T21:50:00+02:00").unwrap();
+        let entry = Entry::from_data("Chapter 3  ; the ending".to_string(), EntryType::Reading, 50, Date::from_naive(october(1)))
+            .with_id(7)
+            .with_resource("Cien años de soledad".to_string())
+            .with_span(october(1).and_hms_opt(21, 0, 0).unwrap(), october(1).and_hms_opt(21, 50, 0).unwrap())
+            .with_logged_at(logged_at)
+            .with_language("Spanish".to_string())
+            .with_finished();
+
+        let (written, _) = write(std::slice::from_ref(&entry));
+
+        assert_eq!(
+            written.lines().next().unwrap(),
+            "i 2026-10-01 21:00:00 Reading:Cien años de soledad  Chapter 3  ; the ending  ; id:7, language:Spanish, logged:2026-10-01T21:50:00+02:00, finished:"
+        );
+        assert_eq!(read(&written, date_of).unwrap(), vec![entry]);
+    }
+
+    #[test]
+    fn reads_untimed_entries_of_files_written_before_tags() {
+        let raw = "i 2026-10-02 00:00:00 Listening  ; untimed\n\
+            o 2026-10-02 00:30:00\n";
+
+        let entries = read(raw, date_of).unwrap();
+
+        assert_eq!(entries, vec![Entry::from_data(String::new(), EntryType::Listening, 30, Date::from_naive(october(2)))]);
+    }
+
+    #[test]
+    fn leaves_out_entries_without_a_date() {
+        let undated = Entry::from_data("Undated".to_string(), EntryType::Other, 10, Date::default());
+
+        let (written, skipped) = write(&[undated]);
+
+        assert!(written.is_empty());
+        assert_eq!(skipped, 1);
+    }
+
+    #[test]
+    fn reads_files_written_by_hand() {
+        let raw = "; my log\n\
+            i 2026/10/01 09:00 Speaking  italki\n\
+            o 2026/10/01 10:00\n\
+            \n\
+            i 2026-10-02 08:00:00 Gardening  Roses\n\
+            o 2026-10-02 08:20:00\n\
+            i 2026-10-03 08:00:00 Garden:Roses\n\
+            o 2026-10-03 08:20:00\n";
+
+        let entries = read(raw, date_of).unwrap();
+
+        assert_eq!(entries[0].entry_type(), &EntryType::Speaking);
+        assert_eq!(entries[0].title(), "italki");
+        assert_eq!(entries[0].minutes(), 60);
+        assert_eq!(entries[0].resource(), None);
+        assert_eq!(entries[1].entry_type(), &EntryType::Other);
+        assert_eq!(entries[1].resource(), Some("Gardening"));
+        assert_eq!(entries[2].resource(), Some("Garden:Roses"));
+
+        // Written back under `Other`, and read as the same entries again
+        let (written, _) = write(&entries);
+        assert!(written.contains("i 2026-10-02 08:00:00 Other:Gardening  Roses"));
+        assert_eq!(read(&written, date_of).unwrap(), entries);
+    }
+
+    #[test]
+    fn reports_unbalanced_clocks() {
+        assert_eq!(read("o 2026-10-01 09:00:00\n", date_of), Err(TimeclockError::CheckOutWithoutCheckIn(1)));
+        assert_eq!(
+            read("i 2026-10-01 09:00:00 Reading\ni 2026-10-01 10:00:00 Reading\n", date_of),
+            Err(TimeclockError::UnclosedCheckIn(1))
+        );
+        assert_eq!(read("i 2026-10-01 09:00:00 Reading\n", date_of), Err(TimeclockError::UnclosedCheckIn(1)));
+        assert!(matches!(read("i tomorrow Reading\n", date_of), Err(TimeclockError::Syntax { line: 1, .. })));
+    }
+
+    #[test]
+    fn late_sessions_count_for_the_day_before() {
+        let settings = Settings {
+            day_boundary_hour: 4,
+            ..Default::default()
+        };
+        let raw = "i 2026-10-02 01:30:00 Reading  Late chapter\n\
+            o 2026-10-02 02:15:00\n\
+            i 2026-10-02 00:00:00 Listening  ; untimed\n\
+            o 2026-10-02 00:30:00\n";
+
+        let entries = read(raw, |start| settings.day_of(start)).unwrap();
+
+        assert_eq!(entries[0].date().to_naive(), Some(october(1)));
+        assert_eq!(entries[0].span().unwrap().0, october(2).and_hms_opt(1, 30, 0).unwrap());
+        assert_eq!(entries[1].date().to_naive(), Some(october(2)));
+    }
+}
@@ -0,0 +1,212 @@
+//! Levels reached by cumulative hours, in the style of Dreaming Spanish's
+//! roadmap. A ladder is plain JSON naming the skills that count and the hours
+//! at which each level starts; the built-in ones live in `ladders/` and
+//! users can add their own.
+
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::log::{EntryType, LogData};
+
+const BUILT_IN: [&str; 1] = [include_str!("../ladders/dreaming-spanish.json")];
+
+/// Ladder shown until another one is picked in the settings.
+pub const DEFAULT_LADDER: &str = "Dreaming Spanish";
+
+/// Most hours a level may start at, far beyond any learner's lifetime.
+pub const MAX_HOURS: usize = 1_000_000;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Level {
+    pub name: String,
+    /// Hours at which the level is reached.
+    pub hours: usize,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Level {
+    /// Minutes at which the level is reached. Saturates, as ladders saved in
+    /// the settings weren't necessarily checked by `Ladder::from_json`.
+    pub fn minutes(&self) -> usize {
+        self.hours.saturating_mul(60)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Ladder {
+    pub name: String,
+    /// Entry types whose time counts towards the levels.
+    #[serde(default = "input_skills")]
+    pub skills: Vec<EntryType>,
+    /// Ascending by hours.
+    pub levels: Vec<Level>,
+}
+
+fn input_skills() -> Vec<EntryType> {
+    vec![EntryType::Listening, EntryType::Reading]
+}
+
+#[derive(Debug)]
+pub enum LadderError {
+    Json(serde_json::Error),
+    NoLevels,
+    /// The levels aren't in ascending order of hours.
+    Unordered(String),
+    /// A level starts beyond `MAX_HOURS`.
+    TooManyHours(String),
+}
+
+impl fmt::Display for LadderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LadderError::Json(error) => write!(f, "not a level ladder: {error}"),
+            LadderError::NoLevels => write!(f, "the ladder has no levels"),
+            LadderError::Unordered(level) => write!(f, "{level} needs more hours than the level before it"),
+            LadderError::TooManyHours(level) => write!(f, "{level} needs more than {MAX_HOURS} hours"),
+        }
+    }
+}
+
+impl Error for LadderError {}
+
+/// Where the logged hours stand on a ladder.
+#[derive(PartialEq, Clone, Debug)]
+pub struct LevelProgress {
+    pub minutes: usize,
+    /// `None` before the first level is reached.
+    pub current: Option<Level>,
+    /// `None` at the top of the ladder.
+    pub next: Option<Level>,
+    /// Share of the way from the current level to the next, from 0 to 1.
+    pub progress: f64,
+}
+
+impl Ladder {
+    pub fn from_json(json: &str) -> Result<Self, LadderError> {
+        let ladder: Ladder = serde_json::from_str(json).map_err(LadderError::Json)?;
+        if ladder.levels.is_empty() {
+            return Err(LadderError::NoLevels);
+        }
+        if let Some(pair) = ladder.levels.windows(2).find(|pair| pair[1].hours <= pair[0].hours) {
+            return Err(LadderError::Unordered(pair[1].name.clone()));
+        }
+        if let Some(level) = ladder.levels.iter().find(|level| level.hours > MAX_HOURS) {
+            return Err(LadderError::TooManyHours(level.name.clone()));
+        }
+        Ok(ladder)
+    }
+
+    pub fn built_in() -> Vec<Ladder> {
+        BUILT_IN
+            .iter()
+            .map(|json| Ladder::from_json(json).expect("built-in ladders are valid"))
+            .collect()
+    }
+
+    /// Progress from the time logged in `language`, or in any language for
+    /// `None`, see `Entry::counts_for`.
+    pub fn progress(&self, log: &LogData, language: Option<&str>) -> LevelProgress {
+        let minutes = log
+            .entries()
+            .iter()
+            .filter(|entry| self.skills.contains(entry.entry_type()))
+            .filter(|entry| entry.counts_for(language))
+            .map(|entry| entry.minutes())
+            .sum();
+
+        let reached = self.levels.iter().take_while(|level| level.minutes() <= minutes).count();
+        let current = reached.checked_sub(1).map(|index| self.levels[index].clone());
+        let next = self.levels.get(reached).cloned();
+        let progress = match (&current, &next) {
+            (_, None) => 1.0,
+            (from, Some(to)) => {
+                let start = from.as_ref().map(Level::minutes).unwrap_or(0);
+                (minutes - start) as f64 / to.minutes().saturating_sub(start).max(1) as f64
+            }
+        };
+
+        LevelProgress {
+            minutes,
+            current,
+            next,
+            progress,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::log::Entry;
+
+    fn log(entries: &[(EntryType, usize)]) -> LogData {
+        let mut log = LogData::new();
+        for (entry_type, minutes) in entries {
+            log.add(Entry::from_data("Session".to_string(), entry_type.clone(), *minutes, Date { day: 1, month: 10, year: 2026 }));
+        }
+        log
+    }
+
+    #[test]
+    fn built_in_ladders_are_valid() {
+        let ladders = Ladder::built_in();
+
+        assert_eq!(ladders[0].name, DEFAULT_LADDER);
+        assert_eq!(ladders[0].levels.len(), 7);
+    }
+
+    #[test]
+    fn counts_input_towards_the_next_level() {
+        let ladder = &Ladder::built_in()[0];
+        let progress = ladder.progress(&log(&[
+            (EntryType::Listening, 60 * 60),
+            (EntryType::Reading, 40 * 60),
+            (EntryType::Speaking, 500 * 60),
+        ]), None);
+
+        assert_eq!(progress.minutes, 100 * 60);
+        assert_eq!(progress.current.unwrap().name, "Level 2");
+        assert_eq!(progress.next.unwrap().name, "Level 3");
+        assert_eq!(progress.progress, 0.5);
+
+        let top = ladder.progress(&log(&[(EntryType::Listening, 2000 * 60)]), None);
+        assert_eq!(top.current.unwrap().name, "Level 7");
+        assert_eq!((top.next, top.progress), (None, 1.0));
+    }
+
+    #[test]
+    fn reads_custom_ladders() {
+        let ladder = Ladder::from_json(
+            r#"{"name": "Team", "skills": ["Speaking"], "levels": [{"name": "Talker", "hours": 10}, {"name": "Orator", "hours": 100}]}"#,
+        )
+        .unwrap();
+        let progress = ladder.progress(&log(&[(EntryType::Speaking, 5 * 60)]), None);
+
+        assert_eq!(progress.current, None);
+        assert_eq!(progress.progress, 0.5);
+
+        let unordered = r#"{"name": "Bad", "levels": [{"name": "A", "hours": 10}, {"name": "B", "hours": 5}]}"#;
+        assert!(matches!(Ladder::from_json(unordered), Err(LadderError::Unordered(level)) if level == "B"));
+        assert!(matches!(Ladder::from_json(r#"{"name": "Empty", "levels": []}"#), Err(LadderError::NoLevels)));
+        let huge = format!(r#"{{"name": "Huge", "levels": [{{"name": "Forever", "hours": {}}}]}}"#, usize::MAX);
+        assert!(matches!(Ladder::from_json(&huge), Err(LadderError::TooManyHours(level)) if level == "Forever"));
+    }
+
+    #[test]
+    fn counts_the_hours_of_one_language() {
+        let ladder = &Ladder::built_in()[0];
+        let mut log = log(&[(EntryType::Listening, 30 * 60)]);
+        let day = Date { day: 2, month: 10, year: 2026 };
+        log.add(Entry::from_data("Spanish".to_string(), EntryType::Listening, 40 * 60, day.clone()).with_language("Spanish".to_string()));
+        log.add(Entry::from_data("Russian".to_string(), EntryType::Listening, 100 * 60, day).with_language("Russian".to_string()));
+
+        // Entries without a language count for both
+        assert_eq!(ladder.progress(&log, Some("Spanish")).minutes, 70 * 60);
+        assert_eq!(ladder.progress(&log, Some("Russian")).minutes, 130 * 60);
+        assert_eq!(ladder.progress(&log, None).minutes, 170 * 60);
+    }
+}
@@ -0,0 +1,22 @@
+//! Domain model of Nazad: the study log, the user profile with its goals and
+//! the importers and exporters for other tools' formats. Kept free of any UI
+//! code so the dashboard and other frontends share one implementation.
+
+pub mod api;
+pub mod avatar;
+pub mod balance;
+pub mod cefr;
+pub mod data;
+pub mod date;
+pub mod import;
+pub mod ladder;
+pub mod log;
+pub mod profiles;
+pub mod projection;
+pub mod review;
+pub mod settings;
+pub mod stats;
+pub mod sync;
+pub mod user;
+pub mod webdav;
+pub mod wrapped;
@@ -0,0 +1,438 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::date::Date;
+
+pub const ENTRY_TYPES:  [&str; 6] = ["FlashCard", "Listening", "Reading", "Speaking", "Writing", "Other"];
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub enum EntryType {
+    FlashCard,
+    Listening,
+    Reading,
+    Speaking,
+    Writing,
+    Other
+}
+
+impl EntryType {
+    pub const ALL: [EntryType; 6] = [
+        EntryType::FlashCard,
+        EntryType::Listening,
+        EntryType::Reading,
+        EntryType::Speaking,
+        EntryType::Writing,
+        EntryType::Other,
+    ];
+
+    /// Looks up an entry type by its name in `ENTRY_TYPES`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "flashcard" => Some(EntryType::FlashCard),
+            "listening" => Some(EntryType::Listening),
+            "reading" => Some(EntryType::Reading),
+            "speaking" => Some(EntryType::Speaking),
+            "writing" => Some(EntryType::Writing),
+            "other" => Some(EntryType::Other),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryType::FlashCard => ENTRY_TYPES[0],
+            EntryType::Listening => ENTRY_TYPES[1],
+            EntryType::Reading => ENTRY_TYPES[2],
+            EntryType::Speaking => ENTRY_TYPES[3],
+            EntryType::Writing => ENTRY_TYPES[4],
+            EntryType::Other => ENTRY_TYPES[5],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Entry {
+    #[serde(default)]
+    id: usize,
+    title: String,
+    entry_type: EntryType,
+    minutes: usize,
+    date: Date,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    span: Option<(NaiveDateTime, NaiveDateTime)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    logged_at: Option<DateTime<FixedOffset>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    /// The resource was finished in this session.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    finished: bool,
+}
+
+impl Entry {
+    pub fn from_data(title: String, entry_type: EntryType, minutes: usize, date: Date) -> Self {
+        Self {
+            id: 0,
+            title,
+            entry_type,
+            minutes,
+            date,
+            resource: None,
+            span: None,
+            logged_at: None,
+            language: None,
+            finished: false,
+        }
+    }
+
+    /// Sets the id for entries that were stored before, new entries get theirs from `LogData`.
+    pub fn with_id(mut self, id: usize) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Names the book, podcast, deck etc. the time was spent on.
+    pub fn with_resource(mut self, resource: String) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Records when the session started and ended, for entries coming from time trackers.
+    pub fn with_span(mut self, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        self.span = Some((start, end));
+        self
+    }
+
+    /// Records when the entry was logged, in the time zone of the device
+    /// that logged it.
+    pub fn with_logged_at(mut self, logged_at: DateTime<FixedOffset>) -> Self {
+        self.logged_at = Some(logged_at);
+        self
+    }
+
+    /// The language studied, one of the profile's target languages.
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Marks the session as the one the resource was finished in.
+    pub fn with_finished(mut self) -> Self {
+        self.finished = true;
+        self
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn entry_type(&self) -> &EntryType {
+        &self.entry_type
+    }
+
+    pub fn minutes(&self) -> usize {
+        self.minutes
+    }
+
+    pub fn date(&self) -> &Date {
+        &self.date
+    }
+
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+
+    pub fn span(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        self.span
+    }
+
+    pub fn logged_at(&self) -> Option<DateTime<FixedOffset>> {
+        self.logged_at
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Whether the entry counts for `language`, or any language for `None`.
+    /// Entries logged without a language, from before languages were kept,
+    /// count for every language.
+    pub fn counts_for(&self, language: Option<&str>) -> bool {
+        language.is_none() || self.language.is_none() || self.language() == language
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The entry without its id, which differs between copies of a log.
+    /// Copies of the same session have the same content.
+    pub fn content(&self) -> Entry {
+        self.clone().with_id(0)
+    }
+
+    // Two entries are the same session if everything but the title's case and padding matches
+    fn is_duplicate_of(&self, other: &Entry) -> bool {
+        self.entry_type == other.entry_type
+            && self.minutes == other.minutes
+            && self.date == other.date
+            && self.span.map(|(start, _)| start) == other.span.map(|(start, _)| start)
+            && self.language == other.language
+            && self.title.trim().eq_ignore_ascii_case(other.title.trim())
+    }
+
+    fn is_same_day_as(&self, other: &Entry) -> bool {
+        self.entry_type == other.entry_type
+            && self.date == other.date
+            && self.title == other.title
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub duplicates: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct LogData {
+    entries: Vec<Entry>
+}
+
+impl Default for LogData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogData {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![]
+        }
+    }
+
+    /// Wraps entries that already carry their ids, e.g. ones loaded from a server.
+    pub fn from_entries(entries: Vec<Entry>) -> Self {
+        let mut log = Self { entries };
+        log.ensure_unique_ids();
+        log
+    }
+
+    pub fn add_entry(&mut self, title: String, entry_type: EntryType, minutes: usize, date: Date) {
+        self.add(Entry::from_data(title, entry_type, minutes, date));
+    }
+
+    /// Adds an entry as a new one, giving it a fresh id.
+    pub fn add(&mut self, mut entry: Entry) {
+        entry.id = self.next_entry_id();
+        self.entries.push(entry);
+    }
+
+    fn next_entry_id(&self) -> usize {
+        self.entries.iter().map(|entry| entry.id + 1).max().unwrap_or_default()
+    }
+
+    /// Gives entries that share an id a fresh one. Files written before
+    /// entries had ids load with every id set to zero.
+    pub fn ensure_unique_ids(&mut self) {
+        let mut seen = HashSet::new();
+        let mut next = self.next_entry_id();
+
+        for entry in self.entries.iter_mut() {
+            if !seen.insert(entry.id) {
+                entry.id = next;
+                next += 1;
+            }
+        }
+    }
+
+    pub fn remove_entry(&mut self, id: usize) {
+        self.entries.retain(|entry| entry.id != id);
+    }
+
+    /// Marks a resource as finished with its latest session, or not finished.
+    pub fn set_resource_finished(&mut self, resource: &str, entry_type: &EntryType, finished: bool) {
+        let mut sessions: Vec<&mut Entry> = self
+            .entries
+            .iter_mut()
+            .filter(|entry| entry.resource() == Some(resource) && entry.entry_type() == entry_type)
+            .collect();
+        for session in sessions.iter_mut() {
+            session.finished = false;
+        }
+        if let Some(latest) = sessions.into_iter().max_by_key(|entry| (entry.date.to_naive(), entry.id)) {
+            latest.finished = finished;
+        }
+    }
+
+    /// Appends all entries that are not already part of the log, including
+    /// ones repeated within `entries` itself.
+    pub fn import_entries(&mut self, entries: Vec<Entry>) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+
+        for entry in entries {
+            if self.contains(&entry) {
+                summary.duplicates += 1;
+            } else {
+                self.add(entry);
+                summary.added += 1;
+            }
+        }
+
+        summary
+    }
+
+    /// Imports per-day totals such as Anki reviews. Entries are matched by
+    /// title, type and date only, so re-running an import replaces the minutes
+    /// of days imported before instead of logging them twice.
+    pub fn import_daily_entries(&mut self, entries: Vec<Entry>) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+
+        for entry in entries {
+            match self.entries.iter_mut().find(|logged| logged.is_same_day_as(&entry)) {
+                Some(logged) if logged.minutes == entry.minutes => summary.duplicates += 1,
+                Some(logged) => {
+                    logged.minutes = entry.minutes;
+                    summary.updated += 1;
+                }
+                None => {
+                    self.add(entry);
+                    summary.added += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    pub fn contains(&self, entry: &Entry) -> bool {
+        self.entries.iter().any(|logged| logged.is_duplicate_of(entry))
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn day(day: u32) -> Date {
+        Date::from_naive(NaiveDate::from_ymd_opt(2023, 10, day).unwrap())
+    }
+
+    fn entry(title: &str, minutes: usize, date: Date) -> Entry {
+        Entry::from_data(title.to_string(), EntryType::Reading, minutes, date)
+    }
+
+    #[test]
+    fn entry_type_names_round_trip() {
+        for name in ENTRY_TYPES {
+            assert_eq!(EntryType::from_name(name).unwrap().name(), name);
+        }
+        assert_eq!(EntryType::from_name(" listening "), Some(EntryType::Listening));
+        assert_eq!(EntryType::from_name("Gardening"), None);
+    }
+
+    #[test]
+    fn import_skips_entries_already_logged() {
+        let mut log = LogData::new();
+        log.add_entry("Book".to_string(), EntryType::Reading, 30, day(1));
+
+        let summary = log.import_entries(vec![
+            entry(" book ", 30, day(1)),
+            entry("Book", 45, day(1)),
+            entry("Book", 30, day(2)),
+        ]);
+
+        assert_eq!(summary, ImportSummary { added: 2, updated: 0, duplicates: 1 });
+        assert_eq!(log.entries().len(), 3);
+    }
+
+    #[test]
+    fn import_skips_repeats_within_the_batch() {
+        let mut log = LogData::new();
+
+        let summary = log.import_entries(vec![entry("Book", 30, day(1)), entry("Book", 30, day(1))]);
+
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.duplicates, 1);
+    }
+
+    #[test]
+    fn sessions_with_different_start_times_are_not_duplicates() {
+        let date = NaiveDate::from_ymd_opt(2023, 10, 1).unwrap();
+        let morning = entry("Podcast", 30, day(1))
+            .with_span(date.and_hms_opt(9, 0, 0).unwrap(), date.and_hms_opt(9, 30, 0).unwrap());
+        let evening = entry("Podcast", 30, day(1))
+            .with_span(date.and_hms_opt(20, 0, 0).unwrap(), date.and_hms_opt(20, 30, 0).unwrap());
+
+        let mut log = LogData::new();
+        let summary = log.import_entries(vec![morning, evening]);
+
+        assert_eq!(summary.added, 2);
+    }
+
+    #[test]
+    fn logging_times_keep_their_offset() {
+        let logged_at = DateTime::parse_from_rfc3339("2023-10-02T01:30:00+02:00").unwrap();
+        let mut log = LogData::new();
+        log.add(entry("Podcast", 30, day(1)).with_logged_at(logged_at));
+
+        let json = serde_json::to_string(&log).unwrap();
+        let loaded: LogData = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains("2023-10-02T01:30:00+02:00"));
+        assert_eq!(loaded.entries()[0].logged_at().unwrap().offset().local_minus_utc(), 2 * 3600);
+        assert_eq!(loaded.entries()[0].content(), log.entries()[0].content());
+    }
+
+    #[test]
+    fn new_entries_get_unused_ids() {
+        let mut log = LogData::from_entries(vec![entry("Book", 30, day(1)).with_id(4)]);
+        log.add_entry("Podcast".to_string(), EntryType::Listening, 20, day(2));
+        log.import_entries(vec![entry("Book", 30, day(3))]);
+
+        let ids: Vec<usize> = log.entries().iter().map(Entry::id).collect();
+        assert_eq!(ids, vec![4, 5, 6]);
+
+        log.remove_entry(5);
+        assert_eq!(log.entries().len(), 2);
+    }
+
+    #[test]
+    fn repeated_ids_are_replaced() {
+        let log = LogData::from_entries(vec![entry("Book", 30, day(1)), entry("Book", 30, day(2)), entry("Book", 30, day(3))]);
+
+        let ids: Vec<usize> = log.entries().iter().map(Entry::id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn daily_import_updates_days_imported_before() {
+        let mut log = LogData::new();
+        log.import_daily_entries(vec![entry("Anki", 20, day(1)), entry("Anki", 10, day(2))]);
+
+        let summary = log.import_daily_entries(vec![
+            entry("Anki", 20, day(1)),
+            entry("Anki", 25, day(2)),
+            entry("Anki", 5, day(3)),
+        ]);
+
+        assert_eq!(summary, ImportSummary { added: 1, updated: 1, duplicates: 1 });
+        let minutes: Vec<usize> = log.entries().iter().map(Entry::minutes).collect();
+        assert_eq!(minutes, vec![20, 25, 5]);
+    }
+}
@@ -0,0 +1,109 @@
+//! Several people sharing one device. Each local profile has a data file of
+//! its own, with its own entries, goals and settings; this list only knows
+//! their names and which one is in use.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct LocalProfile {
+    /// Profile 0 keeps the data that existed before there were profiles.
+    pub id: usize,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ProfileList {
+    profiles: Vec<LocalProfile>,
+    current: usize,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        Self {
+            profiles: vec![LocalProfile {
+                id: 0,
+                name: String::new(),
+            }],
+            current: 0,
+        }
+    }
+}
+
+impl ProfileList {
+    pub fn profiles(&self) -> &[LocalProfile] {
+        &self.profiles
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Adds a profile and returns its id.
+    pub fn add(&mut self, name: &str) -> usize {
+        let id = self.profiles.iter().map(|profile| profile.id + 1).max().unwrap_or(0);
+        self.profiles.push(LocalProfile {
+            id,
+            name: name.to_string(),
+        });
+        id
+    }
+
+    pub fn rename(&mut self, id: usize, name: &str) {
+        if let Some(profile) = self.profiles.iter_mut().find(|profile| profile.id == id) {
+            profile.name = name.to_string();
+        }
+    }
+
+    /// Returns false for unknown ids.
+    pub fn select(&mut self, id: usize) -> bool {
+        if self.profiles.iter().any(|profile| profile.id == id) {
+            self.current = id;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes a profile other than the one in use. Its data file has to be
+    /// deleted as well, a profile added later may get the same id.
+    pub fn remove(&mut self, id: usize) -> bool {
+        if id == self.current {
+            return false;
+        }
+        let before = self.profiles.len();
+        self.profiles.retain(|profile| profile.id != id);
+        self.profiles.len() < before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_with_the_existing_data() {
+        let list = ProfileList::default();
+
+        assert_eq!(list.current(), 0);
+        assert_eq!(list.profiles().len(), 1);
+    }
+
+    #[test]
+    fn profiles_can_be_added_selected_and_removed() {
+        let mut list = ProfileList::default();
+        let mila = list.add("Mila");
+        let luka = list.add("Luka");
+
+        assert_eq!((mila, luka), (1, 2));
+        assert!(list.select(luka));
+        assert!(!list.select(7));
+        assert_eq!(list.current(), luka);
+
+        assert!(!list.remove(luka));
+        assert!(list.remove(mila));
+        assert_eq!(list.add("Ana"), 3);
+
+        list.rename(luka, "Luka K.");
+        assert_eq!(list.profiles()[1].name, "Luka K.");
+    }
+}
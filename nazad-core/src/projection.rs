@@ -0,0 +1,301 @@
+//! When will the next hour milestone be reached? The recent pace is an
+//! exponentially weighted average of the minutes per day, so the last weeks
+//! count most, and the spread of the days around it gives a range of dates
+//! rather than one.
+
+use chrono::{Duration, NaiveDate};
+
+use crate::log::{Entry, EntryType, LogData};
+
+/// Milestones offered until the user configures their own, in hours.
+pub const DEFAULT_MILESTONES: [usize; 3] = [500, 1000, 2000];
+/// More hours than a lifetime of study, larger milestones are typos.
+const MAX_MILESTONE: usize = 1_000_000;
+
+/// Days of history the pace is fitted on.
+const LOOKBACK_DAYS: i64 = 90;
+/// The weight of each day halves after about three weeks.
+const SMOOTHING: f64 = 2.0 / (30.0 + 1.0);
+/// Two-sided 90% interval of a normal distribution.
+const Z_90: f64 = 1.645;
+/// Projections further out than this aren't worth a date.
+const HORIZON_DAYS: f64 = 365.0 * 30.0;
+
+/// Which entries a projection covers.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Track {
+    All,
+    Language(String),
+    Skill(EntryType),
+}
+
+impl Track {
+    fn includes(&self, entry: &Entry) -> bool {
+        match self {
+            Track::All => true,
+            Track::Language(language) => entry.language() == Some(language.as_str()),
+            Track::Skill(skill) => entry.entry_type() == skill,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Track::All => "All",
+            Track::Language(language) => language,
+            Track::Skill(skill) => skill.name(),
+        }
+    }
+}
+
+/// Minutes per day, with a 90% range for where the true pace lies.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Pace {
+    pub minutes_per_day: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Pace {
+    /// Fits the pace on the minutes of consecutive days, oldest first.
+    pub fn of(daily_minutes: &[usize]) -> Self {
+        let Some((first, rest)) = daily_minutes.split_first() else {
+            return Self {
+                minutes_per_day: 0.0,
+                low: 0.0,
+                high: 0.0,
+            };
+        };
+
+        let mut mean = *first as f64;
+        let mut variance = 0.0;
+        for minutes in rest {
+            let deviation = *minutes as f64 - mean;
+            mean += SMOOTHING * deviation;
+            variance = (1.0 - SMOOTHING) * (variance + SMOOTHING * deviation * deviation);
+        }
+
+        // A weighted average of n days is as certain as a plain one of fewer days
+        let effective_days = (daily_minutes.len() as f64).min((2.0 - SMOOTHING) / SMOOTHING);
+        let margin = Z_90 * (variance / effective_days).sqrt();
+        Self {
+            minutes_per_day: mean,
+            low: (mean - margin).max(0.0),
+            high: mean + margin,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Outlook {
+    Reached(NaiveDate),
+    Expected {
+        date: NaiveDate,
+        earliest: NaiveDate,
+        /// `None` when the slow end of the pace never gets there.
+        latest: Option<NaiveDate>,
+    },
+    /// Nothing logged recently, or too far away to tell.
+    OutOfSight,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Milestone {
+    pub hours: usize,
+    pub outlook: Outlook,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Projection {
+    pub track: Track,
+    pub total_minutes: usize,
+    pub pace: Pace,
+    pub milestones: Vec<Milestone>,
+}
+
+impl Projection {
+    pub fn of(log: &LogData, track: Track, today: NaiveDate, milestone_hours: &[usize]) -> Self {
+        let mut entries: Vec<(NaiveDate, usize)> = log
+            .entries()
+            .iter()
+            .filter(|entry| track.includes(entry))
+            .filter_map(|entry| Some((entry.date().to_naive()?, entry.minutes())))
+            .filter(|(day, _)| *day <= today)
+            .collect();
+        entries.sort();
+        let total_minutes = entries.iter().map(|(_, minutes)| minutes).sum();
+
+        // Days before the first entry don't count as a slow start
+        let since = entries
+            .first()
+            .map(|(first, _)| (*first).max(today - Duration::days(LOOKBACK_DAYS - 1)))
+            .unwrap_or(today);
+        let mut daily = vec![0; ((today - since).num_days() + 1) as usize];
+        for (day, minutes) in entries.iter().filter(|(day, _)| *day >= since) {
+            daily[(*day - since).num_days() as usize] += minutes;
+        }
+        let pace = Pace::of(&daily);
+
+        let milestones = milestone_hours
+            .iter()
+            .map(|hours| Milestone {
+                hours: *hours,
+                // Settings synced from elsewhere aren't bounded by `parse_milestones`
+                outlook: outlook(&entries, total_minutes, hours.saturating_mul(60), pace, today),
+            })
+            .collect();
+
+        Self {
+            track,
+            total_minutes,
+            pace,
+            milestones,
+        }
+    }
+}
+
+fn outlook(entries: &[(NaiveDate, usize)], total_minutes: usize, target: usize, pace: Pace, today: NaiveDate) -> Outlook {
+    if total_minutes >= target {
+        let mut sum = 0;
+        for (day, minutes) in entries {
+            sum += minutes;
+            if sum >= target {
+                return Outlook::Reached(*day);
+            }
+        }
+    }
+
+    let remaining = (target - total_minutes) as f64;
+    let day_at = |minutes_per_day: f64| {
+        let days = remaining / minutes_per_day;
+        (minutes_per_day > 0.0 && days <= HORIZON_DAYS).then(|| today + Duration::days(days.ceil() as i64))
+    };
+
+    match (day_at(pace.minutes_per_day), day_at(pace.high)) {
+        (Some(date), Some(earliest)) => Outlook::Expected {
+            date,
+            earliest,
+            latest: day_at(pace.low),
+        },
+        _ => Outlook::OutOfSight,
+    }
+}
+
+/// Reads milestones written as `500, 1000, 2000h`, dropping anything that
+/// isn't a positive number of hours up to `MAX_MILESTONE`.
+pub fn parse_milestones(text: &str) -> Vec<usize> {
+    let mut milestones: Vec<usize> = text
+        .split(',')
+        .filter_map(|hours| hours.trim().trim_end_matches('h').trim().parse().ok())
+        .filter(|hours| (1..=MAX_MILESTONE).contains(hours))
+        .collect();
+    milestones.sort_unstable();
+    milestones.dedup();
+    milestones
+}
+
+/// Projections for everything, each target language and each skill with
+/// time logged.
+pub fn projections(log: &LogData, languages: &[String], today: NaiveDate, milestone_hours: &[usize]) -> Vec<Projection> {
+    let skills = EntryType::ALL
+        .into_iter()
+        .filter(|skill| log.entries().iter().any(|entry| entry.entry_type() == skill))
+        .map(Track::Skill);
+    let tracks = std::iter::once(Track::All)
+        .chain(languages.iter().cloned().map(Track::Language))
+        .chain(skills);
+
+    tracks.map(|track| Projection::of(log, track, today, milestone_hours)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+
+    fn day(offset: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 1).unwrap() + Duration::days(offset)
+    }
+
+    fn entry(offset: i64, minutes: usize, entry_type: EntryType) -> Entry {
+        Entry::from_data("Session".to_string(), entry_type, minutes, Date::from_naive(day(offset)))
+    }
+
+    #[test]
+    fn a_steady_pace_is_certain() {
+        let pace = Pace::of(&[60; 30]);
+
+        assert_eq!(pace.minutes_per_day, 60.0);
+        assert_eq!((pace.low, pace.high), (60.0, 60.0));
+    }
+
+    #[test]
+    fn an_uneven_pace_widens_the_range() {
+        let pace = Pace::of(&[0, 120, 0, 120, 0, 120, 0, 120, 0, 120]);
+
+        assert!(pace.low < pace.minutes_per_day && pace.minutes_per_day < pace.high);
+        assert!(pace.low >= 0.0);
+    }
+
+    #[test]
+    fn projects_milestones_from_the_recent_pace() {
+        let mut log = LogData::new();
+        // 100 hours a long time ago, then an hour every day since
+        // the start of the lookback
+        log.add(entry(-400, 100 * 60, EntryType::Reading));
+        for offset in -89..=0 {
+            log.add(entry(offset, 60, EntryType::Listening));
+        }
+
+        let projection = Projection::of(&log, Track::All, day(0), &[100, 200, 1000]);
+
+        assert_eq!(projection.total_minutes, 190 * 60);
+        assert_eq!(projection.milestones[0].outlook, Outlook::Reached(day(-400)));
+        assert!(matches!(
+            projection.milestones[1].outlook,
+            Outlook::Expected { date, .. } if date == day(10)
+        ));
+        assert!(matches!(
+            projection.milestones[2].outlook,
+            Outlook::Expected { date, .. } if date == day(810)
+        ));
+    }
+
+    #[test]
+    fn nothing_logged_recently_means_no_date() {
+        let mut log = LogData::new();
+        log.add(entry(-400, 600, EntryType::Reading));
+
+        let projection = Projection::of(&log, Track::All, day(0), &[500]);
+
+        assert_eq!(projection.milestones[0].outlook, Outlook::OutOfSight);
+    }
+
+    #[test]
+    fn reads_milestones() {
+        assert_eq!(parse_milestones("1000, 500h,, x, 0, 500"), [500, 1000]);
+        assert!(parse_milestones("").is_empty());
+        assert_eq!(parse_milestones("1000000, 1000001, 18446744073709551615"), [MAX_MILESTONE]);
+    }
+
+    #[test]
+    fn huge_milestones_are_out_of_sight() {
+        let mut log = LogData::new();
+        log.add(entry(0, 60, EntryType::Reading));
+
+        let projection = Projection::of(&log, Track::All, day(0), &[usize::MAX]);
+
+        assert_eq!(projection.milestones[0].outlook, Outlook::OutOfSight);
+    }
+
+    #[test]
+    fn splits_by_language_and_skill() {
+        let mut log = LogData::new();
+        log.add(entry(0, 30, EntryType::Reading).with_language("Spanish".to_string()));
+        log.add(entry(0, 45, EntryType::Listening).with_language("Russian".to_string()));
+
+        let all = projections(&log, &["Spanish".to_string(), "Russian".to_string()], day(0), &[500]);
+        let totals: Vec<(&str, usize)> = all.iter().map(|projection| (projection.track.name(), projection.total_minutes)).collect();
+
+        assert_eq!(totals, [("All", 75), ("Spanish", 30), ("Russian", 45), ("Listening", 45), ("Reading", 30)]);
+    }
+}
@@ -0,0 +1,283 @@
+//! Reviews comparing one period with another: time, active days, goals and
+//! finished resources, each with the change from before. The weekly review
+//! covers the week that just ended and is offered once a new week starts.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::log::{EntryType, LogData};
+use crate::settings::Settings;
+use crate::stats::{self, Summary};
+use crate::user::UserProfile;
+
+/// Which periods a review compares.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Comparison {
+    /// The week so far against the same days of the week before.
+    ThisWeek,
+    /// The last full week against the one before, the weekly review.
+    LastWeek,
+    /// The last full month against the one before, the monthly review.
+    LastMonth,
+    /// The month so far against the same days of the month a year earlier.
+    MonthOnLastYear,
+    /// The year so far against the same days of the year before.
+    ThisYear,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 5] = [
+        Comparison::ThisWeek,
+        Comparison::LastWeek,
+        Comparison::LastMonth,
+        Comparison::MonthOnLastYear,
+        Comparison::ThisYear,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Comparison::ThisWeek => "This week vs the same days last week",
+            Comparison::LastWeek => "Last week vs the week before",
+            Comparison::LastMonth => "Last month vs the month before",
+            Comparison::MonthOnLastYear => "This month vs the same days a year ago",
+            Comparison::ThisYear => "This year vs the same days last year",
+        }
+    }
+
+    /// The period reviewed and the one it is compared with. A period still
+    /// running is compared with as many days of the earlier one, so a
+    /// Wednesday isn't held against a whole week.
+    pub fn periods(&self, today: NaiveDate, settings: &Settings) -> ((NaiveDate, NaiveDate), (NaiveDate, NaiveDate)) {
+        let week_before = |(from, _): (NaiveDate, NaiveDate)| settings.week_of(from - Duration::days(1));
+        let month_before = |(from, _): (NaiveDate, NaiveDate)| stats::month_of(from - Duration::days(1));
+        // The first days of `earlier`, as many as have passed since `from`
+        let so_far = |from: NaiveDate, (earlier_from, earlier_to): (NaiveDate, NaiveDate)| {
+            ((from, today), (earlier_from, (earlier_from + (today - from)).min(earlier_to)))
+        };
+
+        match self {
+            Comparison::ThisWeek => {
+                let this_week = settings.week_of(today);
+                so_far(this_week.0, week_before(this_week))
+            }
+            Comparison::LastWeek => {
+                let last_week = week_before(settings.week_of(today));
+                (last_week, week_before(last_week))
+            }
+            Comparison::LastMonth => {
+                let last_month = month_before(stats::month_of(today));
+                (last_month, month_before(last_month))
+            }
+            Comparison::MonthOnLastYear => {
+                let this_month = stats::month_of(today);
+                let year_ago = NaiveDate::from_ymd_opt(today.year() - 1, today.month(), 1).expect("the first of a month exists");
+                so_far(this_month.0, stats::month_of(year_ago))
+            }
+            Comparison::ThisYear => {
+                let this_year = stats::year_of(today);
+                so_far(this_year.0, stats::year_of(this_year.0 - Duration::days(1)))
+            }
+        }
+    }
+}
+
+/// One number in both periods.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Change {
+    pub current: usize,
+    pub previous: usize,
+}
+
+impl Change {
+    pub fn delta(&self) -> i64 {
+        self.current as i64 - self.previous as i64
+    }
+
+    /// The change relative to before, 0.25 for a quarter more. `None` when
+    /// there was nothing before.
+    pub fn relative(&self) -> Option<f64> {
+        match self.previous {
+            0 => None,
+            previous => Some(self.delta() as f64 / previous as f64),
+        }
+    }
+}
+
+/// What happened in one period.
+#[derive(PartialEq, Clone, Debug)]
+pub struct PeriodReport {
+    pub summary: Summary,
+    pub goals_completed: Vec<String>,
+    pub resources_finished: Vec<String>,
+}
+
+impl PeriodReport {
+    pub fn of(log: &LogData, user: &UserProfile, (from, to): (NaiveDate, NaiveDate)) -> Self {
+        let within = |day: Option<NaiveDate>| day.is_some_and(|day| from <= day && day <= to);
+
+        Self {
+            summary: Summary::of(log, from, to),
+            goals_completed: user
+                .goals()
+                .iter()
+                .filter(|goal| goal.completed() && within(goal.completed_on()))
+                .map(|goal| goal.title().to_string())
+                .collect(),
+            resources_finished: stats::resources(log)
+                .into_iter()
+                .filter(|total| within(total.finished_on))
+                .map(|total| total.resource)
+                .collect(),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Review {
+    pub current: PeriodReport,
+    pub previous: PeriodReport,
+}
+
+impl Review {
+    pub fn of(log: &LogData, user: &UserProfile, comparison: Comparison, today: NaiveDate) -> Self {
+        let (current, previous) = comparison.periods(today, user.settings());
+        Self {
+            current: PeriodReport::of(log, user, current),
+            previous: PeriodReport::of(log, user, previous),
+        }
+    }
+
+    fn change(&self, value: impl Fn(&PeriodReport) -> usize) -> Change {
+        Change {
+            current: value(&self.current),
+            previous: value(&self.previous),
+        }
+    }
+
+    pub fn total_minutes(&self) -> Change {
+        self.change(|report| report.summary.total_minutes)
+    }
+
+    pub fn active_days(&self) -> Change {
+        self.change(|report| report.summary.active_days)
+    }
+
+    pub fn goals_completed(&self) -> Change {
+        self.change(|report| report.goals_completed.len())
+    }
+
+    pub fn resources_finished(&self) -> Change {
+        self.change(|report| report.resources_finished.len())
+    }
+
+    /// Minutes per type, in the order of `EntryType::ALL`.
+    pub fn per_type(&self) -> Vec<(EntryType, Change)> {
+        EntryType::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, entry_type)| (entry_type.clone(), self.change(|report| report.summary.per_type[index].1)))
+            .collect()
+    }
+}
+
+/// Start of the week whose review is due, if it hasn't been seen yet. The
+/// review is due from the first day of a week, for the week before it.
+pub fn weekly_review_due(settings: &Settings, today: NaiveDate) -> Option<NaiveDate> {
+    let this_week = settings.week_of(today).0;
+    let reviewed = settings.reviewed_week.is_some_and(|reviewed| reviewed >= this_week);
+    (!reviewed).then_some(this_week)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::log::Entry;
+    use crate::user::Goal;
+
+    fn october(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn finds_the_periods_to_compare() {
+        let settings = Settings::default();
+        // A Wednesday
+        let today = october(21);
+
+        assert_eq!(
+            Comparison::ThisWeek.periods(today, &settings),
+            ((october(19), october(21)), (october(12), october(14)))
+        );
+        assert_eq!(
+            Comparison::LastWeek.periods(today, &settings),
+            ((october(12), october(18)), (october(5), october(11)))
+        );
+        assert_eq!(
+            Comparison::LastMonth.periods(today, &settings).1,
+            (NaiveDate::from_ymd_opt(2026, 8, 1).unwrap(), NaiveDate::from_ymd_opt(2026, 8, 31).unwrap())
+        );
+        assert_eq!(
+            Comparison::MonthOnLastYear.periods(today, &settings).1,
+            (NaiveDate::from_ymd_opt(2025, 10, 1).unwrap(), NaiveDate::from_ymd_opt(2025, 10, 21).unwrap())
+        );
+        assert_eq!(
+            Comparison::ThisYear.periods(today, &settings),
+            (
+                (NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(), today),
+                (NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), NaiveDate::from_ymd_opt(2025, 10, 21).unwrap())
+            )
+        );
+    }
+
+    #[test]
+    fn compares_as_many_days_as_the_earlier_period_has() {
+        let settings = Settings::default();
+        // On the last day of a month, all of the month a year ago
+        let today = NaiveDate::from_ymd_opt(2027, 3, 31).unwrap();
+
+        assert_eq!(
+            Comparison::MonthOnLastYear.periods(today, &settings).1,
+            (NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(), NaiveDate::from_ymd_opt(2026, 3, 31).unwrap())
+        );
+        // A leap year's last day is one more than the year before has
+        let leap_day = NaiveDate::from_ymd_opt(2028, 12, 31).unwrap();
+        assert_eq!(
+            Comparison::ThisYear.periods(leap_day, &settings).1,
+            (NaiveDate::from_ymd_opt(2027, 1, 1).unwrap(), NaiveDate::from_ymd_opt(2027, 12, 31).unwrap())
+        );
+    }
+
+    #[test]
+    fn compares_the_weeks() {
+        let mut log = LogData::new();
+        for (day, entry_type, minutes) in [(13, EntryType::Reading, 60), (14, EntryType::Reading, 30), (6, EntryType::Listening, 60)] {
+            log.add(Entry::from_data("Session".to_string(), entry_type, minutes, Date::from_naive(october(day))).with_resource("Book".to_string()));
+        }
+        log.set_resource_finished("Book", &EntryType::Reading, true);
+        let mut user = UserProfile::new();
+        user.import_goals(vec![Goal::from_data("Read a book".to_string(), false, None)]);
+        let goal = user.goals()[1].clone();
+        user.mark_goal_as_completed_on(&goal, october(14));
+
+        let review = Review::of(&log, &user, Comparison::LastWeek, october(19));
+
+        assert_eq!(review.total_minutes(), Change { current: 90, previous: 60 });
+        assert_eq!(review.total_minutes().delta(), 30);
+        assert_eq!(review.total_minutes().relative(), Some(0.5));
+        assert_eq!(review.active_days().relative(), Some(1.0));
+        assert_eq!(review.per_type()[1].1.relative(), Some(-1.0));
+        assert_eq!(review.current.goals_completed, ["Read a book"]);
+        assert_eq!(review.current.resources_finished, ["Book"]);
+        assert_eq!(review.goals_completed().relative(), None);
+    }
+
+    #[test]
+    fn the_weekly_review_is_due_once_a_week() {
+        let mut settings = Settings::default();
+
+        assert_eq!(weekly_review_due(&settings, october(21)), Some(october(19)));
+        settings.reviewed_week = Some(october(19));
+        assert_eq!(weekly_review_due(&settings, october(25)), None);
+        assert_eq!(weekly_review_due(&settings, october(26)), Some(october(26)));
+    }
+}
@@ -0,0 +1,313 @@
+//! User preferences, stored next to the profile and synced with it.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::import;
+use crate::import::time_tracker::MappingRule;
+use crate::ladder::{Ladder, DEFAULT_LADDER};
+use crate::log::EntryType;
+use crate::projection::DEFAULT_MILESTONES;
+use crate::stats::{self, format_minutes};
+
+/// How days are written throughout the app.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+pub enum DateFormat {
+    /// 2026-10-19
+    #[default]
+    Iso,
+    /// 19.10.2026
+    DayMonthYear,
+    /// 10/19/2026
+    MonthDayYear,
+    /// Mon Oct 19 2026
+    Long,
+}
+
+impl DateFormat {
+    pub const ALL: [DateFormat; 4] = [DateFormat::Iso, DateFormat::DayMonthYear, DateFormat::MonthDayYear, DateFormat::Long];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "2026-10-19",
+            DateFormat::DayMonthYear => "19.10.2026",
+            DateFormat::MonthDayYear => "10/19/2026",
+            DateFormat::Long => "Mon Oct 19 2026",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+
+    pub fn format(&self, day: NaiveDate) -> String {
+        let pattern = match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::DayMonthYear => "%d.%m.%Y",
+            DateFormat::MonthDayYear => "%m/%d/%Y",
+            DateFormat::Long => "%a %b %e %Y",
+        };
+        day.format(pattern).to_string()
+    }
+}
+
+/// How durations are written, and entered when logging time.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+pub enum DurationUnits {
+    /// 1h 30m
+    #[default]
+    HoursMinutes,
+    /// 1.5h
+    DecimalHours,
+    /// 90m
+    Minutes,
+}
+
+impl DurationUnits {
+    pub const ALL: [DurationUnits; 3] = [DurationUnits::HoursMinutes, DurationUnits::DecimalHours, DurationUnits::Minutes];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DurationUnits::HoursMinutes => "Hours and minutes",
+            DurationUnits::DecimalHours => "Decimal hours",
+            DurationUnits::Minutes => "Minutes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|units| units.name() == name)
+    }
+
+    pub fn format(&self, minutes: usize) -> String {
+        match self {
+            DurationUnits::HoursMinutes => format_minutes(minutes),
+            DurationUnits::DecimalHours => format!("{:.1}h", minutes as f64 / 60.0),
+            DurationUnits::Minutes => format!("{minutes}m"),
+        }
+    }
+
+    /// Minutes in one unit of what is typed into the logger.
+    pub fn input_minutes(&self) -> usize {
+        match self {
+            DurationUnits::Minutes => 1,
+            _ => 60,
+        }
+    }
+
+    /// Reads a duration typed into the logger. A bare number counts in the
+    /// input unit, so `1.5` is an hour and a half unless minutes are picked;
+    /// `1:30` or `1h 30m` are read the way imports read them.
+    pub fn parse_input(&self, raw: &str) -> Option<usize> {
+        match raw.trim().replace(',', ".").parse::<f64>() {
+            Ok(amount) => import::to_minutes(amount * self.input_minutes() as f64),
+            Err(_) => import::parse_duration_minutes(raw),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Default)]
+pub enum Theme {
+    /// Follows the operating system.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|theme| theme.name() == name)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Whether the profile sidebar is open when the app starts.
+    pub visible_at_startup: bool,
+    pub week_start: Weekday,
+    /// Hour at which a new day begins, so a late night session still counts
+    /// for the evening it started in.
+    pub day_boundary_hour: u32,
+    pub date_format: DateFormat,
+    pub duration_units: DurationUnits,
+    /// Type preselected when logging time.
+    pub default_entry_type: EntryType,
+    pub theme: Theme,
+    /// Days the rolling average in the sidebar covers, one of `stats::AVERAGE_WINDOWS`.
+    pub average_window: usize,
+    /// Hour counts the dashboard projects dates for, ascending.
+    pub milestones: Vec<usize>,
+    /// Name of the level ladder shown with the profile.
+    pub ladder: String,
+    /// Ladders added by the user, next to the built-in ones.
+    pub custom_ladders: Vec<Ladder>,
+    /// Language whose hours count on the ladder. The first language being
+    /// learned when unset or no longer learned.
+    pub ladder_language: Option<String>,
+    /// Percent of input and output time that should go to input.
+    pub input_share_target: u32,
+    /// Percent of the time meant for each entry type. Empty for no targets.
+    pub skill_targets: Vec<(EntryType, u32)>,
+    /// Days without a skill after which the dashboard warns, 0 to never warn.
+    pub neglect_days: usize,
+    /// First day of the week whose weekly review was last dismissed.
+    pub reviewed_week: Option<NaiveDate>,
+    /// Rules for importing Toggl Track and Clockify reports, the first match wins.
+    pub tracker_rules: Vec<MappingRule>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            visible_at_startup: true,
+            week_start: Weekday::Mon,
+            day_boundary_hour: 0,
+            date_format: DateFormat::default(),
+            duration_units: DurationUnits::default(),
+            default_entry_type: EntryType::Other,
+            theme: Theme::default(),
+            average_window: 30,
+            milestones: DEFAULT_MILESTONES.to_vec(),
+            ladder: DEFAULT_LADDER.to_string(),
+            custom_ladders: vec![],
+            ladder_language: None,
+            input_share_target: 70,
+            skill_targets: vec![],
+            neglect_days: 14,
+            reviewed_week: None,
+            tracker_rules: vec![],
+        }
+    }
+}
+
+impl Settings {
+    /// The day `time` counts for, taking the day boundary into account.
+    pub fn day_of(&self, time: NaiveDateTime) -> NaiveDate {
+        (time - Duration::hours(self.day_boundary_hour.min(23) as i64)).date()
+    }
+
+    pub fn week_of(&self, day: NaiveDate) -> (NaiveDate, NaiveDate) {
+        stats::week_of(day, self.week_start)
+    }
+
+    pub fn format_date(&self, day: NaiveDate) -> String {
+        self.date_format.format(day)
+    }
+
+    pub fn format_duration(&self, minutes: usize) -> String {
+        self.duration_units.format(minutes)
+    }
+
+    /// Target percent of `entry_type`, 0 without one.
+    pub fn skill_target(&self, entry_type: &EntryType) -> u32 {
+        self.skill_targets
+            .iter()
+            .find(|(skill, _)| skill == entry_type)
+            .map(|(_, percent)| *percent)
+            .unwrap_or_default()
+    }
+
+    pub fn set_skill_target(&mut self, entry_type: &EntryType, percent: u32) {
+        self.skill_targets.retain(|(skill, _)| skill != entry_type);
+        if percent > 0 {
+            self.skill_targets.push((entry_type.clone(), percent));
+        }
+    }
+
+    /// Built-in ladders followed by the user's own.
+    pub fn ladders(&self) -> Vec<Ladder> {
+        Ladder::built_in().into_iter().chain(self.custom_ladders.iter().cloned()).collect()
+    }
+
+    /// The picked ladder, or the default one if it was removed.
+    pub fn current_ladder(&self) -> Ladder {
+        let ladders = self.ladders();
+        ladders
+            .iter()
+            .find(|ladder| ladder.name == self.ladder)
+            .or(ladders.iter().find(|ladder| ladder.name == DEFAULT_LADDER))
+            .cloned()
+            .expect("the default ladder is built in")
+    }
+
+    /// The language counted on the ladder out of those being `learned`, `None`
+    /// to count all of them when none are.
+    pub fn ladder_language_of<'a>(&'a self, learned: &'a [String]) -> Option<&'a str> {
+        self.ladder_language
+            .as_ref()
+            .filter(|language| learned.contains(language))
+            .or(learned.first())
+            .map(String::as_str)
+    }
+
+    /// Adds a ladder of the user's, replacing one of the same name, and picks it.
+    pub fn add_ladder(&mut self, ladder: Ladder) {
+        self.custom_ladders.retain(|custom| custom.name != ladder.name);
+        self.ladder = ladder.name.clone();
+        self.custom_ladders.push(ladder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn october(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn reads_settings_saved_before_new_fields() {
+        let settings: Settings = serde_json::from_str(r#"{"visible_at_startup":false}"#).unwrap();
+
+        assert!(!settings.visible_at_startup);
+        assert_eq!(settings.week_start, Weekday::Mon);
+        assert_eq!(settings.default_entry_type, EntryType::Other);
+        assert_eq!(settings.milestones, [500, 1000, 2000]);
+        assert!(settings.tracker_rules.is_empty());
+    }
+
+    #[test]
+    fn late_sessions_count_for_the_day_before() {
+        let settings = Settings {
+            day_boundary_hour: 4,
+            ..Settings::default()
+        };
+
+        assert_eq!(settings.day_of(october(20).and_hms_opt(3, 30, 0).unwrap()), october(19));
+        assert_eq!(settings.day_of(october(20).and_hms_opt(4, 0, 0).unwrap()), october(20));
+        assert_eq!(Settings::default().day_of(october(20).and_hms_opt(0, 10, 0).unwrap()), october(20));
+    }
+
+    #[test]
+    fn formats_dates_and_durations() {
+        assert_eq!(DateFormat::DayMonthYear.format(october(9)), "09.10.2026");
+        assert_eq!(DateFormat::MonthDayYear.format(october(9)), "10/09/2026");
+        assert_eq!(DurationUnits::HoursMinutes.format(90), "1h 30m");
+        assert_eq!(DurationUnits::DecimalHours.format(90), "1.5h");
+        assert_eq!(DurationUnits::Minutes.format(90), "90m");
+        assert_eq!(DurationUnits::from_name("Minutes"), Some(DurationUnits::Minutes));
+    }
+
+    #[test]
+    fn reads_typed_durations_in_the_input_unit() {
+        assert_eq!(DurationUnits::HoursMinutes.parse_input("0.5"), Some(30));
+        assert_eq!(DurationUnits::DecimalHours.parse_input("1,5"), Some(90));
+        assert_eq!(DurationUnits::Minutes.parse_input("45"), Some(45));
+        assert_eq!(DurationUnits::HoursMinutes.parse_input("1:30"), Some(90));
+        assert_eq!(DurationUnits::Minutes.parse_input("1h 15m"), Some(75));
+        assert_eq!(DurationUnits::HoursMinutes.parse_input("half"), None);
+        assert_eq!(DurationUnits::HoursMinutes.parse_input("-1"), None);
+    }
+}
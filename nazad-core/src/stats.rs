@@ -0,0 +1,305 @@
+use std::cmp::Reverse;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::log::{Entry, EntryType, LogData};
+
+/// Totals of the entries logged between two days, both included.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Summary {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total_minutes: usize,
+    pub entries: usize,
+    pub active_days: usize,
+    /// Minutes per type, in the order of `EntryType::ALL`.
+    pub per_type: Vec<(EntryType, usize)>,
+}
+
+impl Summary {
+    pub fn of(log: &LogData, from: NaiveDate, to: NaiveDate) -> Self {
+        let mut per_type: Vec<(EntryType, usize)> = EntryType::ALL.iter().map(|entry_type| (entry_type.clone(), 0)).collect();
+        let mut days = vec![];
+        let mut total_minutes = 0;
+        let mut entries = 0;
+
+        for entry in log.entries() {
+            let Some(day) = entry.date().to_naive() else {
+                continue;
+            };
+            if day < from || day > to {
+                continue;
+            }
+
+            total_minutes += entry.minutes();
+            entries += 1;
+            if let Some((_, minutes)) = per_type.iter_mut().find(|(entry_type, _)| entry_type == entry.entry_type()) {
+                *minutes += entry.minutes();
+            }
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+
+        Self {
+            from,
+            to,
+            total_minutes,
+            entries,
+            active_days: days.len(),
+            per_type,
+        }
+    }
+
+    pub fn days(&self) -> usize {
+        ((self.to - self.from).num_days() + 1).max(0) as usize
+    }
+
+    /// Minutes per calendar day of the period.
+    pub fn daily_average(&self) -> usize {
+        match self.days() {
+            0 => 0,
+            days => self.total_minutes / days,
+        }
+    }
+
+    /// Minutes per day with at least one entry.
+    pub fn active_day_average(&self) -> usize {
+        match self.active_days {
+            0 => 0,
+            days => self.total_minutes / days,
+        }
+    }
+}
+
+/// Window lengths in days that rolling averages are offered for.
+pub const AVERAGE_WINDOWS: [usize; 4] = [7, 30, 90, 365];
+
+/// The last `days` days up to today, next to the same number of days before.
+#[derive(PartialEq, Clone, Debug)]
+pub struct RollingAverage {
+    pub current: Summary,
+    pub previous: Summary,
+}
+
+impl RollingAverage {
+    pub fn of(log: &LogData, today: NaiveDate, days: usize) -> Self {
+        let days = days.max(1) as i64;
+        let from = today - Duration::days(days - 1);
+        let previous_to = from - Duration::days(1);
+
+        Self {
+            current: Summary::of(log, from, today),
+            previous: Summary::of(log, previous_to - Duration::days(days - 1), previous_to),
+        }
+    }
+
+    /// Change of the time spent against the previous window, 0.25 for a
+    /// quarter more. `None` if nothing was logged back then.
+    pub fn trend(&self) -> Option<f64> {
+        match self.previous.total_minutes {
+            0 => None,
+            previous => Some((self.current.total_minutes as f64 - previous as f64) / previous as f64),
+        }
+    }
+}
+
+/// Time spent on one book, podcast, deck etc.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ResourceTotal {
+    pub resource: String,
+    pub entry_type: EntryType,
+    pub minutes: usize,
+    pub entries: usize,
+    pub last_day: Option<NaiveDate>,
+    /// Day of the session it was finished in.
+    pub finished_on: Option<NaiveDate>,
+}
+
+/// Totals of every resource in the log, the one with the most time first.
+/// Entries logged without a resource are left out.
+pub fn resources(log: &LogData) -> Vec<ResourceTotal> {
+    totals_of(log.entries())
+}
+
+/// Totals of the resources from the entries logged between two days, both
+/// included.
+pub fn resources_between(log: &LogData, from: NaiveDate, to: NaiveDate) -> Vec<ResourceTotal> {
+    totals_of(
+        log.entries()
+            .iter()
+            .filter(|entry| entry.date().to_naive().is_some_and(|day| from <= day && day <= to)),
+    )
+}
+
+fn totals_of<'a>(entries: impl IntoIterator<Item = &'a Entry>) -> Vec<ResourceTotal> {
+    let mut totals: Vec<ResourceTotal> = vec![];
+
+    for entry in entries {
+        let Some(resource) = entry.resource() else {
+            continue;
+        };
+        let day = entry.date().to_naive();
+
+        match totals.iter_mut().find(|total| total.resource == resource && total.entry_type == *entry.entry_type()) {
+            Some(total) => {
+                total.minutes += entry.minutes();
+                total.entries += 1;
+                total.last_day = total.last_day.max(day);
+                if entry.finished() {
+                    total.finished_on = day;
+                }
+            }
+            None => totals.push(ResourceTotal {
+                resource: resource.to_string(),
+                entry_type: entry.entry_type().clone(),
+                minutes: entry.minutes(),
+                entries: 1,
+                last_day: day,
+                finished_on: day.filter(|_| entry.finished()),
+            }),
+        }
+    }
+
+    totals.sort_by_key(|total| Reverse(total.minutes));
+    totals
+}
+
+/// The week containing `day`, starting on `first_day`.
+pub fn week_of(day: NaiveDate, first_day: Weekday) -> (NaiveDate, NaiveDate) {
+    let offset = (day.weekday().num_days_from_monday() + 7 - first_day.num_days_from_monday()) % 7;
+    let start = day - Duration::days(offset as i64);
+    (start, start + Duration::days(6))
+}
+
+pub fn month_of(day: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = day.with_day(1).unwrap();
+    let next = if day.month() == 12 {
+        NaiveDate::from_ymd_opt(day.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(day.year(), day.month() + 1, 1)
+    };
+    (start, next.unwrap() - Duration::days(1))
+}
+
+pub fn year_of(day: NaiveDate) -> (NaiveDate, NaiveDate) {
+    (
+        NaiveDate::from_ymd_opt(day.year(), 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(day.year(), 12, 31).unwrap(),
+    )
+}
+
+/// Formats minutes as `1h 30m`, `45m` or `2h`.
+pub fn format_minutes(minutes: usize) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::log::Entry;
+
+    fn october(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    fn september(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 9, day).unwrap()
+    }
+
+    fn log() -> LogData {
+        let mut log = LogData::new();
+        log.import_entries(vec![
+            Entry::from_data("Podcast".to_string(), EntryType::Listening, 45, Date::from_naive(october(5))),
+            Entry::from_data("Book".to_string(), EntryType::Reading, 30, Date::from_naive(october(5))),
+            Entry::from_data("Book".to_string(), EntryType::Reading, 60, Date::from_naive(october(7))),
+            Entry::from_data("Book".to_string(), EntryType::Reading, 60, Date::from_naive(october(20))),
+            Entry::from_data("Undated".to_string(), EntryType::Other, 60, Date::default()),
+        ]);
+        log
+    }
+
+    #[test]
+    fn sums_up_a_period() {
+        let (from, to) = week_of(october(7), Weekday::Mon);
+        let summary = Summary::of(&log(), from, to);
+
+        assert_eq!((from, to), (october(5), october(11)));
+        assert_eq!(summary.total_minutes, 135);
+        assert_eq!(summary.entries, 3);
+        assert_eq!(summary.active_days, 2);
+        assert_eq!(summary.days(), 7);
+        assert_eq!(summary.daily_average(), 19);
+        assert_eq!(summary.per_type[1], (EntryType::Listening, 45));
+        assert_eq!(summary.per_type[2], (EntryType::Reading, 90));
+    }
+
+    #[test]
+    fn averages_the_last_days_against_the_ones_before() {
+        let average = RollingAverage::of(&log(), october(11), 7);
+
+        assert_eq!((average.current.from, average.current.to), (october(5), october(11)));
+        assert_eq!((average.previous.from, average.previous.to), (september(28), october(4)));
+        assert_eq!(average.current.daily_average(), 19);
+        assert_eq!(average.current.active_day_average(), 67);
+        assert_eq!(average.trend(), None);
+
+        let average = RollingAverage::of(&log(), october(20), 10);
+        assert_eq!(average.current.total_minutes, 60);
+        assert_eq!(average.previous.total_minutes, 135);
+        assert_eq!(average.trend(), Some(60.0 / 135.0 - 1.0));
+    }
+
+    #[test]
+    fn computes_calendar_periods() {
+        assert_eq!(week_of(october(7), Weekday::Sun), (october(4), october(10)));
+        assert_eq!(week_of(october(4), Weekday::Sun), (october(4), october(10)));
+        assert_eq!(week_of(october(9), Weekday::Sat), (october(3), october(9)));
+        assert_eq!(month_of(october(20)), (october(1), october(31)));
+        assert_eq!(
+            month_of(NaiveDate::from_ymd_opt(2026, 12, 24).unwrap()).1,
+            NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()
+        );
+        assert_eq!(year_of(october(20)).0, NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn totals_resources() {
+        let mut log = log();
+        log.import_entries(vec![
+            Entry::from_data("Chapter 1".to_string(), EntryType::Reading, 20, Date::from_naive(october(3)))
+                .with_resource("El Principito".to_string()),
+            Entry::from_data("Chapter 2".to_string(), EntryType::Reading, 25, Date::from_naive(october(9)))
+                .with_resource("El Principito".to_string()),
+            Entry::from_data("Episode 4".to_string(), EntryType::Listening, 50, Date::default())
+                .with_resource("Radio Ambulante".to_string()),
+        ]);
+
+        let totals = resources(&log);
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].resource, "Radio Ambulante");
+        assert_eq!(totals[0].last_day, None);
+        assert_eq!(totals[1].minutes, 45);
+        assert_eq!(totals[1].entries, 2);
+        assert_eq!(totals[1].last_day, Some(october(9)));
+        assert_eq!(totals[1].finished_on, None);
+
+        log.set_resource_finished("El Principito", &EntryType::Reading, true);
+        assert_eq!(resources(&log)[1].finished_on, Some(october(9)));
+        log.set_resource_finished("El Principito", &EntryType::Reading, false);
+        assert_eq!(resources(&log)[1].finished_on, None);
+    }
+
+    #[test]
+    fn formats_minutes() {
+        assert_eq!(format_minutes(45), "45m");
+        assert_eq!(format_minutes(120), "2h");
+        assert_eq!(format_minutes(90), "1h 30m");
+    }
+}
@@ -0,0 +1,973 @@
+//! Offline-first sync between devices and the server.
+//!
+//! Every entry, goal and the profile and settings documents are kept as a
+//! `Record`: their fields as JSON, each with the `Stamp` of its last change.
+//! Stamps come from a hybrid logical clock and are totally ordered, so two
+//! records are merged field by field by keeping the newer value, no matter in
+//! which order devices sync. A delete wins over concurrent edits. The fields
+//! of the user in the profile document are stamped one by one as well, and
+//! its can-do ticks are a history instead, whose merge keeps every tick.
+//!
+//! Devices hold a `Replica`, which turns edits of the app's state into record
+//! changes and queues them until the server can be reached. The server holds a
+//! `Hub` per account and numbers its changes with revisions, so devices only
+//! pull what changed since their last sync. Entries and goals created offline
+//! on two devices can end up with the same id; the hub then moves the one it
+//! sees second to a free id, which the device picks up with its next pull.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+use crate::api::Profile;
+use crate::cefr::{self, CanDoCheck};
+use crate::log::{Entry, LogData};
+use crate::settings::Settings;
+use crate::user::{Goal, UserProfile};
+
+/// The hub's own replica id, used for changes made through the REST API.
+pub const HUB_REPLICA: u64 = 0;
+
+/// When a change was made. Ordered by time, then counter, then replica, so
+/// stamps from different devices never tie.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+pub struct Stamp {
+    pub time: u64,
+    pub counter: u32,
+    pub replica: u64,
+}
+
+/// Hybrid logical clock: milliseconds since the epoch, bumped past every stamp
+/// seen so far so a device with a slow clock still orders its edits after the
+/// ones it synced.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Clock {
+    last: Stamp,
+}
+
+impl Clock {
+    pub fn new(replica: u64) -> Self {
+        Self {
+            last: Stamp {
+                replica,
+                ..Stamp::default()
+            },
+        }
+    }
+
+    pub fn tick(&mut self, now_ms: u64) -> Stamp {
+        if now_ms > self.last.time {
+            self.last.time = now_ms;
+            self.last.counter = 0;
+        } else {
+            self.last.counter += 1;
+        }
+        self.last
+    }
+
+    pub fn observe(&mut self, stamp: Stamp) {
+        if (stamp.time, stamp.counter) > (self.last.time, self.last.counter) {
+            self.last.time = stamp.time;
+            self.last.counter = stamp.counter;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub enum Key {
+    Entry(usize),
+    Goal(usize),
+    Profile,
+    Settings,
+}
+
+impl Key {
+    pub fn id(&self) -> Option<usize> {
+        match self {
+            Key::Entry(id) | Key::Goal(id) => Some(*id),
+            Key::Profile | Key::Settings => None,
+        }
+    }
+
+    fn with_id(&self, id: usize) -> Key {
+        match self {
+            Key::Entry(_) => Key::Entry(id),
+            Key::Goal(_) => Key::Goal(id),
+            other => *other,
+        }
+    }
+
+    fn same_kind(&self, other: &Key) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Entry(id) => write!(f, "entry/{id}"),
+            Key::Goal(id) => write!(f, "goal/{id}"),
+            Key::Profile => write!(f, "profile"),
+            Key::Settings => write!(f, "settings"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Field {
+    pub value: Value,
+    pub stamp: Stamp,
+}
+
+// The profile document's user is split into a field per property, named
+// e.g. "profile.name", so a name and languages changed on two devices are
+// both kept. The REST API and the app still see one nested object.
+const USER: &str = "profile";
+
+fn user_field(name: &str) -> String {
+    format!("{USER}.{name}")
+}
+
+/// An entry, goal or document with the stamp of every field. `created` tells
+/// apart items that were given the same key on different devices.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Record {
+    pub key: Key,
+    pub created: Stamp,
+    pub fields: BTreeMap<String, Field>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<Stamp>,
+}
+
+impl Record {
+    fn new(key: Key, created: Stamp) -> Self {
+        Self {
+            key,
+            created,
+            fields: BTreeMap::new(),
+            deleted: None,
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.deleted.is_none()
+    }
+
+    fn is_same_item(&self, other: &Record) -> bool {
+        is_same_item((self.key, self.created), (other.key, other.created))
+    }
+
+    /// Stamps the fields of `value` that differ from the record. The id is
+    /// the record's key and not a field of its own.
+    fn update(&mut self, value: Value, stamp: Stamp) -> bool {
+        let Value::Object(mut object) = value else {
+            return false;
+        };
+        object.remove("id");
+        if self.key == Key::Profile {
+            if let Some(Value::Object(user)) = object.remove(USER) {
+                object.extend(user.into_iter().map(|(name, value)| (user_field(&name), value)));
+            }
+        }
+
+        // Fields left out of the JSON, like an entry's resource once removed, become null
+        for name in self.fields.keys() {
+            if !object.contains_key(name) {
+                object.insert(name.clone(), Value::Null);
+            }
+        }
+
+        let mut changed = false;
+        for (name, value) in object {
+            if self.fields.get(&name).map(|field| &field.value) != Some(&value) {
+                self.fields.insert(name, Field { value, stamp });
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn delete(&mut self, stamp: Stamp) {
+        self.deleted = Some(stamp);
+    }
+
+    /// Keeps the newer value of every field, the result is the same in
+    /// whichever order records are merged.
+    pub fn merge(&mut self, other: &Record) -> bool {
+        let mut changed = false;
+        // Ticks made on other devices are kept whichever profile is newer
+        let can_do = (self.key == Key::Profile).then(|| cefr::merge_history(&self.can_do(), &other.can_do()));
+
+        for (name, field) in &other.fields {
+            let newer = match self.fields.get(name) {
+                Some(current) => field.stamp > current.stamp,
+                None => true,
+            };
+            if newer {
+                self.fields.insert(name.clone(), field.clone());
+                changed = true;
+            }
+        }
+        if other.deleted > self.deleted {
+            self.deleted = other.deleted;
+            changed = true;
+        }
+        if let Some(can_do) = can_do {
+            changed |= self.set_can_do(can_do);
+        }
+
+        changed
+    }
+
+    // The can-do history kept in the profile document, nested in the user
+    // where it was stored before the user was split into fields
+    fn can_do(&self) -> Vec<CanDoCheck> {
+        self.fields
+            .get(&user_field("can_do"))
+            .map(|field| &field.value)
+            .or_else(|| self.fields.get(USER).and_then(|field| field.value.get("can_do")))
+            .and_then(|history| serde_json::from_value(history.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    fn set_can_do(&mut self, history: Vec<CanDoCheck>) -> bool {
+        if self.can_do() == history {
+            return false;
+        }
+        if let Some(field) = self.fields.get_mut(&user_field("can_do")) {
+            field.value = to_json(&history);
+            return true;
+        }
+        match self.fields.get_mut(USER).map(|field| &mut field.value) {
+            Some(Value::Object(object)) => {
+                object.insert("can_do".to_string(), to_json(&history));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn latest_stamp(&self) -> Stamp {
+        self.fields
+            .values()
+            .map(|field| field.stamp)
+            .chain(self.deleted)
+            .fold(self.created, Stamp::max)
+    }
+
+    /// The entry, goal or document the record holds, `None` once deleted.
+    pub fn live_value<T: DeserializeOwned>(&self) -> Option<T> {
+        self.value().filter(|_| self.is_live())
+    }
+
+    fn value<T: DeserializeOwned>(&self) -> Option<T> {
+        let mut object: Map<String, Value> = self
+            .fields
+            .iter()
+            .map(|(name, field)| (name.clone(), field.value.clone()))
+            .collect();
+        if let Some(id) = self.key.id() {
+            object.insert("id".to_string(), id.into());
+        }
+        if self.key == Key::Profile {
+            let mut user = match object.remove(USER) {
+                Some(Value::Object(user)) => user,
+                _ => Map::new(),
+            };
+            let prefix = user_field("");
+            let split: Vec<String> = object.keys().filter(|name| name.starts_with(&prefix)).cloned().collect();
+            for name in split {
+                if let Some(value) = object.remove(&name) {
+                    user.insert(name[prefix.len()..].to_string(), value);
+                }
+            }
+            object.insert(USER.to_string(), Value::Object(user));
+        }
+        serde_json::from_value(Value::Object(object)).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct SyncRequest {
+    /// The last revision the device merged.
+    pub since: u64,
+    pub records: Vec<Record>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct SyncResponse {
+    pub revision: u64,
+    /// Every record changed after `since`, merged with the pushed ones.
+    pub records: Vec<Record>,
+}
+
+// Entries and goals are told apart by when they were created, their key may
+// change when the hub moves them. The profile and settings exist once.
+fn is_same_item(a: (Key, Stamp), b: (Key, Stamp)) -> bool {
+    match a.0.id() {
+        Some(_) => a.0.same_kind(&b.0) && a.1 == b.1,
+        None => a.0 == b.0,
+    }
+}
+
+// Identifies an item however the hub moves it, see `is_same_item`
+fn item_id(key: Key, created: Stamp) -> (Key, Stamp) {
+    (key.with_id(0), created)
+}
+
+fn pending_items<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeSet<(Key, Stamp)>, D::Error> {
+    let pending: Vec<(Key, Stamp)> = Deserialize::deserialize(deserializer)?;
+    Ok(pending.into_iter().map(|(key, created)| item_id(key, created)).collect())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("synced types serialize to JSON")
+}
+
+fn next_id<'a>(records: impl Iterator<Item = &'a Record>, key: &Key) -> usize {
+    records
+        .filter(|record| record.key.same_kind(key))
+        .filter_map(|record| record.key.id())
+        .map(|id| id + 1)
+        .max()
+        .unwrap_or_default()
+}
+
+// Builds the app's state out of the live records
+fn state<'a>(records: impl Iterator<Item = &'a Record>) -> (LogData, UserProfile) {
+    let mut live: Vec<&Record> = records.filter(|record| record.is_live()).collect();
+    live.sort_by_key(|record| record.key);
+
+    let mut entries = vec![];
+    let mut goals = vec![];
+    let mut profile = Profile::default();
+    let mut settings = Settings::default();
+    for record in live {
+        match record.key {
+            Key::Entry(_) => entries.extend(record.value::<Entry>()),
+            Key::Goal(_) => goals.extend(record.value::<Goal>()),
+            Key::Profile => profile = record.value().unwrap_or_default(),
+            Key::Settings => settings = record.value().unwrap_or_default(),
+        }
+    }
+
+    let user = UserProfile::from_parts(profile.profile, profile.diagnostics, goals, settings);
+    (LogData::from_entries(entries), user)
+}
+
+/// A device's copy of the records, with the changes it still has to push.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Replica {
+    clock: Clock,
+    records: Vec<Record>,
+    /// Every record changed since the last push, see `item_id`.
+    #[serde(deserialize_with = "pending_items")]
+    pending: BTreeSet<(Key, Stamp)>,
+    revision: u64,
+}
+
+impl Replica {
+    /// `replica` has to be unique among the devices of an account, e.g. random.
+    pub fn new(replica: u64) -> Self {
+        Self {
+            clock: Clock::new(replica),
+            records: vec![],
+            pending: BTreeSet::new(),
+            revision: 0,
+        }
+    }
+
+    pub fn state(&self) -> (LogData, UserProfile) {
+        state(self.records.iter())
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn mark_pending(&mut self, key: Key, created: Stamp) {
+        self.pending.insert(item_id(key, created));
+    }
+
+    // Where the live record under each key is in `records`
+    fn live_index(&self) -> HashMap<Key, usize> {
+        self.records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| record.is_live())
+            .map(|(index, record)| (record.key, index))
+            .collect()
+    }
+
+    // Updates the live record under `key` or starts a new one
+    fn put(&mut self, live: &mut HashMap<Key, usize>, key: Key, value: Value, now_ms: u64) -> bool {
+        let stamp = self.clock.tick(now_ms);
+
+        let created = match live.get(&key).map(|index| &mut self.records[*index]) {
+            Some(record) => {
+                if !record.update(value, stamp) {
+                    return false;
+                }
+                record.created
+            }
+            None => {
+                // The profile and settings exist once per account, so every device agrees on them
+                let created = if key.id().is_some() { stamp } else { Stamp::default() };
+                let mut record = Record::new(key, created);
+                record.update(value, stamp);
+                live.insert(key, self.records.len());
+                self.records.push(record);
+                created
+            }
+        };
+
+        self.mark_pending(key, created);
+        true
+    }
+
+    fn delete(&mut self, live: &HashMap<Key, usize>, key: Key, now_ms: u64) -> bool {
+        let stamp = self.clock.tick(now_ms);
+
+        let Some(record) = live.get(&key).map(|index| &mut self.records[*index]) else {
+            return false;
+        };
+        record.delete(stamp);
+        let created = record.created;
+        self.mark_pending(key, created);
+        true
+    }
+
+    /// Records how `log` and `user` differ from the replica's state as local
+    /// edits. Returns whether anything changed.
+    pub fn commit(&mut self, log: &LogData, user: &UserProfile, now_ms: u64) -> bool {
+        let mut changed = false;
+        let mut live = self.live_index();
+
+        for entry in log.entries() {
+            changed |= self.put(&mut live, Key::Entry(entry.id()), to_json(entry), now_ms);
+        }
+        for goal in user.goals() {
+            changed |= self.put(&mut live, Key::Goal(goal.id()), to_json(goal), now_ms);
+        }
+        changed |= self.put(&mut live, Key::Profile, to_json(&Profile::of(user)), now_ms);
+        changed |= self.put(&mut live, Key::Settings, to_json(user.settings()), now_ms);
+
+        let entries: HashSet<usize> = log.entries().iter().map(Entry::id).collect();
+        let goals: HashSet<usize> = user.goals().iter().map(Goal::id).collect();
+        let mut removed: Vec<Key> = live
+            .keys()
+            .copied()
+            .filter(|key| match key {
+                Key::Entry(id) => !entries.contains(id),
+                Key::Goal(id) => !goals.contains(id),
+                Key::Profile | Key::Settings => false,
+            })
+            .collect();
+        removed.sort();
+        for key in removed {
+            changed |= self.delete(&live, key, now_ms);
+        }
+
+        changed
+    }
+
+    /// Takes the queued changes for pushing them to the hub.
+    pub fn request(&mut self) -> SyncRequest {
+        let pending = std::mem::take(&mut self.pending);
+        let records = self
+            .records
+            .iter()
+            .filter(|record| pending.contains(&item_id(record.key, record.created)))
+            .cloned()
+            .collect();
+
+        SyncRequest {
+            since: self.revision,
+            records,
+        }
+    }
+
+    /// Queues the changes of a request that didn't reach the hub again.
+    pub fn requeue(&mut self, request: &SyncRequest) {
+        for record in &request.records {
+            self.mark_pending(record.key, record.created);
+        }
+    }
+
+    pub fn apply(&mut self, response: SyncResponse) {
+        for incoming in response.records {
+            self.clock.observe(incoming.latest_stamp());
+
+            // Created here while the request was on its way, moved aside until the next push
+            let clash = self.records.iter().position(|record| {
+                record.key == incoming.key && record.key.id().is_some() && !record.is_same_item(&incoming)
+            });
+            if let Some(index) = clash {
+                let id = next_id(self.records.iter().chain([&incoming]), &incoming.key);
+                let record = &mut self.records[index];
+                record.key = incoming.key.with_id(id);
+                let (key, created) = (record.key, record.created);
+                self.mark_pending(key, created);
+            }
+
+            match self.records.iter_mut().find(|record| record.is_same_item(&incoming)) {
+                Some(record) => {
+                    // The hub decides under which key an item lives
+                    record.key = incoming.key;
+                    record.merge(&incoming);
+                }
+                None => self.records.push(incoming),
+            }
+        }
+
+        self.revision = response.revision;
+    }
+}
+
+/// The server's copy of an account's records, each with the revision it last changed in.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Hub {
+    records: Vec<(Record, u64)>,
+    revision: u64,
+    /// The first key of each kind that no record uses, loaded or not.
+    free_keys: Vec<Key>,
+}
+
+impl Hub {
+    pub fn new(records: Vec<(Record, u64)>) -> Self {
+        let revision = records.iter().map(|(_, revision)| *revision).max().unwrap_or_default();
+        Self {
+            records,
+            revision,
+            free_keys: vec![],
+        }
+    }
+
+    /// A hub holding only some of the account's records, enough for one
+    /// change: every record it touches, the ones a sync sends back, and for
+    /// the rest the account's `revision` and the first `free_keys` of each
+    /// kind. Changes only compare stamps within a record, so the clock needs
+    /// no more than the records loaded.
+    pub fn partial(records: Vec<(Record, u64)>, revision: u64, free_keys: Vec<Key>) -> Self {
+        Self {
+            records,
+            revision,
+            free_keys,
+        }
+    }
+
+    /// Records changed after `revision`, for storing them.
+    pub fn changed_since(&self, revision: u64) -> impl Iterator<Item = &(Record, u64)> {
+        self.records.iter().filter(move |(_, changed)| *changed > revision)
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn state(&self) -> (LogData, UserProfile) {
+        state(self.records.iter().map(|(record, _)| record))
+    }
+
+    /// A key for a new entry or goal that was never used before, not even by a deleted one.
+    pub fn next_key(&self, kind: Key) -> Key {
+        let loaded = next_id(self.records.iter().map(|(record, _)| record), &kind);
+        let unloaded = self
+            .free_keys
+            .iter()
+            .filter(|free| free.same_kind(&kind))
+            .filter_map(Key::id)
+            .max()
+            .unwrap_or_default();
+        kind.with_id(loaded.max(unloaded))
+    }
+
+    fn clock(&self) -> Clock {
+        let mut clock = Clock::new(HUB_REPLICA);
+        for (record, _) in &self.records {
+            clock.observe(record.latest_stamp());
+        }
+        clock
+    }
+
+    /// Changes made through the REST API instead of a replica.
+    pub fn put<T: Serialize>(&mut self, key: Key, value: &T, now_ms: u64) {
+        let stamp = self.clock().tick(now_ms);
+        let revision = self.revision + 1;
+
+        if let Some(index) = self.records.iter().position(|(record, _)| record.key == key && !record.is_live()) {
+            // The deleted item keeps its tombstone under a fresh key, so devices still learn about it
+            let moved = self.next_key(key);
+            self.records[index] = (Record { key: moved, ..self.records[index].0.clone() }, revision);
+        }
+
+        match self.records.iter_mut().find(|(record, _)| record.key == key) {
+            Some((record, changed)) => {
+                if !record.update(to_json(value), stamp) {
+                    return;
+                }
+                *changed = revision;
+            }
+            None => {
+                let created = if key.id().is_some() { stamp } else { Stamp::default() };
+                let mut record = Record::new(key, created);
+                record.update(to_json(value), stamp);
+                self.records.push((record, revision));
+            }
+        }
+        self.revision = revision;
+    }
+
+    /// Returns whether there was a live record to delete.
+    pub fn delete(&mut self, key: Key, now_ms: u64) -> bool {
+        let stamp = self.clock().tick(now_ms);
+
+        let Some((record, changed)) = self.records.iter_mut().find(|(record, _)| record.key == key && record.is_live()) else {
+            return false;
+        };
+        record.delete(stamp);
+        self.revision += 1;
+        *changed = self.revision;
+        true
+    }
+
+    pub fn sync(&mut self, request: SyncRequest) -> SyncResponse {
+        let revision = self.revision + 1;
+
+        for mut incoming in request.records {
+            if let Some((record, changed)) = self.records.iter_mut().find(|(record, _)| record.is_same_item(&incoming)) {
+                if record.merge(&incoming) {
+                    *changed = revision;
+                }
+                continue;
+            }
+
+            if self.records.iter().any(|(record, _)| record.key == incoming.key) {
+                incoming.key = self.next_key(incoming.key);
+            }
+            self.records.push((incoming, revision));
+        }
+
+        if self.records.iter().any(|(_, changed)| *changed == revision) {
+            self.revision = revision;
+        }
+
+        let mut records: Vec<Record> = self
+            .changed_since(request.since)
+            .map(|(record, _)| record.clone())
+            .collect();
+        records.sort_by_key(|record| record.key);
+
+        SyncResponse {
+            revision: self.revision,
+            records,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    use crate::date::Date;
+    use crate::log::EntryType;
+    use crate::user::User;
+
+    fn day(day: u32) -> Date {
+        Date::from_naive(NaiveDate::from_ymd_opt(2023, 10, day).unwrap())
+    }
+
+    fn sync(replica: &mut Replica, hub: &mut Hub) {
+        let request = replica.request();
+        replica.apply(hub.sync(request));
+    }
+
+    // Changes the replica's state the way the app does and commits it
+    fn edit(replica: &mut Replica, now_ms: u64, change: impl FnOnce(&mut LogData, &mut UserProfile)) {
+        let (mut log, mut user) = replica.state();
+        change(&mut log, &mut user);
+        replica.commit(&log, &user, now_ms);
+    }
+
+    fn two_devices_with_a_goal() -> (Replica, Replica, Hub) {
+        let mut hub = Hub::default();
+        let mut laptop = Replica::new(1);
+        let mut phone = Replica::new(2);
+
+        edit(&mut laptop, 1_000, |_, user| {
+            user.import_goals(vec![Goal::from_data("Pass DELE B2".to_string(), false, None)]);
+        });
+        sync(&mut laptop, &mut hub);
+        sync(&mut phone, &mut hub);
+
+        (laptop, phone, hub)
+    }
+
+    fn assert_converged(laptop: &mut Replica, phone: &mut Replica, hub: &mut Hub) {
+        sync(laptop, hub);
+        sync(phone, hub);
+        sync(laptop, hub);
+
+        assert_eq!(laptop.state(), hub.state());
+        assert_eq!(phone.state(), hub.state());
+    }
+
+    #[test]
+    fn edits_reach_the_other_device() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+        assert_eq!(phone.state().1.goals()[0].title(), "Pass DELE B2");
+
+        edit(&mut phone, 2_000, |log, _| {
+            log.add_entry("Podcast".to_string(), EntryType::Listening, 30, day(1));
+        });
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        assert_eq!(laptop.state().0.entries()[0].title(), "Podcast");
+    }
+
+    #[test]
+    fn unchanged_state_queues_nothing() {
+        let (mut laptop, _, _) = two_devices_with_a_goal();
+        let (log, user) = laptop.state();
+
+        assert!(!laptop.commit(&log, &user, 5_000));
+        assert!(!laptop.has_pending());
+    }
+
+    #[test]
+    fn goal_toggled_on_both_devices_merges_the_same_in_any_order() {
+        let results: Vec<bool> = [true, false]
+            .into_iter()
+            .map(|laptop_first| {
+                let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+                // Both complete the goal offline, the phone a bit later and then undoes it
+                edit(&mut laptop, 2_000, |_, user| {
+                    let goal = user.goals()[0].clone();
+                    user.mark_goal_as_completed(&goal);
+                });
+                edit(&mut phone, 3_000, |_, user| {
+                    let goal = user.goals()[0].clone();
+                    user.mark_goal_as_completed(&goal);
+                });
+                edit(&mut phone, 4_000, |_, user| {
+                    let goal = user.goals()[0].clone();
+                    user.mark_goal_as_completed(&goal);
+                });
+
+                if laptop_first {
+                    sync(&mut laptop, &mut hub);
+                }
+                assert_converged(&mut phone, &mut laptop, &mut hub);
+                laptop.state().1.goals()[0].completed()
+            })
+            .collect();
+
+        // The phone's last change is the newest one
+        assert_eq!(results, vec![false, false]);
+    }
+
+    #[test]
+    fn goal_completed_on_both_devices_stays_completed() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+        for (replica, now_ms) in [(&mut laptop, 2_000), (&mut phone, 2_000)] {
+            edit(replica, now_ms, |_, user| {
+                let goal = user.goals()[0].clone();
+                user.mark_goal_as_completed(&goal);
+            });
+        }
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        assert!(phone.state().1.goals()[0].completed());
+    }
+
+    #[test]
+    fn concurrent_edits_of_different_fields_are_both_kept() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+        edit(&mut laptop, 2_000, |_, user| {
+            user.goal_mut(0).unwrap().set_title("Pass DELE C1".to_string());
+        });
+        edit(&mut phone, 2_000, |_, user| {
+            let goal = user.goals()[0].clone();
+            user.mark_goal_as_completed(&goal);
+        });
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        let goal = laptop.state().1.goals()[0].clone();
+        assert_eq!(goal.title(), "Pass DELE C1");
+        assert!(goal.completed());
+    }
+
+    #[test]
+    fn can_do_ticks_from_both_devices_are_kept() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+        let at = |hour: u32| chrono::DateTime::parse_from_rfc3339(&format!("2023-10-01T{hour:02}:00:00+02:00")).unwrap();
+
+        edit(&mut laptop, 2_000, |_, user| {
+            user.check_can_do("reading-a1-1", Some("Spanish"), true, at(9));
+        });
+        edit(&mut phone, 3_000, |_, user| {
+            user.check_can_do("listening-a1-1", Some("Spanish"), true, at(10));
+            let mut profile = user.profile().clone();
+            profile.name = "Ana".to_string();
+            user.set_profile(profile);
+        });
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        let profile = laptop.state().1.profile().clone();
+        assert_eq!(profile.name, "Ana");
+        let ticked: Vec<&str> = profile.can_do.iter().map(|check| check.id.as_str()).collect();
+        assert_eq!(ticked, ["reading-a1-1", "listening-a1-1"]);
+    }
+
+    #[test]
+    fn name_and_languages_changed_on_two_devices_are_both_kept() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+        edit(&mut laptop, 2_000, |_, user| {
+            let mut profile = user.profile().clone();
+            profile.name = "Ana".to_string();
+            user.set_profile(profile);
+        });
+        edit(&mut phone, 3_000, |_, user| {
+            let mut profile = user.profile().clone();
+            profile.target_languages = vec!["Spanish".to_string()];
+            user.set_profile(profile);
+        });
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        let profile = laptop.state().1.profile().clone();
+        assert_eq!(profile.name, "Ana");
+        assert_eq!(profile.target_languages, ["Spanish"]);
+    }
+
+    #[test]
+    fn profiles_stored_as_one_field_still_load_and_split() {
+        let mut record = Record::new(Key::Profile, Stamp::default());
+        let user = User {
+            name: "Ana".to_string(),
+            ..User::default()
+        };
+        let stamp = Stamp { time: 1_000, counter: 0, replica: 1 };
+        record.fields.insert(USER.to_string(), Field { value: to_json(&user), stamp });
+        let diagnostics = to_json(&Profile::default().diagnostics);
+        record.fields.insert("diagnostics".to_string(), Field { value: diagnostics, stamp });
+        assert_eq!(record.live_value::<Profile>().unwrap().profile.name, "Ana");
+
+        let renamed = User {
+            native_language: "English".to_string(),
+            ..user
+        };
+        record.update(to_json(&Profile { profile: renamed.clone(), ..Profile::default() }), Stamp { time: 2_000, ..stamp });
+
+        assert_eq!(record.fields[&user_field("native_language")].value, "English");
+        assert_eq!(record.live_value::<Profile>().unwrap().profile, renamed);
+    }
+
+    #[test]
+    fn entries_created_offline_with_the_same_id_are_both_kept() {
+        let mut hub = Hub::default();
+        let mut laptop = Replica::new(1);
+        let mut phone = Replica::new(2);
+
+        edit(&mut laptop, 1_000, |log, _| {
+            log.add_entry("Book".to_string(), EntryType::Reading, 30, day(1));
+        });
+        edit(&mut phone, 1_000, |log, _| {
+            log.add_entry("Podcast".to_string(), EntryType::Listening, 20, day(1));
+        });
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        let (log, _) = phone.state();
+        let entries: Vec<(usize, &str)> = log.entries().iter().map(|entry| (entry.id(), entry.title())).collect();
+        assert_eq!(entries, vec![(0, "Book"), (1, "Podcast")]);
+    }
+
+    #[test]
+    fn entry_created_while_a_push_is_on_its_way_is_moved_aside() {
+        let mut hub = Hub::default();
+        let mut laptop = Replica::new(1);
+        let mut phone = Replica::new(2);
+
+        edit(&mut laptop, 1_000, |log, _| {
+            log.add_entry("Book".to_string(), EntryType::Reading, 30, day(1));
+        });
+        sync(&mut laptop, &mut hub);
+
+        let request = phone.request();
+        edit(&mut phone, 2_000, |log, _| {
+            log.add_entry("Podcast".to_string(), EntryType::Listening, 20, day(1));
+        });
+        phone.apply(hub.sync(request));
+        assert!(phone.has_pending());
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        assert_eq!(laptop.state().0.entries().len(), 2);
+    }
+
+    #[test]
+    fn deletes_win_over_concurrent_edits() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+        edit(&mut laptop, 2_000, |_, user| {
+            let goal = user.goals()[0].clone();
+            user.remove_goal(&goal);
+        });
+        edit(&mut phone, 3_000, |_, user| {
+            user.goal_mut(0).unwrap().set_title("Pass DELE C1".to_string());
+        });
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        assert!(phone.state().1.goals().is_empty());
+    }
+
+    #[test]
+    fn failed_pushes_are_queued_again() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+        edit(&mut laptop, 2_000, |log, _| {
+            log.add_entry("Podcast".to_string(), EntryType::Listening, 30, day(1));
+        });
+        let lost = laptop.request();
+        assert!(!laptop.has_pending());
+        laptop.requeue(&lost);
+        assert!(laptop.has_pending());
+
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+        assert_eq!(phone.state().0.entries().len(), 1);
+    }
+
+    #[test]
+    fn changes_through_the_hub_reach_devices() {
+        let (mut laptop, mut phone, mut hub) = two_devices_with_a_goal();
+
+        hub.delete(Key::Goal(0), 2_000);
+        let entry = Entry::from_data("Film".to_string(), EntryType::Listening, 90, day(2));
+        hub.put(hub.next_key(Key::Entry(0)), &entry, 2_000);
+        // Reusing the deleted goal's id doesn't bring the deleted goal back on devices
+        hub.put(Key::Goal(0), &Goal::from_data("Watch 100 films".to_string(), false, None), 3_000);
+        assert_converged(&mut laptop, &mut phone, &mut hub);
+
+        let (log, user) = phone.state();
+        assert_eq!(log.entries()[0].title(), "Film");
+        let goals: Vec<&str> = user.goals().iter().map(Goal::title).collect();
+        assert_eq!(goals, vec!["Watch 100 films"]);
+    }
+
+    #[test]
+    fn clock_orders_edits_after_everything_seen() {
+        let mut clock = Clock::new(1);
+        clock.observe(Stamp { time: 5_000, counter: 3, replica: 2 });
+
+        let stamp = clock.tick(1_000);
+
+        assert_eq!(stamp, Stamp { time: 5_000, counter: 4, replica: 1 });
+        assert!(clock.tick(6_000) > stamp);
+    }
+}
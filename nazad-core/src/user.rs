@@ -0,0 +1,351 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use std::{
+    fs,
+};
+
+use crate::cefr::CanDoCheck;
+use crate::date::Date;
+use crate::log::ImportSummary;
+use crate::settings::Settings;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Goal {
+    id: usize,
+    completed: bool,
+    title: String,
+    date: Date,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_on: Option<NaiveDate>,
+}
+
+impl Goal {
+    /// Creates a goal for importing, its id is assigned once it is added to a profile.
+    pub fn from_data(title: String, completed: bool, date: Option<NaiveDate>) -> Self {
+        Self {
+            id: 0,
+            completed,
+            title,
+            date: date.map(Date::from_naive).unwrap_or_default(),
+            completed_on: None,
+        }
+    }
+
+    /// Sets the id for goals that were stored before, e.g. on a server.
+    pub fn with_id(mut self, id: usize) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// The goal without its id, which differs between copies of a profile,
+    /// and without the day it was completed on, which older copies lack.
+    pub fn content(&self) -> Goal {
+        Goal {
+            id: 0,
+            completed_on: None,
+            ..self.clone()
+        }
+    }
+
+    pub fn completed(&self) -> bool {
+        self.completed
+    }
+
+    /// The day the goal was marked as completed, `None` for goals completed
+    /// before this was recorded.
+    pub fn completed_on(&self) -> Option<NaiveDate> {
+        self.completed_on
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn date(&self) -> &Date {
+        &self.date
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    pub fn date_mut(&mut self) -> &mut Date {
+        &mut self.date
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct UserDiagnostics {
+    pub total_study_time: usize,
+    /// Kept for older data files, averages are computed from the log with
+    /// `stats::RollingAverage`.
+    pub daily_average: usize,
+    pub vocab_size: usize,
+    pub chapters_read: usize,
+    pub books_read: usize,
+    pub videos_watched: usize,
+    pub goals_completed: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct User {
+    /// URL of the profile picture, a `data:` URL for uploaded ones. Empty
+    /// shows the initials instead.
+    pub picture_path: String,
+    pub name: String,
+    pub native_language: String,
+    pub target_languages: Vec<String>,
+    /// Every CEFR can-do statement ticked or unticked, oldest first.
+    pub can_do: Vec<CanDoCheck>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct UserProfile {
+    profile: User,
+    diagnostics: UserDiagnostics,
+    goals: Vec<Goal>,
+    #[serde(default)]
+    settings: Settings,
+}
+
+impl Default for UserProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UserProfile {
+    pub fn new() -> Self {
+        Self {
+            profile: User::default(),
+            goals: vec![Goal {
+                id: 0,
+                completed: false,
+                title: String::new(),
+                date: Date::default(),
+                completed_on: None,
+            }],
+            settings: Settings::default(),
+            diagnostics: UserDiagnostics::default(),
+        }
+    }
+
+    /// Puts a profile back together from the parts a server stores separately.
+    pub fn from_parts(profile: User, diagnostics: UserDiagnostics, goals: Vec<Goal>, settings: Settings) -> Self {
+        Self {
+            profile,
+            diagnostics,
+            goals,
+            settings,
+        }
+    }
+
+    pub fn profile(&self) -> &User {
+        &self.profile
+    }
+
+    pub fn diagnostics(&self) -> &UserDiagnostics {
+        &self.diagnostics
+    }
+
+    pub fn goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    pub fn goal_mut(&mut self, id: usize) -> Option<&mut Goal> {
+        self.goals.iter_mut().find(|goal| goal.id == id)
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn set_profile(&mut self, profile: User) {
+        self.profile = profile;
+    }
+
+    /// Ticks or unticks a CEFR can-do statement for `language`, keeping the
+    /// change in its history.
+    pub fn check_can_do(&mut self, id: &str, language: Option<&str>, checked: bool, at: DateTime<FixedOffset>) {
+        self.profile.can_do.push(CanDoCheck {
+            id: id.to_string(),
+            language: language.map(str::to_string),
+            checked,
+            at,
+        });
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: UserDiagnostics) {
+        self.diagnostics = diagnostics;
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+    }
+
+    pub fn set_vocab_size(&mut self, vocab_size: usize) {
+        self.diagnostics.vocab_size = vocab_size;
+    }
+
+    pub fn test(&mut self) {
+        self.diagnostics.goals_completed += 1;
+
+        self.profile.name = self.goals.iter().fold(String::from(""), |acc: String, goal| acc + goal.title.as_str());
+        self.diagnostics.goals_completed = self.goals.iter().fold(0, |acc, goal| {if goal.completed { acc + 1} else {acc}});
+    }
+
+    pub fn generate_diagnostic_data(&mut self, data_path: &str) {
+        let raw_diagnostic_data = UserProfile::get_diagnostics(data_path).unwrap();
+        let diagnostics = UserProfile::deserialize_diagnostics(&raw_diagnostic_data).unwrap();
+
+        self.diagnostics = UserProfile::process_diagnostics(diagnostics);
+    }
+
+    fn get_diagnostics(data_path: &str) -> std::io::Result<String> {
+        fs::read_to_string(data_path)
+    }
+
+    fn deserialize_diagnostics(diagnostic_data: &str) -> serde_json::Result<UserDiagnostics> {
+        serde_json::from_str(diagnostic_data)
+    }
+
+    fn process_diagnostics(diagnostics: UserDiagnostics) -> UserDiagnostics {
+        diagnostics
+    }
+
+    pub fn mark_goal_as_completed(&mut self, target_goal: &Goal) {
+        let pos = self.goals.iter().position(|goal| goal.id == target_goal.id).unwrap();
+        self.goals[pos].completed  = !self.goals[pos].completed;
+        self.goals[pos].completed_on = None;
+    }
+
+    /// Like `mark_goal_as_completed`, remembering `day` as the day of completion.
+    pub fn mark_goal_as_completed_on(&mut self, target_goal: &Goal, day: NaiveDate) {
+        self.mark_goal_as_completed(target_goal);
+        if let Some(goal) = self.goal_mut(target_goal.id).filter(|goal| goal.completed) {
+            goal.completed_on = Some(day);
+        }
+    }
+
+    pub fn remove_goal(&mut self, target_goal: &Goal) {
+        let pos = self.goals.iter().position(|goal| goal.id == target_goal.id).unwrap();
+        self.goals.remove(pos);
+    }
+
+    pub fn update_goal(&mut self, target_goal: &Goal, new_goal: Goal) {
+        let pos = self.goals.iter().position(|goal| goal.id == target_goal.id).unwrap();
+        self.goals[pos] = new_goal;
+    }
+
+    /// Adds all goals whose title and date are not already on the list.
+    pub fn import_goals(&mut self, goals: Vec<Goal>) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+
+        for mut goal in goals {
+            if self.goals.iter().any(|existing| existing.title == goal.title && existing.date == goal.date) {
+                summary.duplicates += 1;
+                continue;
+            }
+
+            goal.id = self.next_goal_id();
+            self.goals.push(goal);
+            summary.added += 1;
+        }
+
+        summary
+    }
+
+    // Goals merged from several devices aren't in the order of their ids
+    fn next_goal_id(&self) -> usize {
+        self.goals.iter().map(|goal| goal.id + 1).max().unwrap_or_default()
+    }
+
+    pub fn add_goal(&mut self) {
+        let id = self.next_goal_id();
+
+        let goal = Goal{
+            id,
+            completed: false,
+            title: "".to_string(),
+            date: Date::default(),
+            completed_on: None,
+        };
+
+        self.goals.push(goal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(title: &str) -> Goal {
+        Goal::from_data(title.to_string(), false, NaiveDate::from_ymd_opt(2023, 12, 31))
+    }
+
+    #[test]
+    fn new_goals_get_increasing_ids() {
+        let mut user = UserProfile::new();
+        user.add_goal();
+        user.add_goal();
+
+        let ids: Vec<usize> = user.goals().iter().map(Goal::id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn marking_a_goal_toggles_it() {
+        let mut user = UserProfile::new();
+        let target = user.goals()[0].clone();
+
+        user.mark_goal_as_completed(&target);
+        assert!(user.goals()[0].completed());
+
+        user.mark_goal_as_completed(&target);
+        assert!(!user.goals()[0].completed());
+    }
+
+    #[test]
+    fn goals_can_be_edited_and_removed() {
+        let mut user = UserProfile::new();
+        user.add_goal();
+
+        let goal = user.goal_mut(1).unwrap();
+        goal.set_title("Finish El Principito".to_string());
+        goal.date_mut().year = 2024;
+        assert_eq!(user.goals()[1].title(), "Finish El Principito");
+        assert_eq!(user.goals()[1].date().year, 2024);
+
+        let target = user.goals()[0].clone();
+        user.remove_goal(&target);
+        assert_eq!(user.goals().len(), 1);
+        assert_eq!(user.goals()[0].id(), 1);
+    }
+
+    #[test]
+    fn update_goal_replaces_the_matching_goal() {
+        let mut user = UserProfile::new();
+        let target = user.goals()[0].clone();
+
+        user.update_goal(&target, goal("Read 10 books"));
+
+        assert_eq!(user.goals()[0].title(), "Read 10 books");
+    }
+
+    #[test]
+    fn imported_goals_skip_duplicates_and_get_fresh_ids() {
+        let mut user = UserProfile::new();
+
+        let summary = user.import_goals(vec![goal("Pass DELE B2"), goal("Pass DELE B2"), goal("Watch 100 films")]);
+
+        assert_eq!(summary.added, 2);
+        assert_eq!(summary.duplicates, 1);
+        let ids: Vec<usize> = user.goals().iter().map(Goal::id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,339 @@
+//! Syncing the data file through a WebDAV server such as Nextcloud, shared by
+//! the command line client and the app, which each bring their own HTTP
+//! client as a `Transport`. The file is stored there as is, so the dashboard
+//! can import it too. ETags tell whether it changed since the last sync and
+//! keep two devices from overwriting each other's uploads; changes on both
+//! sides are merged with `DataFile::reconcile`.
+
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{DataFile, DataFileError};
+
+/// Another device uploading between our download and our upload means
+/// starting over, at most this many times.
+const ATTEMPTS: usize = 5;
+
+/// As much of a response from the WebDAV server as syncing needs.
+pub struct Response {
+    pub status: u16,
+    pub etag: Option<String>,
+    /// Only needed for successful downloads.
+    pub body: Option<String>,
+}
+
+/// What an upload may replace: the version with this ETag (`If-Match`), or
+/// nothing at all (`If-None-Match: *`).
+pub enum Precondition {
+    Matches(String),
+    Missing,
+}
+
+/// Sends the requests of a sync to the file's URL, with whatever HTTP client
+/// the platform has.
+// Futures are awaited where they are made, so they need no Send bound
+#[allow(async_fn_in_trait)]
+pub trait Transport {
+    type Error: fmt::Display + fmt::Debug;
+
+    /// Downloads the file, with `If-None-Match` if an ETag is known.
+    async fn get(&self, known_etag: Option<&str>) -> Result<Response, Self::Error>;
+
+    /// Uploads `body` as JSON under the precondition.
+    async fn put(&self, body: String, precondition: Precondition) -> Result<Response, Self::Error>;
+}
+
+#[derive(Debug)]
+pub enum WebDavError<E> {
+    Transport(E),
+    Unauthorized,
+    Status(u16),
+    DataFile(DataFileError),
+    /// The file exists, but without an ETag an upload can't be kept from
+    /// replacing what another device uploaded in the meantime.
+    NoEtag,
+    /// Other devices kept uploading while this one was merging.
+    Busy,
+}
+
+impl<E: fmt::Display> fmt::Display for WebDavError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebDavError::Transport(error) => write!(f, "{error}"),
+            WebDavError::Unauthorized => write!(f, "the WebDAV server rejected the username or password"),
+            WebDavError::Status(status) => write!(f, "the WebDAV server answered with status {status}"),
+            WebDavError::DataFile(error) => write!(f, "could not read the file on the WebDAV server: {error}"),
+            WebDavError::NoEtag => write!(
+                f,
+                "conflict detection is unavailable because the WebDAV server sends no ETag or doesn't let the browser read it \
+                 (Access-Control-Expose-Headers), so the file on the server was left alone"
+            ),
+            WebDavError::Busy => write!(f, "the file on the WebDAV server kept changing, try again later"),
+        }
+    }
+}
+
+impl<E: fmt::Display + fmt::Debug> Error for WebDavError<E> {}
+
+/// What the last sync agreed on with the server, kept on the device.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct SyncState {
+    url: String,
+    etag: Option<String>,
+    base: Option<DataFile>,
+}
+
+impl SyncState {
+    pub fn from_json(raw: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(raw)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("sync states serialize to JSON")
+    }
+
+    /// The version both sides last agreed on and its ETag. A different URL
+    /// is a different file, there is nothing to compare with then.
+    pub fn base_for(&self, url: &str) -> (Option<DataFile>, Option<String>) {
+        match &self.base {
+            Some(base) if self.url == url => (Some(base.clone()), self.etag.clone()),
+            _ => (None, None),
+        }
+    }
+}
+
+/// What a download of the file found.
+enum Remote {
+    Missing,
+    /// The ETag sent along still matches.
+    Unchanged,
+    Changed(Box<DataFile>, Option<String>),
+}
+
+impl Remote {
+    fn of<E>(response: Response) -> Result<Self, WebDavError<E>> {
+        match response.status {
+            304 => Ok(Remote::Unchanged),
+            404 => Ok(Remote::Missing),
+            401 => Err(WebDavError::Unauthorized),
+            200..=299 => {
+                let file = DataFile::from_json(&response.body.unwrap_or_default()).map_err(WebDavError::DataFile)?;
+                Ok(Remote::Changed(Box::new(file), response.etag))
+            }
+            status => Err(WebDavError::Status(status)),
+        }
+    }
+
+    /// The file on the server and its ETag, with the base standing in for
+    /// an unchanged one.
+    fn resolve(self, base: Option<&DataFile>, known_etag: Option<&str>) -> (Option<DataFile>, Option<String>) {
+        match self {
+            Remote::Missing => (None, None),
+            Remote::Unchanged => (base.cloned(), known_etag.map(str::to_string)),
+            Remote::Changed(file, etag) => (Some(*file), etag),
+        }
+    }
+}
+
+pub struct Synced {
+    pub data: DataFile,
+    pub state: SyncState,
+    pub pulled: bool,
+    pub pushed: bool,
+}
+
+impl Synced {
+    /// `merged` is what the server holds now, under `etag`.
+    fn new(local: &DataFile, merged: DataFile, pushed: bool, url: &str, etag: Option<String>) -> Self {
+        Self {
+            pulled: merged != *local,
+            pushed,
+            state: SyncState {
+                url: url.to_string(),
+                etag,
+                base: Some(merged.clone()),
+            },
+            data: merged,
+        }
+    }
+
+    /// A line for the user about what the sync did.
+    pub fn describe(&self, url: &str) -> String {
+        match (self.pulled, self.pushed) {
+            (false, false) => format!("Already in sync with {url}"),
+            (true, false) => format!("Pulled changes from {url}"),
+            (false, true) => format!("Pushed changes to {url}"),
+            (true, true) => format!("Merged changes with {url}"),
+        }
+    }
+}
+
+/// `local` merged with the file on the server, and whether the server has
+/// to get the result.
+fn merge(local: &DataFile, base: Option<&DataFile>, remote: Option<&DataFile>) -> (DataFile, bool) {
+    let merged = match remote {
+        Some(remote) => local.reconcile(base, remote),
+        None => local.clone(),
+    };
+    let push = remote != Some(&merged);
+    (merged, push)
+}
+
+/// Pulls what changed on the server at `url`, merges it with `local` and
+/// pushes the result if the server doesn't have it yet.
+pub async fn sync<T: Transport>(transport: &T, url: &str, local: &DataFile, state: &SyncState) -> Result<Synced, WebDavError<T::Error>> {
+    let (base, known_etag) = state.base_for(url);
+
+    for _ in 0..ATTEMPTS {
+        let response = transport.get(known_etag.as_deref()).await.map_err(WebDavError::Transport)?;
+        let (remote, etag) = Remote::of(response)?.resolve(base.as_ref(), known_etag.as_deref());
+
+        let (merged, pushed) = merge(local, base.as_ref(), remote.as_ref());
+        let etag = if pushed {
+            // Only replaces the version that was downloaded, or creates the file if there was none
+            let precondition = match (etag, &remote) {
+                (Some(etag), _) => Precondition::Matches(etag),
+                (None, None) => Precondition::Missing,
+                (None, Some(_)) => return Err(WebDavError::NoEtag),
+            };
+            let response = transport.put(merged.to_json(), precondition).await.map_err(WebDavError::Transport)?;
+            match response.status {
+                412 => continue,
+                401 => return Err(WebDavError::Unauthorized),
+                200..=299 => response.etag,
+                status => return Err(WebDavError::Status(status)),
+            }
+        } else {
+            etag
+        };
+
+        return Ok(Synced::new(local, merged, pushed, url, etag));
+    }
+
+    Err(WebDavError::Busy)
+}
+
+/// `sync` for a transport whose requests block rather than wait, like the
+/// command line client's.
+pub fn sync_blocking<T: Transport>(transport: &T, url: &str, local: &DataFile, state: &SyncState) -> Result<Synced, WebDavError<T::Error>> {
+    match pin!(sync(transport, url, local, state)).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(result) => result,
+        Poll::Pending => panic!("a blocking transport never waits"),
+    }
+}
+
+/// The `Authorization` header for HTTP basic authentication.
+pub fn basic_auth(username: &str, password: &str) -> String {
+    format!("Basic {}", STANDARD.encode(format!("{username}:{password}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+
+    use crate::log::{Entry, EntryType, LogData};
+    use crate::user::UserProfile;
+
+    const URL: &str = "https://dav.example.com/nazad.json";
+
+    /// A server keeping the file in memory, which may leave out ETags.
+    #[derive(Default)]
+    struct Server {
+        file: RefCell<Option<(String, u32)>>,
+        sends_etags: bool,
+        uploads: RefCell<usize>,
+    }
+
+    impl Server {
+        fn etag(&self) -> Option<String> {
+            let file = self.file.borrow();
+            file.as_ref().filter(|_| self.sends_etags).map(|(_, version)| format!("\"{version}\""))
+        }
+    }
+
+    impl Transport for Server {
+        type Error = String;
+
+        async fn get(&self, known_etag: Option<&str>) -> Result<Response, String> {
+            let etag = self.etag();
+            let (status, body) = match &*self.file.borrow() {
+                None => (404, None),
+                Some(_) if known_etag.is_some() && known_etag == etag.as_deref() => (304, None),
+                Some((body, _)) => (200, Some(body.clone())),
+            };
+            Ok(Response { status, etag, body })
+        }
+
+        async fn put(&self, body: String, precondition: Precondition) -> Result<Response, String> {
+            let allowed = match precondition {
+                Precondition::Matches(etag) => Some(etag) == self.etag(),
+                Precondition::Missing => self.file.borrow().is_none(),
+            };
+            if !allowed {
+                return Ok(Response { status: 412, etag: None, body: None });
+            }
+            let version = self.file.borrow().as_ref().map_or(0, |(_, version)| version + 1);
+            *self.file.borrow_mut() = Some((body, version));
+            *self.uploads.borrow_mut() += 1;
+            Ok(Response { status: 201, etag: self.etag(), body: None })
+        }
+    }
+
+    fn logged(title: &str) -> DataFile {
+        let mut log = LogData::new();
+        log.add(Entry::from_data(title.to_string(), EntryType::Reading, 30, Default::default()));
+        DataFile::new(log, UserProfile::new())
+    }
+
+    #[test]
+    fn syncs_both_ways() {
+        let server = Server {
+            sends_etags: true,
+            ..Server::default()
+        };
+        let laptop = sync_blocking(&server, URL, &logged("Chapter 3"), &SyncState::default()).unwrap();
+        assert!(laptop.pushed && !laptop.pulled);
+
+        let phone = sync_blocking(&server, URL, &logged("Podcast"), &SyncState::default()).unwrap();
+        assert!(phone.pushed && phone.pulled);
+        assert_eq!(phone.data.log.entries().len(), 2);
+
+        let laptop = sync_blocking(&server, URL, &laptop.data, &laptop.state).unwrap();
+        assert!(laptop.pulled && !laptop.pushed);
+        assert_eq!(laptop.data, phone.data);
+    }
+
+    #[test]
+    fn never_uploads_over_a_file_without_an_etag() {
+        let server = Server::default();
+        sync_blocking(&server, URL, &logged("Chapter 3"), &SyncState::default()).unwrap();
+
+        let result = sync_blocking(&server, URL, &logged("Podcast"), &SyncState::default());
+        assert!(matches!(result, Err(WebDavError::NoEtag)));
+        assert_eq!(*server.uploads.borrow(), 1);
+    }
+
+    #[test]
+    fn only_the_same_url_has_a_base() {
+        let file = DataFile::new(LogData::new(), UserProfile::new());
+        let state = Synced::new(&file, file.clone(), true, "https://dav.example.com/nazad.json", Some("\"1\"".to_string())).state;
+
+        assert_eq!(state.base_for("https://dav.example.com/nazad.json"), (Some(file), Some("\"1\"".to_string())));
+        assert_eq!(state.base_for("https://dav.example.com/other.json"), (None, None));
+        assert_eq!(SyncState::from_json(&state.to_json()).unwrap(), state);
+    }
+
+    #[test]
+    fn encodes_basic_auth() {
+        assert_eq!(basic_auth("ana", "app-password"), "Basic YW5hOmFwcC1wYXNzd29yZA==");
+    }
+}
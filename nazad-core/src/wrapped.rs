@@ -0,0 +1,364 @@
+//! A year in review: total time, the best month, the longest streak, the
+//! resources used most, goals achieved and the time per skill. Exports as a
+//! standalone SVG image or HTML page, generated here without any service.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::log::LogData;
+use crate::review::PeriodReport;
+use crate::settings::Settings;
+use crate::stats::{self, ResourceTotal, Summary};
+use crate::user::UserProfile;
+
+/// Number of resources listed.
+pub const TOP_RESOURCES: usize = 5;
+
+/// Number of goals drawn in the image before the rest are summed up.
+const GOALS_SHOWN: usize = 5;
+
+const WIDTH: usize = 600;
+const MARGIN: usize = 40;
+const BAR_COLOR: &str = "#3b82f6";
+const BEST_COLOR: &str = "#eab308";
+const TEXT_COLOR: &str = "#e5e7eb";
+const MUTED_COLOR: &str = "#a1a1aa";
+
+/// Days in a row with at least one entry.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Streak {
+    pub days: usize,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct YearInReview {
+    pub year: i32,
+    pub summary: Summary,
+    /// First day and minutes of every month, January first.
+    pub months: Vec<(NaiveDate, usize)>,
+    /// Only counts days of the year itself.
+    pub longest_streak: Option<Streak>,
+    pub top_resources: Vec<ResourceTotal>,
+    pub goals_completed: Vec<String>,
+}
+
+impl YearInReview {
+    pub fn of(log: &LogData, user: &UserProfile, year: i32) -> Self {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1).expect("the first of January exists");
+        let (from, to) = stats::year_of(first);
+        let report = PeriodReport::of(log, user, (from, to));
+
+        let months = (1..=12)
+            .map(|month| {
+                let start = NaiveDate::from_ymd_opt(year, month, 1).expect("the first of a month exists");
+                let (from, to) = stats::month_of(start);
+                (start, Summary::of(log, from, to).total_minutes)
+            })
+            .collect();
+
+        let mut days: Vec<NaiveDate> = log
+            .entries()
+            .iter()
+            .filter_map(|entry| entry.date().to_naive())
+            .filter(|day| from <= *day && *day <= to)
+            .collect();
+        days.sort();
+        days.dedup();
+
+        let mut top_resources = stats::resources_between(log, from, to);
+        top_resources.truncate(TOP_RESOURCES);
+
+        Self {
+            year,
+            summary: report.summary,
+            months,
+            longest_streak: longest_streak(&days),
+            top_resources,
+            goals_completed: report.goals_completed,
+        }
+    }
+
+    /// The month with the most time, the earlier one on a tie. `None` for a
+    /// year without entries.
+    pub fn best_month(&self) -> Option<(NaiveDate, usize)> {
+        self.months
+            .iter()
+            .filter(|(_, minutes)| *minutes > 0)
+            .fold(None, |best: Option<(NaiveDate, usize)>, month| match best {
+                Some(best) if best.1 >= month.1 => Some(best),
+                _ => Some(*month),
+            })
+    }
+
+    /// The review as an image, drawn in the colors of the dashboard.
+    pub fn to_svg(&self, settings: &Settings) -> String {
+        let mut svg = vec![];
+        let mut y = 64;
+
+        svg.push(text(MARGIN, y, 32, TEXT_COLOR, BOLD, &format!("{} in review", self.year)));
+        y += 64;
+
+        let streak = self.longest_streak.map(|streak| streak.days).unwrap_or_default();
+        let headline = [
+            (settings.format_duration(self.summary.total_minutes), "logged"),
+            (self.summary.active_days.to_string(), "active days"),
+            (streak.to_string(), "days longest streak"),
+        ];
+        for (column, (value, label)) in headline.iter().enumerate() {
+            let x = MARGIN + column * 180;
+            svg.push(text(x, y, 30, BAR_COLOR, BOLD, value));
+            svg.push(text(x, y + 20, 13, MUTED_COLOR, "", label));
+        }
+        y += 60;
+
+        let best = self.best_month();
+        let best_line = match best {
+            Some((month, minutes)) => format!("Best month: {}, {}", month.format("%B"), settings.format_duration(minutes)),
+            None => "Nothing logged this year".to_string(),
+        };
+        svg.push(text(MARGIN, y, 16, TEXT_COLOR, "", &best_line));
+        if let Some(streak) = self.longest_streak {
+            let streak_line = format!(
+                "Longest streak: {} to {}",
+                settings.format_date(streak.from),
+                settings.format_date(streak.to)
+            );
+            svg.push(text(MARGIN, y + 22, 13, MUTED_COLOR, "", &streak_line));
+        }
+        y += 40;
+
+        // Bars per month, the best one highlighted
+        let chart_height = 120;
+        let most = self.months.iter().map(|(_, minutes)| *minutes).max().unwrap_or_default().max(1);
+        for (index, (month, minutes)) in self.months.iter().enumerate() {
+            let x = MARGIN + index * 44;
+            let height = minutes * chart_height / most;
+            let color = if best.is_some_and(|(best, _)| best == *month) { BEST_COLOR } else { BAR_COLOR };
+            svg.push(format!(
+                r#"<rect x="{x}" y="{}" width="36" height="{height}" rx="3" fill="{color}"/>"#,
+                y + chart_height - height
+            ));
+            let name = month.format("%b").to_string();
+            svg.push(text(x + 18, y + chart_height + 16, 11, MUTED_COLOR, CENTERED, &name));
+        }
+        y += chart_height + 56;
+
+        let skills: Vec<_> = self.summary.per_type.iter().filter(|(_, minutes)| *minutes > 0).collect();
+        if !skills.is_empty() {
+            svg.push(text(MARGIN, y, 18, TEXT_COLOR, BOLD, "Skills"));
+            y += 28;
+            let most = skills.iter().map(|(_, minutes)| *minutes).max().unwrap_or_default().max(1);
+            for (entry_type, minutes) in skills {
+                svg.push(text(MARGIN, y, 14, TEXT_COLOR, "", entry_type.name()));
+                svg.push(format!(
+                    r#"<rect x="150" y="{}" width="{}" height="12" rx="3" fill="{BAR_COLOR}"/>"#,
+                    y - 11,
+                    (minutes * 300 / most).max(1)
+                ));
+                svg.push(text(460, y, 14, MUTED_COLOR, "", &settings.format_duration(*minutes)));
+                y += 24;
+            }
+            y += 24;
+        }
+
+        if !self.top_resources.is_empty() {
+            svg.push(text(MARGIN, y, 18, TEXT_COLOR, BOLD, "Most used"));
+            y += 28;
+            for total in &self.top_resources {
+                let resource = format!("{} ({})", total.resource, total.entry_type.name());
+                svg.push(text(MARGIN, y, 14, TEXT_COLOR, "", &shorten(&resource, 48)));
+                svg.push(text(460, y, 14, MUTED_COLOR, "", &settings.format_duration(total.minutes)));
+                y += 24;
+            }
+            y += 24;
+        }
+
+        if !self.goals_completed.is_empty() {
+            svg.push(text(MARGIN, y, 18, TEXT_COLOR, BOLD, &format!("{} goals achieved", self.goals_completed.len())));
+            y += 28;
+            for goal in self.goals_completed.iter().take(GOALS_SHOWN) {
+                svg.push(text(MARGIN, y, 14, TEXT_COLOR, "", &format!("✓ {}", shorten(goal, 60))));
+                y += 24;
+            }
+            if self.goals_completed.len() > GOALS_SHOWN {
+                let more = format!("and {} more", self.goals_completed.len() - GOALS_SHOWN);
+                svg.push(text(MARGIN, y, 14, MUTED_COLOR, "", &more));
+                y += 24;
+            }
+            y += 24;
+        }
+
+        let height = y;
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" "#,
+                r#"font-family="Helvetica, Arial, sans-serif">"#,
+                r##"<rect width="{width}" height="{height}" rx="24" fill="#27272a"/>{body}</svg>"##
+            ),
+            width = WIDTH,
+            height = height,
+            body = svg.join("")
+        )
+    }
+
+    /// A page showing the image, with the time per month as a table below
+    /// for copying.
+    pub fn to_html(&self, settings: &Settings) -> String {
+        let rows: String = self
+            .months
+            .iter()
+            .map(|(month, minutes)| format!("<tr><td>{}</td><td>{}</td></tr>", month.format("%B"), settings.format_duration(*minutes)))
+            .collect();
+
+        format!(
+            concat!(
+                r#"<!DOCTYPE html><html lang="en"><head><meta charset="utf-8">"#,
+                r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#,
+                "<title>{year} in review</title><style>",
+                "body{{background:#18181b;color:#e5e7eb;font-family:Helvetica,Arial,sans-serif;",
+                "display:flex;flex-direction:column;align-items:center;gap:2rem;padding:2rem}}",
+                "svg{{max-width:100%;height:auto}}td{{padding:0.25rem 1rem}}",
+                "</style></head><body>{svg}<table>{rows}</table></body></html>"
+            ),
+            year = self.year,
+            svg = self.to_svg(settings),
+            rows = rows
+        )
+    }
+}
+
+/// Years with entries and the current one, newest first.
+pub fn years(log: &LogData, today: NaiveDate) -> Vec<i32> {
+    let mut years: Vec<i32> = log
+        .entries()
+        .iter()
+        .filter_map(|entry| entry.date().to_naive())
+        .map(|day| day.year())
+        .chain([today.year()])
+        .collect();
+    years.sort_by(|a, b| b.cmp(a));
+    years.dedup();
+    years
+}
+
+/// The longest run of consecutive days in sorted, distinct `days`, the
+/// earlier one on a tie.
+fn longest_streak(days: &[NaiveDate]) -> Option<Streak> {
+    let mut longest: Option<Streak> = None;
+    let mut current: Option<Streak> = None;
+
+    for day in days {
+        current = match current {
+            Some(streak) if streak.to + Duration::days(1) == *day => Some(Streak {
+                days: streak.days + 1,
+                to: *day,
+                ..streak
+            }),
+            _ => Some(Streak { days: 1, from: *day, to: *day }),
+        };
+        if longest.is_none_or(|longest| current.is_some_and(|current| current.days > longest.days)) {
+            longest = current;
+        }
+    }
+    longest
+}
+
+const BOLD: &str = r#" font-weight="bold""#;
+const CENTERED: &str = r#" text-anchor="middle""#;
+
+/// A `text` element, `attributes` like `BOLD` added to it.
+fn text(x: usize, y: usize, size: usize, color: &str, attributes: &str, content: &str) -> String {
+    format!(
+        r#"<text x="{x}" y="{y}" font-size="{size}" fill="{color}"{attributes}>{}</text>"#,
+        escape(content)
+    )
+}
+
+fn shorten(text: &str, length: usize) -> String {
+    match text.char_indices().nth(length) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Escapes text for use in SVG and HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::log::{Entry, EntryType};
+    use crate::user::Goal;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn log() -> LogData {
+        let mut log = LogData::new();
+        let entries = [
+            (day(3, 1), EntryType::Reading, 60, "Book"),
+            (day(3, 2), EntryType::Reading, 30, "Book"),
+            (day(3, 3), EntryType::Listening, 45, "Podcast <Daily>"),
+            (day(5, 10), EntryType::Listening, 60, "Podcast <Daily>"),
+            (day(5, 11), EntryType::Listening, 30, "Podcast <Daily>"),
+            (NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(), EntryType::Reading, 120, "Book"),
+        ];
+        for (date, entry_type, minutes, resource) in entries {
+            log.add(Entry::from_data("Session".to_string(), entry_type, minutes, Date::from_naive(date)).with_resource(resource.to_string()));
+        }
+        log
+    }
+
+    #[test]
+    fn sums_up_the_year() {
+        let mut user = UserProfile::new();
+        user.import_goals(vec![Goal::from_data("Finish the book".to_string(), false, None)]);
+        let goal = user.goals()[1].clone();
+        user.mark_goal_as_completed_on(&goal, day(3, 2));
+
+        let review = YearInReview::of(&log(), &user, 2026);
+
+        assert_eq!(review.summary.total_minutes, 225);
+        assert_eq!(review.best_month(), Some((day(3, 1), 135)));
+        assert_eq!(review.longest_streak, Some(Streak { days: 3, from: day(3, 1), to: day(3, 3) }));
+        assert_eq!(review.top_resources[0].resource, "Podcast <Daily>");
+        assert_eq!(review.top_resources[1].minutes, 90);
+        assert_eq!(review.goals_completed, ["Finish the book"]);
+        assert_eq!(years(&log(), day(10, 19)), [2026, 2025]);
+    }
+
+    #[test]
+    fn exports_escaped_images_and_pages() {
+        let review = YearInReview::of(&log(), &UserProfile::new(), 2026);
+        let svg = review.to_svg(&Settings::default());
+        let html = review.to_html(&Settings::default());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("Podcast &lt;Daily&gt;"));
+        assert!(!svg.contains("<Daily>"));
+        assert!(svg.contains("Best month: March"));
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(&svg));
+
+        let empty = YearInReview::of(&LogData::new(), &UserProfile::new(), 2026);
+        assert_eq!(empty.best_month(), None);
+        assert_eq!(empty.longest_streak, None);
+        assert!(empty.to_svg(&Settings::default()).contains("Nothing logged this year"));
+    }
+}
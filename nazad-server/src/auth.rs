@@ -0,0 +1,114 @@
+//! Password hashing and session tokens. Passwords are stored as Argon2
+//! hashes; tokens are random and only their SHA-256 hash is stored, so a
+//! leaked database doesn't hand out working sessions. Logins to an account
+//! slow down after a few failed attempts, and logins to accounts that don't
+//! exist take as long as wrong passwords, so timing doesn't tell them apart.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Argon2 hashes passwords of any length")
+        .to_string()
+}
+
+// Checked in place of a missing account's hash
+static DUMMY_HASH: LazyLock<String> = LazyLock::new(|| hash_password(&new_token()));
+
+/// Checks `password` against the account's hash, or against a dummy one
+/// when there's no account, which fails just as slowly.
+pub fn verify_password(password: &str, hash: Option<&str>) -> bool {
+    let verified = match PasswordHash::new(hash.unwrap_or(&DUMMY_HASH)) {
+        Ok(hash) => Argon2::default().verify_password(password.as_bytes(), &hash).is_ok(),
+        Err(_) => false,
+    };
+    verified && hash.is_some()
+}
+
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex(&bytes)
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// Attempts an account gets before they have to wait, and the longest wait
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_WAIT: Duration = Duration::from_secs(5 * 60);
+// Failed attempts this long ago are forgotten
+const FORGET_AFTER: Duration = Duration::from_secs(60 * 60);
+// Usernames tracked at most, past that the one tried longest ago is forgotten
+const MAX_TRACKED: usize = 10_000;
+
+struct Attempts {
+    count: u32,
+    last: Instant,
+}
+
+impl Attempts {
+    // Doubles with every attempt after the free ones, from a second on
+    fn wait(&self) -> Duration {
+        match self.count.checked_sub(FREE_ATTEMPTS) {
+            None | Some(0) => Duration::ZERO,
+            Some(over) => Duration::from_secs(1 << (over - 1).min(16)).min(MAX_WAIT),
+        }
+    }
+}
+
+/// Login attempts per username that haven't succeeded yet, kept in memory.
+#[derive(Clone, Default)]
+pub struct LoginThrottle {
+    attempts: Arc<Mutex<HashMap<String, Attempts>>>,
+}
+
+impl LoginThrottle {
+    /// Counts an attempt to log in as `username`, or returns how long it has
+    /// to wait. Attempts count before the password is checked, so requests
+    /// sent at the same time don't get around the wait.
+    pub fn attempt(&self, username: &str) -> Result<(), Duration> {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let username = username.to_lowercase();
+        if !attempts.contains_key(&username) && attempts.len() >= MAX_TRACKED {
+            attempts.retain(|_, attempt| now.duration_since(attempt.last) < FORGET_AFTER);
+            let oldest = attempts.iter().min_by_key(|(_, attempt)| attempt.last).map(|(name, _)| name.clone());
+            if let Some(oldest) = oldest.filter(|_| attempts.len() >= MAX_TRACKED) {
+                attempts.remove(&oldest);
+            }
+        }
+
+        let attempt = attempts.entry(username).or_insert(Attempts { count: 0, last: now });
+        // Forgotten like the attempts of usernames no longer tracked
+        if now.duration_since(attempt.last) >= FORGET_AFTER {
+            attempt.count = 0;
+        }
+        let ready = attempt.last + attempt.wait();
+        if ready > now {
+            return Err(ready - now);
+        }
+        attempt.count += 1;
+        attempt.last = now;
+        Ok(())
+    }
+
+    pub fn succeeded(&self, username: &str) {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        attempts.remove(&username.to_lowercase());
+    }
+}
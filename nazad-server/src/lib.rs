@@ -0,0 +1,49 @@
+//! Self-hosted server for Nazad. Keeps the log, goals, profile and settings in
+//! SQLite and serves them over the REST API described in `nazad_core::api`,
+//! separately for every registered account.
+
+mod auth;
+mod routes;
+pub mod store;
+
+use std::path::Path;
+
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::routing::{get, post, put};
+use axum::Router;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::services::{ServeDir, ServeFile};
+
+use nazad_core::api::{ENTRIES_PATH, GOALS_PATH, LOGIN_PATH, LOGOUT_PATH, PROFILE_PATH, REGISTER_PATH, SETTINGS_PATH, SYNC_PATH};
+
+use store::Store;
+
+pub fn app(store: Store) -> Router {
+    // The dashboard may be served by `trunk serve` on another port. A wildcard
+    // doesn't cover the Authorization header, so the headers are listed.
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE]);
+
+    Router::new()
+        .route(REGISTER_PATH, post(routes::register))
+        .route(LOGIN_PATH, post(routes::login))
+        .route(LOGOUT_PATH, post(routes::logout))
+        .route(ENTRIES_PATH, get(routes::list_entries).post(routes::create_entry))
+        .route(&format!("{ENTRIES_PATH}/{{id}}"), put(routes::put_entry).delete(routes::delete_entry))
+        .route(GOALS_PATH, get(routes::list_goals).post(routes::create_goal))
+        .route(&format!("{GOALS_PATH}/{{id}}"), put(routes::put_goal).delete(routes::delete_goal))
+        .route(PROFILE_PATH, get(routes::get_profile).put(routes::put_profile))
+        .route(SETTINGS_PATH, get(routes::get_settings).put(routes::put_settings))
+        .route(SYNC_PATH, post(routes::sync))
+        .layer(cors)
+        .with_state(store)
+}
+
+/// Also serves the built dashboard, e.g. trunk's `dist` directory, from `/`.
+pub fn app_with_static_files(store: Store, static_dir: &Path) -> Router {
+    // Pages like /history only exist in the dashboard, so unknown paths get its index.html
+    let index = ServeFile::new(static_dir.join("index.html"));
+    app(store).fallback_service(ServeDir::new(static_dir).fallback(index))
+}
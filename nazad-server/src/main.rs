@@ -0,0 +1,61 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::Parser;
+
+use nazad_server::store::Store;
+
+#[derive(Parser)]
+#[command(name = "nazad-server", version, about = "Self-hosted storage for the Nazad dashboard")]
+struct Cli {
+    /// SQLite database, created if it doesn't exist
+    #[arg(long, env = "NAZAD_DATABASE", default_value = "nazad.sqlite")]
+    database: PathBuf,
+
+    #[arg(long, env = "NAZAD_LISTEN", default_value = "127.0.0.1:3000")]
+    listen: SocketAddr,
+
+    /// Days until a login has to be repeated
+    #[arg(long, env = "NAZAD_SESSION_DAYS", default_value_t = 30)]
+    session_days: u64,
+
+    /// Directory with the built dashboard to serve alongside the API
+    #[arg(long)]
+    static_dir: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let store = match Store::open(&cli.database) {
+        Ok(store) => store.with_session_lifetime(Duration::from_secs(cli.session_days * 24 * 60 * 60)),
+        Err(error) => {
+            eprintln!("Could not open {}: {error}", cli.database.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let app = match &cli.static_dir {
+        Some(static_dir) => nazad_server::app_with_static_files(store, static_dir),
+        None => nazad_server::app(store),
+    };
+
+    let listener = match tokio::net::TcpListener::bind(cli.listen).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on {}: {error}", cli.listen);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("Serving on http://{}", cli.listen);
+    if let Err(error) = axum::serve(listener, app).await {
+        eprintln!("{error}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use axum::extract::{FromRequestParts, Path, State};
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+use nazad_core::api::{Credentials, Profile, Session, MIN_PASSWORD_LENGTH};
+use nazad_core::log::Entry;
+use nazad_core::settings::Settings;
+use nazad_core::sync::{SyncRequest, SyncResponse};
+use nazad_core::user::Goal;
+
+use crate::store::{Login, Store, StoreError, UserId};
+
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized,
+    NotFound,
+    UsernameTaken,
+    TooManyAttempts(Duration),
+    Store(StoreError),
+}
+
+impl From<StoreError> for ApiError {
+    fn from(error: StoreError) -> Self {
+        ApiError::Store(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED.into_response(),
+            ApiError::NotFound => StatusCode::NOT_FOUND.into_response(),
+            ApiError::UsernameTaken => (StatusCode::CONFLICT, "username is taken").into_response(),
+            ApiError::TooManyAttempts(wait) => {
+                let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                (StatusCode::TOO_MANY_REQUESTS, [(RETRY_AFTER, seconds.to_string())], "too many failed logins, try again later").into_response()
+            }
+            ApiError::Store(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+        }
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+// SQLite and Argon2 block the thread, so store calls run where that doesn't hold up other requests
+async fn blocking<T: Send + 'static>(store: &Store, call: impl FnOnce(Store) -> Result<T, StoreError> + Send + 'static) -> ApiResult<T> {
+    let store = store.clone();
+    match tokio::task::spawn_blocking(move || call(store)).await {
+        Ok(result) => Ok(result?),
+        Err(error) => std::panic::resume_unwind(error.into_panic()),
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(AUTHORIZATION)?.to_str().ok()?.strip_prefix("Bearer ")
+}
+
+/// The account behind the request's session token, rejects requests without a valid one.
+pub struct Owner(UserId);
+
+impl FromRequestParts<Store> for Owner {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, store: &Store) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(ApiError::Unauthorized)?.to_string();
+        match blocking(store, move |store| store.session_user(&token)).await? {
+            Some(user) => Ok(Owner(user)),
+            None => Err(ApiError::Unauthorized),
+        }
+    }
+}
+
+fn deleted(found: bool) -> ApiResult<StatusCode> {
+    if found {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound)
+    }
+}
+
+async fn start_session(store: &Store, user: UserId, username: String) -> ApiResult<Json<Session>> {
+    let token = blocking(store, move |store| store.create_session(user)).await?;
+    Ok(Json(Session { username, token }))
+}
+
+pub async fn register(State(store): State<Store>, Json(credentials): Json<Credentials>) -> ApiResult<(StatusCode, Json<Session>)> {
+    let credentials = Credentials {
+        username: credentials.username.trim().to_string(),
+        password: credentials.password,
+    };
+    if credentials.username.is_empty() {
+        return Err(ApiError::BadRequest("username is empty".to_string()));
+    }
+    if credentials.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ApiError::BadRequest(format!("password needs at least {MIN_PASSWORD_LENGTH} characters")));
+    }
+
+    let username = credentials.username.clone();
+    let user = blocking(&store, move |store| store.register(&credentials)).await?.ok_or(ApiError::UsernameTaken)?;
+    Ok((StatusCode::CREATED, start_session(&store, user, username).await?))
+}
+
+pub async fn login(State(store): State<Store>, Json(credentials): Json<Credentials>) -> ApiResult<Json<Session>> {
+    let credentials = Credentials {
+        username: credentials.username.trim().to_string(),
+        password: credentials.password,
+    };
+    let username = credentials.username.clone();
+    match blocking(&store, move |store| store.login(&credentials)).await? {
+        Login::User(user) => start_session(&store, user, username).await,
+        Login::WrongPassword => Err(ApiError::Unauthorized),
+        Login::TooSoon(wait) => Err(ApiError::TooManyAttempts(wait)),
+    }
+}
+
+pub async fn logout(State(store): State<Store>, headers: HeaderMap) -> ApiResult<StatusCode> {
+    let token = bearer_token(&headers).ok_or(ApiError::Unauthorized)?.to_string();
+    blocking(&store, move |store| store.end_session(&token)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_entries(State(store): State<Store>, Owner(owner): Owner) -> ApiResult<Json<Vec<Entry>>> {
+    Ok(Json(blocking(&store, move |store| store.entries(owner)).await?))
+}
+
+pub async fn create_entry(State(store): State<Store>, Owner(owner): Owner, Json(entry): Json<Entry>) -> ApiResult<(StatusCode, Json<Entry>)> {
+    Ok((StatusCode::CREATED, Json(blocking(&store, move |store| store.add_entry(owner, entry)).await?)))
+}
+
+// The id in the path wins over the one in the body
+pub async fn put_entry(State(store): State<Store>, Owner(owner): Owner, Path(id): Path<usize>, Json(entry): Json<Entry>) -> ApiResult<Json<Entry>> {
+    let entry = entry.with_id(id);
+    Ok(Json(blocking(&store, move |store| store.put_entry(owner, &entry).map(|()| entry)).await?))
+}
+
+pub async fn delete_entry(State(store): State<Store>, Owner(owner): Owner, Path(id): Path<usize>) -> ApiResult<StatusCode> {
+    deleted(blocking(&store, move |store| store.delete_entry(owner, id)).await?)
+}
+
+pub async fn list_goals(State(store): State<Store>, Owner(owner): Owner) -> ApiResult<Json<Vec<Goal>>> {
+    Ok(Json(blocking(&store, move |store| store.goals(owner)).await?))
+}
+
+pub async fn create_goal(State(store): State<Store>, Owner(owner): Owner, Json(goal): Json<Goal>) -> ApiResult<(StatusCode, Json<Goal>)> {
+    Ok((StatusCode::CREATED, Json(blocking(&store, move |store| store.add_goal(owner, goal)).await?)))
+}
+
+pub async fn put_goal(State(store): State<Store>, Owner(owner): Owner, Path(id): Path<usize>, Json(goal): Json<Goal>) -> ApiResult<Json<Goal>> {
+    let goal = goal.with_id(id);
+    Ok(Json(blocking(&store, move |store| store.put_goal(owner, &goal).map(|()| goal)).await?))
+}
+
+pub async fn delete_goal(State(store): State<Store>, Owner(owner): Owner, Path(id): Path<usize>) -> ApiResult<StatusCode> {
+    deleted(blocking(&store, move |store| store.delete_goal(owner, id)).await?)
+}
+
+pub async fn get_profile(State(store): State<Store>, Owner(owner): Owner) -> ApiResult<Json<Profile>> {
+    Ok(Json(blocking(&store, move |store| store.profile(owner)).await?))
+}
+
+pub async fn put_profile(State(store): State<Store>, Owner(owner): Owner, Json(profile): Json<Profile>) -> ApiResult<Json<Profile>> {
+    Ok(Json(blocking(&store, move |store| store.put_profile(owner, &profile).map(|()| profile)).await?))
+}
+
+pub async fn get_settings(State(store): State<Store>, Owner(owner): Owner) -> ApiResult<Json<Settings>> {
+    Ok(Json(blocking(&store, move |store| store.settings(owner)).await?))
+}
+
+pub async fn put_settings(State(store): State<Store>, Owner(owner): Owner, Json(settings): Json<Settings>) -> ApiResult<Json<Settings>> {
+    Ok(Json(blocking(&store, move |store| store.put_settings(owner, &settings).map(|()| settings)).await?))
+}
+
+pub async fn sync(State(store): State<Store>, Owner(owner): Owner, Json(request): Json<SyncRequest>) -> ApiResult<Json<SyncResponse>> {
+    Ok(Json(blocking(&store, move |store| store.sync(owner, request)).await?))
+}
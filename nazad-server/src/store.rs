@@ -0,0 +1,386 @@
+//! SQLite storage for the server. Every account's entries, goals, profile and
+//! settings are kept as sync records, one row each. Changes from the REST API
+//! and the sync endpoint go through a `Hub` holding just the rows they touch,
+//! and reads take just the rows they need too. All calls block, so the API
+//! runs them on tokio's blocking threads.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+
+use nazad_core::api::{Credentials, Profile};
+use nazad_core::log::Entry;
+use nazad_core::settings::Settings;
+use nazad_core::sync::{Hub, Key, Record, SyncRequest, SyncResponse};
+use nazad_core::user::Goal;
+
+use crate::auth;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS users (
+        id INTEGER PRIMARY KEY,
+        username TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        password_hash TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS sessions (
+        token_hash TEXT PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users(id),
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS records (
+        owner INTEGER NOT NULL REFERENCES users(id),
+        key TEXT NOT NULL,
+        revision INTEGER NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (owner, key)
+    );
+";
+
+/// Stored in SQLite's `user_version`, for telling future layouts apart.
+const SCHEMA_VERSION: i64 = 1;
+
+// Patterns for the keys of entry and goal records, see `Key`'s `Display`
+const ENTRY_KEYS: &str = "entry/%";
+const GOAL_KEYS: &str = "goal/%";
+
+/// How long a session token stays valid after logging in.
+pub const SESSION_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Row id of an account.
+pub type UserId = i64;
+
+// The rows a change through the hub reads and writes
+enum Touched {
+    Key(Key),
+    /// What a sync sends back and the keys of the pushed records.
+    Sync { since: u64, keys: Vec<Key> },
+}
+
+pub enum Login {
+    User(UserId),
+    WrongPassword,
+    /// Too many attempts failed lately, the next one has to wait this long.
+    TooSoon(Duration),
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(error) => write!(f, "database error: {error}"),
+            StoreError::Json(error) => write!(f, "stored document is not valid JSON: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        StoreError::Sqlite(error)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(error: serde_json::Error) -> Self {
+        StoreError::Json(error)
+    }
+}
+
+/// Handle to the database, cheap to clone for every request.
+#[derive(Clone)]
+pub struct Store {
+    connection: Arc<Mutex<Connection>>,
+    logins: auth::LoginThrottle,
+    session_lifetime: Duration,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// A store that is gone once the server stops, for tests and trying things out.
+    pub fn in_memory() -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, StoreError> {
+        connection.execute_batch(SCHEMA)?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            logins: auth::LoginThrottle::default(),
+            session_lifetime: SESSION_LIFETIME,
+        })
+    }
+
+    /// Lets sessions expire after `lifetime` instead of `SESSION_LIFETIME`.
+    pub fn with_session_lifetime(mut self, lifetime: Duration) -> Self {
+        self.session_lifetime = lifetime;
+        self
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock leaves SQLite itself consistent
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Creates an account, returns None if the username is taken.
+    pub fn register(&self, credentials: &Credentials) -> Result<Option<UserId>, StoreError> {
+        let password_hash = auth::hash_password(&credentials.password);
+        let connection = self.connection();
+        let inserted = connection.execute(
+            "INSERT INTO users (username, password_hash) VALUES (?1, ?2) ON CONFLICT (username) DO NOTHING",
+            params![credentials.username, password_hash],
+        )?;
+        Ok((inserted > 0).then(|| connection.last_insert_rowid()))
+    }
+
+    /// Returns the account the credentials belong to, if the password is right
+    /// and the account didn't see too many failed attempts lately.
+    pub fn login(&self, credentials: &Credentials) -> Result<Login, StoreError> {
+        if let Err(wait) = self.logins.attempt(&credentials.username) {
+            return Ok(Login::TooSoon(wait));
+        }
+        let account: Option<(UserId, String)> = self
+            .connection()
+            .query_row(
+                "SELECT id, password_hash FROM users WHERE username = ?1",
+                [&credentials.username],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        let verified = auth::verify_password(&credentials.password, account.as_ref().map(|(_, hash)| hash.as_str()));
+        match account.filter(|_| verified) {
+            Some((id, _)) => {
+                self.logins.succeeded(&credentials.username);
+                Ok(Login::User(id))
+            }
+            None => Ok(Login::WrongPassword),
+        }
+    }
+
+    // Sessions created at or before this second have expired
+    fn session_cutoff(&self) -> i64 {
+        now_ms().saturating_sub(self.session_lifetime.as_millis() as u64) as i64 / 1000
+    }
+
+    /// Starts a session and returns its token, which is only known to the caller.
+    /// Expired sessions of all accounts are cleared out on the way.
+    pub fn create_session(&self, user: UserId) -> Result<String, StoreError> {
+        let token = auth::new_token();
+        let connection = self.connection();
+        connection.execute("DELETE FROM sessions WHERE created_at <= ?1", [self.session_cutoff()])?;
+        connection.execute(
+            "INSERT INTO sessions (token_hash, user_id, created_at) VALUES (?1, ?2, ?3)",
+            params![auth::hash_token(&token), user, now_ms() as i64 / 1000],
+        )?;
+        Ok(token)
+    }
+
+    /// The account of a session that hasn't expired yet.
+    pub fn session_user(&self, token: &str) -> Result<Option<UserId>, StoreError> {
+        Ok(self
+            .connection()
+            .query_row(
+                "SELECT user_id FROM sessions WHERE token_hash = ?1 AND created_at > ?2",
+                params![auth::hash_token(token), self.session_cutoff()],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn end_session(&self, token: &str) -> Result<(), StoreError> {
+        self.connection().execute("DELETE FROM sessions WHERE token_hash = ?1", [auth::hash_token(token)])?;
+        Ok(())
+    }
+
+    // The account's live items under keys matching `pattern`, in the order of their keys
+    fn items<T: DeserializeOwned>(&self, owner: UserId, pattern: &str) -> Result<Vec<T>, StoreError> {
+        let connection = self.connection();
+        let mut statement = connection.prepare("SELECT data FROM records WHERE owner = ?1 AND key LIKE ?2")?;
+        let rows = statement.query_map(params![owner, pattern], |row| row.get::<_, String>(0))?;
+
+        let mut records = vec![];
+        for row in rows {
+            records.push(serde_json::from_str::<Record>(&row?)?);
+        }
+        records.sort_by_key(|record| record.key);
+        Ok(records.iter().filter_map(Record::live_value).collect())
+    }
+
+    // The profile or settings document, the default before it was first stored
+    fn document<T: DeserializeOwned + Default>(&self, owner: UserId, key: Key) -> Result<T, StoreError> {
+        let data: Option<String> = self
+            .connection()
+            .query_row("SELECT data FROM records WHERE owner = ?1 AND key = ?2", params![owner, key.to_string()], |row| row.get(0))
+            .optional()?;
+
+        Ok(match data {
+            Some(data) => serde_json::from_str::<Record>(&data)?.live_value().unwrap_or_default(),
+            None => T::default(),
+        })
+    }
+
+    // Loads the records `touched` names, lets `change` work on them and stores what it changed
+    fn with_hub<T>(&self, owner: UserId, touched: Touched, change: impl FnOnce(&mut Hub) -> T) -> Result<T, StoreError> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+
+        let mut hub = load_hub(&transaction, owner, touched)?;
+        let revision = hub.revision();
+        let result = change(&mut hub);
+        save_hub(&transaction, owner, &hub, revision)?;
+
+        transaction.commit()?;
+        Ok(result)
+    }
+
+    pub fn entries(&self, owner: UserId) -> Result<Vec<Entry>, StoreError> {
+        self.items(owner, ENTRY_KEYS)
+    }
+
+    /// Stores a new entry under the next free id and returns it with that id.
+    pub fn add_entry(&self, owner: UserId, entry: Entry) -> Result<Entry, StoreError> {
+        self.with_hub(owner, Touched::Key(Key::Entry(0)), |hub| {
+            let key = hub.next_key(Key::Entry(0));
+            let entry = entry.with_id(key.id().unwrap_or_default());
+            hub.put(key, &entry, now_ms());
+            entry
+        })
+    }
+
+    /// Inserts the entry or replaces the one with the same id.
+    pub fn put_entry(&self, owner: UserId, entry: &Entry) -> Result<(), StoreError> {
+        let key = Key::Entry(entry.id());
+        self.with_hub(owner, Touched::Key(key), |hub| hub.put(key, entry, now_ms()))
+    }
+
+    /// Returns whether there was an entry to delete.
+    pub fn delete_entry(&self, owner: UserId, id: usize) -> Result<bool, StoreError> {
+        self.with_hub(owner, Touched::Key(Key::Entry(id)), |hub| hub.delete(Key::Entry(id), now_ms()))
+    }
+
+    pub fn goals(&self, owner: UserId) -> Result<Vec<Goal>, StoreError> {
+        self.items(owner, GOAL_KEYS)
+    }
+
+    pub fn add_goal(&self, owner: UserId, goal: Goal) -> Result<Goal, StoreError> {
+        self.with_hub(owner, Touched::Key(Key::Goal(0)), |hub| {
+            let key = hub.next_key(Key::Goal(0));
+            let goal = goal.with_id(key.id().unwrap_or_default());
+            hub.put(key, &goal, now_ms());
+            goal
+        })
+    }
+
+    pub fn put_goal(&self, owner: UserId, goal: &Goal) -> Result<(), StoreError> {
+        let key = Key::Goal(goal.id());
+        self.with_hub(owner, Touched::Key(key), |hub| hub.put(key, goal, now_ms()))
+    }
+
+    pub fn delete_goal(&self, owner: UserId, id: usize) -> Result<bool, StoreError> {
+        self.with_hub(owner, Touched::Key(Key::Goal(id)), |hub| hub.delete(Key::Goal(id), now_ms()))
+    }
+
+    pub fn profile(&self, owner: UserId) -> Result<Profile, StoreError> {
+        self.document(owner, Key::Profile)
+    }
+
+    pub fn put_profile(&self, owner: UserId, profile: &Profile) -> Result<(), StoreError> {
+        self.with_hub(owner, Touched::Key(Key::Profile), |hub| hub.put(Key::Profile, profile, now_ms()))
+    }
+
+    pub fn settings(&self, owner: UserId) -> Result<Settings, StoreError> {
+        self.document(owner, Key::Settings)
+    }
+
+    pub fn put_settings(&self, owner: UserId, settings: &Settings) -> Result<(), StoreError> {
+        self.with_hub(owner, Touched::Key(Key::Settings), |hub| hub.put(Key::Settings, settings, now_ms()))
+    }
+
+    pub fn sync(&self, owner: UserId, request: SyncRequest) -> Result<SyncResponse, StoreError> {
+        let touched = Touched::Sync {
+            since: request.since,
+            keys: request.records.iter().map(|record| record.key).collect(),
+        };
+        self.with_hub(owner, touched, |hub| hub.sync(request))
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn load_hub(connection: &Connection, owner: UserId, touched: Touched) -> Result<Hub, StoreError> {
+    let mut rows = BTreeMap::new();
+    match touched {
+        Touched::Key(key) => load_rows(connection, "key = ?2", params![owner, key.to_string()], &mut rows)?,
+        // A pushed item the hub moved to another key since the device's last pull changed after
+        // `since`, so the records sent back and those under the pushed keys hold every pushed item
+        Touched::Sync { since, keys } => {
+            load_rows(connection, "revision > ?2", params![owner, since], &mut rows)?;
+            for key in keys {
+                load_rows(connection, "key = ?2", params![owner, key.to_string()], &mut rows)?;
+            }
+        }
+    }
+
+    let revision: u64 = connection.query_row("SELECT COALESCE(MAX(revision), 0) FROM records WHERE owner = ?1", [owner], |row| row.get(0))?;
+    let free_keys = vec![Key::Entry(free_id(connection, owner, ENTRY_KEYS)?), Key::Goal(free_id(connection, owner, GOAL_KEYS)?)];
+    Ok(Hub::partial(rows.into_values().collect(), revision, free_keys))
+}
+
+// Adds the account's rows matching `condition` to `rows`, by key
+fn load_rows(
+    connection: &Connection,
+    condition: &str,
+    params: impl rusqlite::Params,
+    rows: &mut BTreeMap<String, (Record, u64)>,
+) -> Result<(), StoreError> {
+    let mut statement = connection.prepare_cached(&format!("SELECT key, data, revision FROM records WHERE owner = ?1 AND {condition}"))?;
+    let found = statement.query_map(params, |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, u64>(2)?)))?;
+    for row in found {
+        let (key, data, revision) = row?;
+        rows.insert(key, (serde_json::from_str::<Record>(&data)?, revision));
+    }
+    Ok(())
+}
+
+// The id after the highest one under keys matching `pattern`, deleted items included
+fn free_id(connection: &Connection, owner: UserId, pattern: &str) -> Result<usize, StoreError> {
+    let id_start = pattern.len(); // SUBSTR counts from 1, the pattern ends in %
+    let highest: Option<i64> = connection.query_row(
+        "SELECT MAX(CAST(SUBSTR(key, ?3) AS INTEGER)) FROM records WHERE owner = ?1 AND key LIKE ?2",
+        params![owner, pattern, id_start],
+        |row| row.get(0),
+    )?;
+    Ok(highest.map_or(0, |id| id as usize + 1))
+}
+
+// Records are stored under their key, which is unique within a hub
+fn save_hub(connection: &Connection, owner: UserId, hub: &Hub, since: u64) -> Result<(), StoreError> {
+    for (record, revision) in hub.changed_since(since) {
+        connection.execute(
+            "INSERT OR REPLACE INTO records (owner, key, revision, data) VALUES (?1, ?2, ?3, ?4)",
+            params![owner, record.key.to_string(), revision, serde_json::to_string(record)?],
+        )?;
+    }
+    Ok(())
+}
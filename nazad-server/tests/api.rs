@@ -0,0 +1,386 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use reqwest::{Client, RequestBuilder, StatusCode};
+
+use nazad_core::api::{self, Credentials, Profile, Session};
+use nazad_core::date::Date;
+use nazad_core::log::{Entry, EntryType};
+use nazad_core::settings::Settings;
+use nazad_core::sync::{Replica, SyncResponse};
+use nazad_core::user::Goal;
+use nazad_server::store::Store;
+
+// Starts a server with an empty in-memory database on a free port
+async fn spawn_server() -> String {
+    spawn_server_with(Store::in_memory().unwrap()).await
+}
+
+async fn spawn_server_with(store: Store) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = nazad_server::app(store);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn credentials(username: &str, password: &str) -> Credentials {
+    Credentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+}
+
+/// A logged in client.
+struct Account {
+    server: String,
+    client: Client,
+    token: String,
+}
+
+impl Account {
+    async fn register(server: &str, username: &str) -> Self {
+        let client = Client::new();
+        let response = client
+            .post(format!("{server}{}", api::REGISTER_PATH))
+            .json(&credentials(username, "correct horse"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let session: Session = response.json().await.unwrap();
+
+        Self {
+            server: server.to_string(),
+            client,
+            token: session.token,
+        }
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        self.client.get(format!("{}{path}", self.server)).bearer_auth(&self.token)
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        self.client.post(format!("{}{path}", self.server)).bearer_auth(&self.token)
+    }
+
+    fn put(&self, path: &str) -> RequestBuilder {
+        self.client.put(format!("{}{path}", self.server)).bearer_auth(&self.token)
+    }
+
+    fn delete(&self, path: &str) -> RequestBuilder {
+        self.client.delete(format!("{}{path}", self.server)).bearer_auth(&self.token)
+    }
+
+    async fn entries(&self) -> Vec<Entry> {
+        self.get(api::ENTRIES_PATH).send().await.unwrap().json().await.unwrap()
+    }
+
+    async fn sync(&self, replica: &mut Replica) {
+        let request = replica.request();
+        let response: SyncResponse = self.post(api::SYNC_PATH).json(&request).send().await.unwrap().json().await.unwrap();
+        replica.apply(response);
+    }
+}
+
+fn podcast() -> Entry {
+    let date = NaiveDate::from_ymd_opt(2023, 10, 1).unwrap();
+    Entry::from_data("Podcast".to_string(), EntryType::Listening, 30, Date::from_naive(date))
+        .with_resource("Cafe con Leche".to_string())
+        .with_span(date.and_hms_opt(9, 0, 0).unwrap(), date.and_hms_opt(9, 30, 0).unwrap())
+}
+
+#[tokio::test]
+async fn entries_can_be_created_replaced_and_deleted() {
+    let server = spawn_server().await;
+    let account = Account::register(&server, "ana").await;
+
+    let response = account.post(api::ENTRIES_PATH).json(&podcast()).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let first: Entry = response.json().await.unwrap();
+    let second: Entry = account.post(api::ENTRIES_PATH).json(&podcast()).send().await.unwrap().json().await.unwrap();
+    assert_eq!((first.id(), second.id()), (0, 1));
+
+    let longer = Entry::from_data("Podcast".to_string(), EntryType::Listening, 45, first.date().clone());
+    account.put(&api::entry_path(7)).json(&longer).send().await.unwrap();
+    let deleted = account.delete(&api::entry_path(1)).send().await.unwrap();
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(account.entries().await, vec![first, longer.with_id(7)]);
+
+    let missing = account.delete(&api::entry_path(1)).send().await.unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn goals_can_be_created_and_completed() {
+    let server = spawn_server().await;
+    let account = Account::register(&server, "ana").await;
+
+    let goal = Goal::from_data("Pass DELE B2".to_string(), false, NaiveDate::from_ymd_opt(2024, 5, 18));
+    let created: Goal = account.post(api::GOALS_PATH).json(&goal).send().await.unwrap().json().await.unwrap();
+
+    let completed = Goal::from_data("Pass DELE B2".to_string(), true, NaiveDate::from_ymd_opt(2024, 5, 18));
+    account.put(&api::goal_path(created.id())).json(&completed).send().await.unwrap();
+
+    let goals: Vec<Goal> = account.get(api::GOALS_PATH).send().await.unwrap().json().await.unwrap();
+    assert_eq!(goals.len(), 1);
+    assert!(goals[0].completed());
+    assert_eq!(goals[0].date().year, 2024);
+}
+
+#[tokio::test]
+async fn profile_and_settings_start_empty_and_can_be_replaced() {
+    let server = spawn_server().await;
+    let account = Account::register(&server, "ana").await;
+
+    let profile: Profile = account.get(api::PROFILE_PATH).send().await.unwrap().json().await.unwrap();
+    assert_eq!(profile, Profile::default());
+
+    let mut profile = Profile::default();
+    profile.profile.name = "Ana".to_string();
+    profile.diagnostics.vocab_size = 4200;
+    account.put(api::PROFILE_PATH).json(&profile).send().await.unwrap();
+    let settings = Settings {
+        visible_at_startup: false,
+        week_start: chrono::Weekday::Sun,
+        day_boundary_hour: 4,
+        ..Settings::default()
+    };
+    account.put(api::SETTINGS_PATH).json(&settings).send().await.unwrap();
+
+    let stored: Profile = account.get(api::PROFILE_PATH).send().await.unwrap().json().await.unwrap();
+    assert_eq!(stored, profile);
+    let stored: Settings = account.get(api::SETTINGS_PATH).send().await.unwrap().json().await.unwrap();
+    assert_eq!(stored, settings);
+}
+
+#[tokio::test]
+async fn accounts_only_see_their_own_data() {
+    let server = spawn_server().await;
+    let ana = Account::register(&server, "ana").await;
+    let ben = Account::register(&server, "ben").await;
+
+    ana.post(api::ENTRIES_PATH).json(&podcast()).send().await.unwrap();
+    ben.put(&api::entry_path(3)).json(&podcast()).send().await.unwrap();
+    let not_found = ben.delete(&api::entry_path(0)).send().await.unwrap();
+
+    assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+    assert_eq!(ana.entries().await, vec![podcast().with_id(0)]);
+    assert_eq!(ben.entries().await, vec![podcast().with_id(3)]);
+}
+
+#[tokio::test]
+async fn requests_need_a_valid_session() {
+    let server = spawn_server().await;
+    let client = Client::new();
+
+    let anonymous = client.get(format!("{server}{}", api::ENTRIES_PATH)).send().await.unwrap();
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+    let made_up = client.get(format!("{server}{}", api::ENTRIES_PATH)).bearer_auth("0123").send().await.unwrap();
+    assert_eq!(made_up.status(), StatusCode::UNAUTHORIZED);
+
+    let account = Account::register(&server, "ana").await;
+    let logout = account.post(api::LOGOUT_PATH).send().await.unwrap();
+    assert_eq!(logout.status(), StatusCode::NO_CONTENT);
+    let logged_out = account.get(api::ENTRIES_PATH).send().await.unwrap();
+    assert_eq!(logged_out.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn login_checks_the_password() {
+    let server = spawn_server().await;
+    let client = Client::new();
+    let account = Account::register(&server, "ana").await;
+    account.post(api::ENTRIES_PATH).json(&podcast()).send().await.unwrap();
+
+    let login = |credentials: Credentials| client.post(format!("{server}{}", api::LOGIN_PATH)).json(&credentials).send();
+
+    let wrong = login(credentials("ana", "wrong horse")).await.unwrap();
+    assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+    let unknown = login(credentials("carla", "correct horse")).await.unwrap();
+    assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+
+    let session: Session = login(credentials(" Ana ", "correct horse")).await.unwrap().json().await.unwrap();
+    let entries: Vec<Entry> = client
+        .get(format!("{server}{}", api::ENTRIES_PATH))
+        .bearer_auth(session.token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+}
+
+#[tokio::test]
+async fn failed_logins_make_the_account_wait() {
+    let server = spawn_server().await;
+    let client = Client::new();
+    Account::register(&server, "ana").await;
+
+    let login = |credentials: Credentials| client.post(format!("{server}{}", api::LOGIN_PATH)).json(&credentials).send();
+
+    for _ in 0..4 {
+        let wrong = login(credentials("ana", "wrong horse")).await.unwrap();
+        assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+    }
+    let waiting = login(credentials("ANA", "correct horse")).await.unwrap();
+    assert_eq!(waiting.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(waiting.headers()["retry-after"], "1");
+
+    Account::register(&server, "ben").await;
+    let other = login(credentials("ben", "correct horse")).await.unwrap();
+    assert_eq!(other.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn sessions_expire() {
+    let server = spawn_server_with(Store::in_memory().unwrap().with_session_lifetime(Duration::ZERO)).await;
+    let account = Account::register(&server, "ana").await;
+
+    let expired = account.get(api::ENTRIES_PATH).send().await.unwrap();
+    assert_eq!(expired.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn registration_rejects_taken_names_and_short_passwords() {
+    let server = spawn_server().await;
+    let client = Client::new();
+    Account::register(&server, "ana").await;
+
+    let register = |credentials: Credentials| client.post(format!("{server}{}", api::REGISTER_PATH)).json(&credentials).send();
+
+    let taken = register(credentials("ANA", "another horse")).await.unwrap();
+    assert_eq!(taken.status(), StatusCode::CONFLICT);
+    let short = register(credentials("ben", "horse")).await.unwrap();
+    assert_eq!(short.status(), StatusCode::BAD_REQUEST);
+    let nameless = register(credentials("  ", "correct horse")).await.unwrap();
+    assert_eq!(nameless.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn devices_sync_through_the_server() {
+    let server = spawn_server().await;
+    let account = Account::register(&server, "ana").await;
+    let mut laptop = Replica::new(1);
+    let mut phone = Replica::new(2);
+
+    let (mut log, user) = laptop.state();
+    log.add_entry("Book".to_string(), EntryType::Reading, 30, podcast().date().clone());
+    laptop.commit(&log, &user, 1_000);
+    account.sync(&mut laptop).await;
+    account.post(api::ENTRIES_PATH).json(&podcast()).send().await.unwrap();
+    account.sync(&mut phone).await;
+
+    let (log, _) = phone.state();
+    let titles: Vec<&str> = log.entries().iter().map(Entry::title).collect();
+    assert_eq!(titles, vec!["Book", "Podcast"]);
+    assert_eq!(account.entries().await, log.entries());
+}
+
+#[test]
+fn syncs_find_pushed_items_among_the_stored_ones() {
+    let store = Store::in_memory().unwrap();
+    let owner = store.register(&credentials("ana", "correct horse")).unwrap().unwrap();
+    let sync = |replica: &mut Replica| {
+        let request = replica.request();
+        replica.apply(store.sync(owner, request).unwrap());
+    };
+    let add = |replica: &mut Replica, title: &str, now_ms: u64| {
+        let (mut log, user) = replica.state();
+        log.add_entry(title.to_string(), EntryType::Reading, 30, podcast().date().clone());
+        replica.commit(&log, &user, now_ms);
+    };
+    // Browsers pick random replica ids, beyond what SQLite holds as an integer
+    let mut laptop = Replica::new(u64::MAX - 1);
+    let mut phone = Replica::new(u64::MAX - 2);
+
+    add(&mut laptop, "Book", 1_000);
+    sync(&mut laptop);
+    sync(&mut phone);
+    add(&mut laptop, "Podcast", 2_000);
+    sync(&mut laptop);
+
+    // The phone's entry is moved to a free id, but the phone never hears of it and pushes it again
+    add(&mut phone, "Italki", 3_000);
+    let lost = phone.request();
+    store.sync(owner, lost.clone()).unwrap();
+    phone.requeue(&lost);
+    sync(&mut phone);
+    sync(&mut laptop);
+
+    let titles: Vec<String> = store.entries(owner).unwrap().iter().map(|entry| entry.title().to_string()).collect();
+    assert_eq!(titles, ["Book", "Podcast", "Italki"]);
+    assert_eq!(phone.state(), laptop.state());
+}
+
+fn temp_database(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("nazad-server-{name}-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn data_survives_reopening_the_database() {
+    let path = temp_database("reopen");
+
+    let store = Store::open(&path).unwrap();
+    let owner = store.register(&credentials("ana", "correct horse")).unwrap().unwrap();
+    let stored = store.add_entry(owner, podcast()).unwrap();
+    drop(store);
+    let entries = Store::open(&path).unwrap().entries(owner).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(entries, vec![stored]);
+}
+
+#[test]
+fn reads_list_live_items_by_id() {
+    let store = Store::in_memory().unwrap();
+    let owner = store.register(&credentials("ana", "correct horse")).unwrap().unwrap();
+    for _ in 0..12 {
+        store.add_entry(owner, podcast()).unwrap();
+    }
+    store.delete_entry(owner, 3).unwrap();
+
+    let ids: Vec<usize> = store.entries(owner).unwrap().iter().map(Entry::id).collect();
+
+    // Stored keys sort as text, entry/10 before entry/2
+    assert_eq!(ids, [0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11]);
+    assert_eq!(store.settings(owner).unwrap(), Settings::default());
+}
+
+#[test]
+fn ids_of_deleted_items_are_not_given_out_again() {
+    let store = Store::in_memory().unwrap();
+    let owner = store.register(&credentials("ana", "correct horse")).unwrap().unwrap();
+    for _ in 0..11 {
+        store.add_entry(owner, podcast()).unwrap();
+    }
+    store.delete_entry(owner, 10).unwrap();
+
+    assert_eq!(store.add_entry(owner, podcast()).unwrap().id(), 11);
+}
+
+#[tokio::test]
+async fn pages_of_the_dashboard_get_its_index() {
+    let static_dir = std::env::temp_dir().join(format!("nazad-static-{}", std::process::id()));
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "<div id=\"main\"></div>").unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = nazad_server::app_with_static_files(Store::in_memory().unwrap(), &static_dir);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let response = reqwest::get(format!("http://{address}/history")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "<div id=\"main\"></div>");
+
+    // The API doesn't fall back to the dashboard
+    let response = reqwest::get(format!("http://{address}{}", api::ENTRIES_PATH)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,212 @@
+//! Where the dashboard keeps its data. By default everything stays on the
+//! device, see `storage`; opening the app with `?server=http://host:3000`
+//! (or building it with `NAZAD_SERVER` set) reads and writes through a
+//! nazad-server instead. An empty value, `?server`, uses the server the app
+//! was loaded from. The choice is remembered for the browser tab, moving
+//! between pages drops the query.
+//!
+//! In server mode the device keeps a `Replica` of the account's data in local
+//! storage, so the app works offline and pushes its edits once it's back.
+
+use std::fmt;
+
+use gloo_net::http::{Request, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use web_sys::{Storage, UrlSearchParams};
+
+use nazad_core::api::{self, Credentials, Session};
+use nazad_core::sync::{Replica, SyncRequest, SyncResponse, HUB_REPLICA};
+
+use crate::storage::StorageError;
+
+const SESSION_KEY: &str = "nazad-session";
+const REPLICA_KEY: &str = "nazad-replica";
+const SERVER_KEY: &str = "nazad-server";
+
+#[derive(Clone, PartialEq)]
+pub enum Backend {
+    Local,
+    Server(ApiClient),
+}
+
+impl Backend {
+    pub fn from_location() -> Self {
+        let from_query = web_sys::window()
+            .and_then(|window| window.location().search().ok())
+            .and_then(|search| UrlSearchParams::new_with_str(&search).ok())
+            .and_then(|params| params.get("server"));
+        let tab_storage = web_sys::window().and_then(|window| window.session_storage().ok()?);
+
+        if let (Some(url), Some(storage)) = (&from_query, &tab_storage) {
+            let _ = storage.set_item(SERVER_KEY, url);
+        }
+        let from_tab = tab_storage.and_then(|storage| storage.get_item(SERVER_KEY).ok().flatten());
+
+        match from_query.or(from_tab).or_else(|| option_env!("NAZAD_SERVER").map(str::to_string)) {
+            Some(url) => Backend::Server(ApiClient::new(&url)),
+            None => Backend::Local,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RequestError {
+    Network(gloo_net::Error),
+    Unauthorized,
+    /// Any other unsuccessful status, with the message the server sent along.
+    Status(u16, String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Network(error) => write!(f, "{error}"),
+            RequestError::Unauthorized => write!(f, "not logged in"),
+            RequestError::Status(status, message) if message.is_empty() => write!(f, "server answered with status {status}"),
+            RequestError::Status(_, message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<gloo_net::Error> for RequestError {
+    fn from(error: gloo_net::Error) -> Self {
+        RequestError::Network(error)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ApiClient {
+    base_url: String,
+    session: Option<Session>,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: None,
+        }
+    }
+
+    pub fn with_session(&self, session: Session) -> Self {
+        Self {
+            base_url: self.base_url.clone(),
+            session: Some(session),
+        }
+    }
+
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Shown to the user, the app's own origin when talking to the server it came from.
+    pub fn name(&self) -> &str {
+        if self.base_url.is_empty() {
+            "this server"
+        } else {
+            &self.base_url
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.session {
+            Some(session) => request.header("Authorization", &format!("Bearer {}", session.token)),
+            None => request,
+        }
+    }
+
+    async fn check(response: Response) -> Result<Response, RequestError> {
+        match response.status() {
+            200..=299 => Ok(response),
+            401 => Err(RequestError::Unauthorized),
+            status => Err(RequestError::Status(status, response.text().await.unwrap_or_default())),
+        }
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: &impl serde::Serialize) -> Result<T, RequestError> {
+        let response = self.authorized(Request::post(&self.url(path))).json(body)?.send().await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    pub async fn register(&self, credentials: &Credentials) -> Result<Session, RequestError> {
+        self.post(api::REGISTER_PATH, credentials).await
+    }
+
+    pub async fn login(&self, credentials: &Credentials) -> Result<Session, RequestError> {
+        self.post(api::LOGIN_PATH, credentials).await
+    }
+
+    pub async fn logout(&self) -> Result<(), RequestError> {
+        let response = self.authorized(Request::post(&self.url(api::LOGOUT_PATH))).send().await?;
+        Self::check(response).await?;
+        Ok(())
+    }
+
+    pub async fn sync(&self, request: &SyncRequest) -> Result<SyncResponse, RequestError> {
+        self.post(api::SYNC_PATH, request).await
+    }
+
+    // Sessions are remembered per server, so reloading the page keeps you logged in
+    fn session_key(&self) -> String {
+        format!("{SESSION_KEY}:{}", self.base_url)
+    }
+
+    pub fn saved_session(&self) -> Option<Session> {
+        let raw = local_storage()?.get_item(&self.session_key()).ok()??;
+        serde_json::from_str(&raw).ok()
+    }
+
+    pub fn save_session(&self, session: &Session) {
+        if let (Some(storage), Ok(raw)) = (local_storage(), serde_json::to_string(session)) {
+            let _ = storage.set_item(&self.session_key(), &raw);
+        }
+    }
+
+    pub fn forget_session(&self) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(&self.session_key());
+        }
+    }
+
+    // One replica per account, next to the session
+    fn replica_key(&self) -> String {
+        let username = self.session.as_ref().map(|session| session.username.as_str()).unwrap_or_default();
+        format!("{REPLICA_KEY}:{}:{username}", self.base_url)
+    }
+
+    /// The replica kept on this device, or a new one on the first login.
+    pub fn saved_replica(&self) -> Replica {
+        local_storage()
+            .and_then(|storage| storage.get_item(&self.replica_key()).ok()?)
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_else(|| Replica::new(new_replica_id()))
+    }
+
+    /// Fails when local storage is missing or full, the edits then only
+    /// live as long as the page.
+    pub fn save_replica(&self, replica: &Replica) -> Result<(), StorageError> {
+        let raw = serde_json::to_string(replica).expect("replicas serialize");
+        local_storage()
+            .ok_or(StorageError::Unavailable)?
+            .set_item(&self.replica_key(), &raw)
+            .map_err(|_| StorageError::Unavailable)
+    }
+}
+
+pub fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+// Random, so two devices of one account practically never share an id
+fn new_replica_id() -> u64 {
+    let half = || (js_sys::Math::random() * f64::from(u32::MAX)) as u64;
+    ((half() << 32) | half()).max(HUB_REPLICA + 1)
+}
+
+pub fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
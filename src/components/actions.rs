@@ -2,11 +2,15 @@
 
 // import the prelude to get access to the `rsx!` macro and the `Scope` and `Element` types
 use dioxus::prelude::*;
+use dioxus_router::Link;
 
-const BUTTON_STYLE: &str = "bg-blue-500 hover:bg-blue-400 text-white font-bold py-2 px-4 border-b-4 border-blue-700 hover:border-blue-500 rounded";
+use crate::routes;
+use crate::components::style::BUTTON_STYLE;
 
 const TOPICS: [&str; 3] = ["Books", "Video", "Audio"];
 const TITLE_HEADER: &str = "Quick Actions";
+const LINK_STYLE: &str = "w-32 text-center bg-zinc-600 hover:bg-zinc-500 text-white py-1 px-4 rounded";
+const ACTIVE_LINK_STYLE: &str = "ring-2 ring-blue-500";
 
 enum Category {
     Books,
@@ -36,7 +40,7 @@ impl SelectedCategory {
 }
 
 #[inline_props]
-fn ActionButton<'a>(cx: Scope, title: &'a str) -> Element {
+fn ActionButton<'a>(cx: Scope, title: &'a str) -> Element<'a> {
     let category = use_shared_state::<SelectedCategory>(cx).unwrap();
 
      cx.render(rsx!(
@@ -57,6 +61,18 @@ pub fn QuickActions(cx: Scope) -> Element {
     cx.render(rsx!(
         div {
             class: "grid gap-2 justify-items-center items-center",
+            nav {
+                class: "grid gap-2",
+                routes::PAGES.iter().map(|(path, title)| {
+                    rsx!(Link {
+                        key: "{path}",
+                        to: path,
+                        class: LINK_STYLE,
+                        active_class: ACTIVE_LINK_STYLE,
+                        "{title}"
+                    })
+                })
+            },
             p {
                 TITLE_HEADER
             },
@@ -0,0 +1,187 @@
+#![allow(non_snake_case)]
+
+use std::f64::consts::PI;
+
+use dioxus::prelude::*;
+
+use nazad_core::balance::{self, SkillBalance};
+use nazad_core::log::LogData;
+use nazad_core::stats::Summary;
+use nazad_core::user::UserProfile;
+
+use crate::components::settings::today;
+use crate::components::style::PANEL_STYLE;
+
+const RADAR_SIZE: f64 = 260.0;
+const RADAR_RADIUS: f64 = 80.0;
+
+/// Polygon points for one value per axis, scaled so `max` reaches the rim.
+fn radar_points(values: &[f64], max: f64) -> String {
+    values
+        .iter()
+        .enumerate()
+        .map(|(axis, value)| {
+            let (x, y) = radar_point(axis, values.len(), value / max.max(f64::EPSILON) * RADAR_RADIUS);
+            format!("{x:.1},{y:.1}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn radar_point(axis: usize, axes: usize, radius: f64) -> (f64, f64) {
+    let angle = -PI / 2.0 + 2.0 * PI * axis as f64 / axes as f64;
+    (RADAR_SIZE / 2.0 + radius * angle.cos(), RADAR_SIZE / 2.0 + radius * angle.sin())
+}
+
+/// Share of the time per entry type on a spider chart, with the targets from
+/// the settings drawn dashed over it.
+#[inline_props]
+fn RadarChart(cx: Scope, balance: SkillBalance, targets: Vec<f64>) -> Element<'a> {
+    let shares = balance.shares();
+    let values: Vec<f64> = shares.iter().map(|(_, share)| *share).collect();
+    let max = values.iter().chain(targets.iter()).cloned().fold(0.0, f64::max);
+    let axes = shares.len();
+    let rim = radar_points(&vec![1.0; axes], 1.0);
+    let area = radar_points(&values, max);
+    let target_area = radar_points(targets, max);
+    let has_targets = targets.iter().any(|target| *target > 0.0);
+
+    cx.render(rsx!(
+        svg {
+            class: "col-span-2 w-full",
+            view_box: "0 0 {RADAR_SIZE} {RADAR_SIZE}",
+            polygon {
+                points: "{rim}",
+                fill: "none",
+                stroke: "#71717a"
+            },
+            shares.iter().enumerate().map(|(axis, (entry_type, share))| {
+                let (x, y) = radar_point(axis, axes, RADAR_RADIUS);
+                let (label_x, label_y) = radar_point(axis, axes, RADAR_RADIUS + 22.0);
+                let percent = (share * 100.0).round();
+                rsx!(
+                    line {
+                        key: "{entry_type.name()}",
+                        x1: "{RADAR_SIZE / 2.0}",
+                        y1: "{RADAR_SIZE / 2.0}",
+                        x2: "{x}",
+                        y2: "{y}",
+                        stroke: "#71717a"
+                    },
+                    text {
+                        x: "{label_x}",
+                        y: "{label_y}",
+                        fill: "currentColor",
+                        font_size: "10",
+                        text_anchor: "middle",
+                        "{entry_type.name()} {percent}%"
+                    }
+                )
+            }),
+            if has_targets {
+                rsx!(
+                    polygon {
+                        points: "{target_area}",
+                        fill: "none",
+                        stroke: "#eab308",
+                        stroke_dasharray: "4 3"
+                    }
+                )
+            }
+            polygon {
+                points: "{area}",
+                fill: "#3b82f6",
+                fill_opacity: "0.4",
+                stroke: "#3b82f6",
+                stroke_width: "2"
+            }
+        }
+    ))
+}
+
+/// The spread over the skills in a period of the statistics page, with input
+/// against output.
+#[inline_props]
+pub fn BalanceSection(cx: Scope, summary: Summary) -> Element<'a> {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let balance = SkillBalance::of(summary);
+    let targets: Vec<f64> = balance
+        .per_skill
+        .iter()
+        .map(|(entry_type, _)| settings.skill_target(entry_type) as f64 / 100.0)
+        .collect();
+
+    let target = settings.input_share_target;
+    let (ratio, ratio_color) = match balance.input_share() {
+        Some(share) => {
+            let input = (share * 100.0).round() as u32;
+            let color = if input.abs_diff(target) <= 10 { "text-green-500" } else { "text-yellow-500" };
+            (format!("Input {input}%, output {}% (target {target}% input)", 100 - input), color)
+        }
+        None => ("No listening, reading, speaking or writing in this period".to_string(), "text-gray-400"),
+    };
+    let input = settings.format_duration(balance.input_minutes);
+    let output = settings.format_duration(balance.output_minutes);
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            span {
+                class: "col-span-4 font-bold",
+                "Skill balance"
+            },
+            RadarChart {
+                balance: balance.clone(),
+                targets: targets
+            },
+            div {
+                class: "col-span-2 grid gap-1 content-start",
+                p {
+                    "Input (listening and reading): {input}"
+                },
+                p {
+                    "Output (speaking and writing): {output}"
+                },
+                p {
+                    class: "{ratio_color}",
+                    "{ratio}"
+                }
+            }
+        }
+    ))
+}
+
+/// Warns about skills without entries for longer than the settings allow.
+pub fn NeglectWarning(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    if settings.neglect_days == 0 {
+        return None;
+    }
+
+    let neglected = balance::neglected(&log_data.read(), today(&settings), settings.neglect_days);
+    if neglected.is_empty() {
+        return None;
+    }
+
+    cx.render(rsx!(
+        div {
+            class: "grid gap-1 bg-yellow-100 text-yellow-900 m-4 p-2 rounded-md border-8 border-yellow-100",
+            neglected.into_iter().map(|neglected| {
+                let name = neglected.skill.name();
+                let since = match neglected.last_day {
+                    Some(day) => format!("since {}", settings.format_date(day)),
+                    None => "yet".to_string(),
+                };
+                rsx!(
+                    p {
+                        key: "{name}",
+                        "No {name} logged {since}"
+                    }
+                )
+            })
+        }
+    ))
+}
@@ -1,15 +1,17 @@
 #![allow(non_snake_case)]
 
 use dioxus::prelude::*;
-use chrono::{DateTime, Utc};
 
-use crate::components::log;
+use nazad_core::user::UserProfile;
+
+use crate::components::{balance, log, milestones, review, settings};
 
 const TITLE: &str = "Nazad";
 
-fn Header(cx: Scope) -> Element {
-    let now: DateTime<Utc> = Utc::now();
-    let time = format!("{}",now.format("%a %b %e %Y"));
+pub fn Header(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let user = user.read();
+    let time = user.settings().format_date(settings::today(user.settings()));
 
     cx.render(rsx!(
         div {
@@ -31,10 +33,9 @@ fn Header(cx: Scope) -> Element {
 
 pub fn View(cx: Scope) -> Element {
     cx.render(rsx!(
-        div {
-            class: "col-span-4",
-            Header {},
-            log::Logger {}
-        }
+        review::WeeklyReview {},
+        balance::NeglectWarning {},
+        log::Logger {},
+        milestones::MilestoneSection {}
     ))
-}
\ No newline at end of file
+}
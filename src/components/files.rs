@@ -0,0 +1,158 @@
+//! Opening files for imports and saving exports. The browser uses a file
+//! input and a download link, the desktop app native open and save dialogs.
+
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::components::style::BUTTON_STYLE;
+
+/// A file picked for an import, read only once it's needed.
+pub struct PickedFile {
+    name: String,
+    #[cfg(feature = "web")]
+    files: std::sync::Arc<dyn FileEngine>,
+    #[cfg(feature = "desktop")]
+    path: std::path::PathBuf,
+}
+
+impl PickedFile {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[cfg(feature = "web")]
+    pub async fn read(&self) -> Option<Vec<u8>> {
+        self.files.read_file(&self.name).await
+    }
+
+    #[cfg(feature = "web")]
+    pub async fn read_to_string(&self) -> Option<String> {
+        self.files.read_file_to_string(&self.name).await
+    }
+
+    #[cfg(feature = "desktop")]
+    pub async fn read(&self) -> Option<Vec<u8>> {
+        std::fs::read(&self.path).ok()
+    }
+
+    #[cfg(feature = "desktop")]
+    pub async fn read_to_string(&self) -> Option<String> {
+        std::fs::read_to_string(&self.path).ok()
+    }
+}
+
+/// `accept` lists file extensions like a file input does, e.g. ".csv,.tsv,text/csv".
+#[cfg(feature = "web")]
+#[inline_props]
+pub fn OpenFile<'a>(cx: Scope<'a>, class: &'a str, accept: &'a str, on_open: EventHandler<'a, PickedFile>) -> Element<'a> {
+    cx.render(rsx!(
+        input {
+            class: "{class} text-gray-200",
+            r#type: "file",
+            accept: "{accept}",
+            onchange: move |evt: FormEvent| {
+                let Some(files) = evt.files.clone() else {
+                    return;
+                };
+                if let Some(name) = files.files().into_iter().next() {
+                    on_open.call(PickedFile { name, files });
+                }
+            }
+        }
+    ))
+}
+
+#[cfg(feature = "desktop")]
+#[inline_props]
+pub fn OpenFile<'a>(cx: Scope<'a>, class: &'a str, accept: &'a str, on_open: EventHandler<'a, PickedFile>) -> Element<'a> {
+    let picked = use_state(cx, String::new);
+    let extensions: Vec<&str> = accept
+        .split(',')
+        .filter_map(|kind| kind.trim().strip_prefix('.'))
+        .collect();
+
+    cx.render(rsx!(
+        div {
+            class: "{class} flex flex-row gap-2 items-center text-gray-200",
+            button {
+                class: BUTTON_STYLE,
+                onclick: move |_| {
+                    let Some(path) = rfd::FileDialog::new().add_filter("Supported files", &extensions).pick_file() else {
+                        return;
+                    };
+                    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                    picked.set(name.clone());
+                    on_open.call(PickedFile { name, path });
+                },
+                "Open…"
+            },
+            span {
+                "{picked}"
+            }
+        }
+    ))
+}
+
+/// Builds a `data:` URL so exports can be downloaded through a plain link.
+#[cfg(feature = "web")]
+fn data_url(mime: &str, content: &str) -> String {
+    let mut url = format!("data:{mime};charset=utf-8,");
+    for byte in content.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => url.push(byte as char),
+            _ => url.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    url
+}
+
+#[cfg(feature = "web")]
+#[inline_props]
+pub fn SaveFile<'a>(cx: Scope<'a>, class: &'a str, file_name: &'a str, contents: String) -> Element<'a> {
+    let mime = match file_name.rsplit('.').next() {
+        Some("json") => "application/json",
+        Some("html") => "text/html",
+        Some("svg") => "image/svg+xml",
+        _ => "text/plain",
+    };
+    let url = data_url(mime, contents);
+
+    cx.render(rsx!(
+        a {
+            class: "{class} text-center {BUTTON_STYLE}",
+            href: "{url}",
+            download: "{file_name}",
+            "Export"
+        }
+    ))
+}
+
+#[cfg(feature = "desktop")]
+#[inline_props]
+pub fn SaveFile<'a>(cx: Scope<'a>, class: &'a str, file_name: &'a str, contents: String) -> Element<'a> {
+    let status = use_state(cx, String::new);
+
+    cx.render(rsx!(
+        div {
+            class: "{class} grid gap-1",
+            button {
+                class: BUTTON_STYLE,
+                onclick: move |_| {
+                    let Some(path) = rfd::FileDialog::new().set_file_name(file_name).save_file() else {
+                        return;
+                    };
+                    match std::fs::write(&path, contents) {
+                        Ok(()) => status.set(format!("Saved to {}", path.display())),
+                        Err(error) => status.set(format!("Could not save {}: {error}", path.display())),
+                    }
+                },
+                "Export"
+            },
+            span {
+                class: "text-xs text-gray-200 break-all",
+                "{status}"
+            }
+        }
+    ))
+}
@@ -0,0 +1,250 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use nazad_core::user::{Goal, UserProfile};
+
+use crate::components::settings::today;
+use crate::components::icons::{
+    TrashIcon,
+    PencilIcon
+};
+
+struct Edit(bool);
+
+const NUMBER_INPUT_STYLE: &str = "peer w-20 rounded-[7px] border border-blue-gray-200 border-t-transparent bg-transparent px-3 py-2.5 font-sans text-sm font-normal text-blue-gray-700 outline outline-0 transition-all placeholder-shown:border placeholder-shown:border-blue-gray-200 placeholder-shown:border-t-blue-gray-200 focus:border-2 focus:border-pink-500 focus:border-t-transparent focus:outline-0 disabled:border-0 disabled:bg-blue-gray-50";
+const NUMBER_LABEL_STYLE: &str = "before:content[' '] after:content[' '] pointer-events-none absolute left-0 -top-1.5 flex w-20 select-none text-[11px] font-normal leading-tight text-blue-gray-400 transition-all before:pointer-events-none before:mt-[6.5px] before:mr-1 before:box-border before:block before:h-1.5 before:w-2.5 before:rounded-tl-md before:border-t before:border-l before:border-blue-gray-200 before:transition-all after:pointer-events-none after:mt-[6.5px] after:ml-1 after:box-border after:block after:h-1.5 after:w-2.5 after:flex-grow after:rounded-tr-md after:border-t after:border-r after:border-blue-gray-200 after:transition-all peer-placeholder-shown:text-sm peer-placeholder-shown:leading-[3.75] peer-placeholder-shown:text-blue-gray-500 peer-placeholder-shown:before:border-transparent peer-placeholder-shown:after:border-transparent peer-focus:text-[11px] peer-focus:leading-tight peer-focus:text-pink-500 peer-focus:before:border-t-2 peer-focus:before:border-l-2 peer-focus:before:border-pink-500 peer-focus:after:border-t-2 peer-focus:after:border-r-2 peer-focus:after:border-pink-500 peer-disabled:text-transparent peer-disabled:before:border-transparent peer-disabled:after:border-transparent peer-disabled:peer-placeholder-shown:text-blue-gray-500";
+
+#[inline_props]
+fn NumberInput<'a>(cx: Scope, value: String, kind: &'a str, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        div {
+            class: "w-20",
+            div {
+                class: "relative h-10 w-full",
+                input {
+                    class: NUMBER_INPUT_STYLE,
+                    placeholder: " ",
+                    r#type: "number",
+                    min: "0",
+                    max: "10000",
+                    value: "{value}",
+                    oninput: move |evt| {
+                        on_input.call(evt)
+                    }
+                },
+                label {
+                    class: NUMBER_LABEL_STYLE,
+                    "{kind}"
+                }
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn StringInput<'a>(cx: Scope, value: String, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        div {
+            class: "w-20",
+            div {
+                class: "relative h-10 w-full",
+                input {
+                    class: NUMBER_INPUT_STYLE,
+                    placeholder: " ",
+                    r#type: "text",
+                    value: "{value}",
+                    oninput: move |evt| {
+                        on_input.call(evt)
+                    }
+                },
+                label {
+                    class: NUMBER_LABEL_STYLE,
+                    "{value}"
+                }
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn GoalParagraph(cx: Scope, goal: Goal) -> Element<'a> {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+
+    let edit = use_state(cx, || Edit(false));
+    let id = goal.id();
+
+    if !edit.0 {
+    let due = goal.date().to_naive().map(|day| user.read().settings().format_date(day)).unwrap_or_default();
+    return cx.render(rsx!(
+       div {
+            class: "flex flex-row gap-2 group-checked:opacity-0",
+            if goal.completed() {
+                rsx!(
+            input {
+                id: "{id}",
+                class: "checked:text-blue-600 transition-colors will-change-auto",
+                r#type: "checkbox",
+                checked: "",
+                name: "{id}",
+                onchange: move |_| {
+                    user.write().mark_goal_as_completed(goal);
+                }
+            })
+            } else {
+                rsx!(
+                     input {
+                id: "{id}",
+                class: "checked:text-blue-600 transition-colors will-change-auto",
+                r#type: "checkbox",
+                name: "{id}",
+                onchange: move |_| {
+                    let day = today(user.read().settings());
+                    user.write().mark_goal_as_completed_on(goal, day);
+                }
+            })
+            },
+            label {
+                    class: "self-center",
+                    r#for: "{id}",
+                div {
+                    class: "flex flex-row gap-2",
+                    span {
+                    "{goal.title()}"
+                },
+                span {
+                    "{due}"
+                }
+                },
+                },
+            div {
+                class: "flex items-center",
+            button {
+                class: "w-6 h-6",
+                onclick: move |_| {
+                    edit.set(Edit(true));
+                },
+                PencilIcon {}
+            }
+            },
+            div {
+                class: "flex items-center",
+            button {
+                class: "w-6 h-6" ,
+                onclick: move |_| {
+                    user.write().remove_goal(goal);
+                },
+                TrashIcon {},
+            },
+            }
+            }
+    ));
+    }
+
+
+    cx.render(rsx!(
+         div {
+            class: "flex flex-row gap-2 group-checked:opacity-0",
+            input {
+                id: "{id}",
+                class: "checked:opacity-0 transition-opacity will-change-auto",
+                r#type: "checkbox",
+                disabled: "disabled",
+                name: "{id}",
+            },
+            label {
+                    class: "self-center",
+                    r#for: "{id}",
+            },
+                StringInput {
+                value: goal.title().to_string(),
+                    on_input: move |event: FormEvent| {
+                    if let Some(goal) = user.write().goal_mut(id) {
+                        goal.set_title(event.value.clone());
+                    }
+                    }
+                },
+                 NumberInput {
+                value: goal.date().day.to_string(),
+                        kind: "Day",
+                        on_input:  move |event: FormEvent| {
+                    let val = event.value.parse::<usize>().unwrap_or_default();
+                    if let Some(goal) = user.write().goal_mut(id) {
+                        goal.date_mut().day = val;
+                    }
+                        }
+                },
+                NumberInput {
+                value: goal.date().month.to_string(),
+                        kind: "Month",
+                        on_input: move |event: FormEvent| {
+                    let val = event.value.parse::<usize>().unwrap_or_default();
+                    if let Some(goal) = user.write().goal_mut(id) {
+                        goal.date_mut().month = val;
+                    }
+                        }
+                },
+                NumberInput {
+                value: goal.date().year.to_string(),
+                        kind: "Year",
+                        on_input: move |event: FormEvent| {
+                    let val = event.value.parse::<usize>().unwrap_or_default();
+                    if let Some(goal) = user.write().goal_mut(id) {
+                        goal.date_mut().year = val;
+                    }
+                        }
+                },
+              div {
+                class: "flex items-center",
+              button {
+                        class: "w-6 h-6",
+                        onclick: move |_| {
+                            edit.set(Edit(false));
+                        },
+                PencilIcon {}
+            }
+            }
+        }
+    ))
+}
+
+pub fn GoalsSection(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+
+    cx.render(rsx!(
+        div {
+            user.read().goals().iter().map(|goal| rsx!(
+                GoalParagraph {
+                    goal: goal.clone()
+                }
+            ))
+        }
+    ))
+}
+
+pub fn AddGoalSection(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+
+    cx.render(rsx!(
+        div {
+            class: "flex w-full h-full items-end",
+            button {
+                class: "w-full text-white font-bold bg-green-400 py-2 px-4 border-b-4 border-green-700 rounded hover:border-green-500 hover:bg-green-300",
+                onclick: move |_| {
+                    user.write().add_goal()
+                },
+                "+ Add Goal"
+            }
+        }
+    ))
+}
+
+pub fn GoalsPage(cx: Scope) -> Element {
+    cx.render(rsx!(
+        div {
+            class: "grid gap-2 m-4",
+            GoalsSection {},
+            AddGoalSection {}
+        }
+    ))
+}
@@ -0,0 +1,124 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use chrono::{Local, NaiveDate};
+
+use nazad_core::log::{Entry, LogData};
+use nazad_core::user::UserProfile;
+
+use crate::components::icons::TrashIcon;
+
+const PANEL_STYLE: &str = "grid gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600";
+
+#[inline_props]
+fn EntryRow(cx: Scope, entry: Entry) -> Element<'a> {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let id = entry.id();
+    let entry_type = entry.entry_type().name();
+    let minutes = user.read().settings().format_duration(entry.minutes());
+    let resource = entry.resource().unwrap_or_default();
+    // Sessions from time trackers know when they started, others when they were logged
+    let time = match (entry.span(), entry.logged_at()) {
+        (Some((start, _)), _) => start.format("%H:%M").to_string(),
+        (None, Some(logged_at)) => logged_at.with_timezone(&Local).format("%H:%M").to_string(),
+        (None, None) => String::new(),
+    };
+
+    cx.render(rsx!(
+        div {
+            class: "flex flex-row gap-2 items-center text-gray-200",
+            span {
+                class: "w-12 text-xs",
+                "{time}"
+            },
+            span {
+                class: "w-20",
+                "{minutes}"
+            },
+            span {
+                class: "w-24",
+                "{entry_type}"
+            },
+            span {
+                class: "grow",
+                "{entry.title()}"
+            },
+            span {
+                class: "text-xs",
+                "{resource}"
+            },
+            button {
+                class: "w-6 h-6",
+                onclick: move |_| {
+                    log_data.write().remove_entry(id);
+                },
+                TrashIcon {}
+            }
+        }
+    ))
+}
+
+/// Every logged entry grouped by day, the latest first. Entries without a
+/// date come last.
+pub fn HistoryPage(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let log = log_data.read();
+    let user = user.read();
+    let settings = user.settings();
+
+    let mut days: Vec<(Option<NaiveDate>, Vec<&Entry>)> = vec![];
+    for entry in log.entries() {
+        let day = entry.date().to_naive();
+        match days.iter_mut().find(|(other, _)| *other == day) {
+            Some((_, entries)) => entries.push(entry),
+            None => days.push((day, vec![entry])),
+        }
+    }
+    // `None` sorts before every day, so reversing puts it last
+    days.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            if days.is_empty() {
+                rsx!(
+                    p {
+                        class: "text-gray-200",
+                        "Nothing logged yet."
+                    }
+                )
+            },
+            days.into_iter().map(|(day, entries)| {
+                let heading = match day {
+                    Some(day) => settings.format_date(day),
+                    None => String::from("No date"),
+                };
+                let total = settings.format_duration(entries.iter().map(|entry| entry.minutes()).sum());
+                rsx!(
+                    div {
+                        key: "{heading}",
+                        class: "grid gap-1",
+                        div {
+                            class: "flex flex-row font-bold text-gray-100",
+                            span {
+                                "{heading}"
+                            },
+                            span {
+                                class: "ml-auto",
+                                "{total}"
+                            }
+                        },
+                        entries.into_iter().map(|entry| rsx!(
+                            EntryRow {
+                                key: "{entry.id()}",
+                                entry: entry.clone()
+                            }
+                        ))
+                    }
+                )
+            })
+        }
+    ))
+}
@@ -3,7 +3,7 @@
 use dioxus::prelude::*;
 
 #[inline_props]
-fn SVGIcon<'a>(cx: Scope, d: &'a str) -> Element {
+fn SVGIcon<'a>(cx: Scope, d: &'a str) -> Element<'a> {
     cx.render(rsx!(
         svg {
                 class: "w-full h-full hover:fill-red-300",
@@ -13,7 +13,7 @@ fn SVGIcon<'a>(cx: Scope, d: &'a str) -> Element {
                 path {
                     stroke_linecap: "round",
                     stroke_linejoin: "round",
-                    d: d.clone()
+                    d: *d
                 }
             }
     ))
@@ -0,0 +1,676 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use crate::components::files::{self, PickedFile};
+use crate::components::icons::TrashIcon;
+use crate::components::settings;
+use crate::components::style::BUTTON_STYLE;
+
+use nazad_core::{data::DataFile, log::{EntryType, LogData, ENTRY_TYPES}, user::UserProfile};
+use nazad_core::import::{self, anki, ll_dashboard, csv::{self, ColumnMapping, CsvTable, DurationUnit}};
+use nazad_core::import::time_tracker::{self, MappingRule, RuleField, Tracker, RULE_FIELDS};
+use nazad_core::import::timeclock;
+
+const PREVIEW_ROWS: usize = 10;
+
+#[inline_props]
+fn ColumnSelect<'a>(cx: Scope, label: &'a str, headers: Vec<String>, value: String, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        label {
+            class: "flex flex-col text-xs text-gray-200",
+            "{label}",
+            select {
+                class: "text-black text-base",
+                value: "{value}",
+                oninput: move |evt| {
+                    on_input.call(evt)
+                },
+                option {
+                    value: "",
+                    "—"
+                },
+                headers.iter().enumerate().map(|(column, header)| {
+                    rsx!(
+                        option {
+                            value: "{column}",
+                            "{header}"
+                        })
+                })
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn DateFormatSelect<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        label {
+            class: "flex flex-col text-xs text-gray-200",
+            "Date Format",
+            select {
+                class: "text-black text-base",
+                oninput: move |evt| {
+                    on_input.call(evt)
+                },
+                option {
+                    value: "",
+                    "Auto"
+                },
+                import::DATE_FORMATS.iter().map(|(label, format)| {
+                    rsx!(
+                        option {
+                            value: "{format}",
+                            "{label}"
+                        })
+                })
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn DurationUnitSelect<'a>(cx: Scope, selected: DurationUnit, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        label {
+            class: "flex flex-col text-xs text-gray-200",
+            "Plain Numbers Are",
+            select {
+                class: "text-black text-base",
+                oninput: move |evt| {
+                    on_input.call(evt)
+                },
+                DurationUnit::ALL.iter().map(|unit| {
+                    rsx!(
+                        option {
+                            value: "{unit.name()}",
+                            selected: "{unit == selected}",
+                            "{unit.name()}"
+                        })
+                })
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn Preview(cx: Scope, table: CsvTable, mapping: ColumnMapping) -> Element<'a> {
+    let rows = csv::parse_rows(table, mapping);
+    let failed = rows.iter().filter(|row| row.result.is_err()).count();
+
+    cx.render(rsx!(
+        div {
+            class: "col-span-4 overflow-x-auto",
+            p {
+                class: "text-xs text-gray-200",
+                "{rows.len()} rows, {failed} with errors"
+            },
+            table {
+                class: "w-full text-sm bg-white",
+                thead {
+                    tr {
+                        table.headers.iter().map(|header| rsx!(
+                            th {
+                                class: "text-left px-1",
+                                "{header}"
+                            }
+                        )),
+                        th {
+                            class: "text-left px-1",
+                            "Status"
+                        }
+                    }
+                },
+                tbody {
+                    rows.iter().take(PREVIEW_ROWS).map(|row| {
+                        let (style, status) = match &row.result {
+                            Ok(entry) => ("", format!("{} min {}", entry.minutes(), entry.entry_type().name())),
+                            Err(error) => ("bg-red-200", error.to_string()),
+                        };
+
+                        rsx!(
+                            tr {
+                                class: "{style}",
+                                row.cells.iter().map(|cell| rsx!(
+                                    td {
+                                        class: "px-1",
+                                        "{cell}"
+                                    }
+                                )),
+                                td {
+                                    class: "px-1",
+                                    "{status}"
+                                }
+                            }
+                        )
+                    })
+                }
+            }
+        }
+    ))
+}
+
+pub fn CsvImport(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let table = use_state(cx, || Option::<CsvTable>::None);
+    let mapping = use_state(cx, ColumnMapping::default);
+    let status = use_state(cx, String::new);
+
+    let parse_column = |value: &str| value.parse::<usize>().ok();
+    let column_value = |column: Option<usize>| column.map(|column| column.to_string()).unwrap_or_default();
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-4 text-gray-200",
+                "Import CSV"
+            },
+            files::OpenFile {
+                class: "col-span-4",
+                accept: ".csv,.tsv,text/csv",
+                on_open: move |file: PickedFile| {
+                    let table = table.clone();
+                    let mapping = mapping.clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(raw) = file.read_to_string().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        match csv::read_table(&raw) {
+                            Ok(parsed) => {
+                                mapping.set(ColumnMapping::guess(&parsed.headers));
+                                status.set(String::new());
+                                table.set(Some(parsed));
+                            }
+                            Err(error) => {
+                                status.set(format!("Could not parse {name}: {error}"));
+                                table.set(None);
+                            }
+                        }
+                    });
+                }
+            },
+            table.get().as_ref().map(|parsed| rsx!(
+                ColumnSelect {
+                    label: "Title",
+                    headers: parsed.headers.clone(),
+                    value: column_value(mapping.title),
+                    on_input: move |event: FormEvent| {
+                        mapping.make_mut().title = parse_column(&event.value);
+                    }
+                },
+                ColumnSelect {
+                    label: "Type",
+                    headers: parsed.headers.clone(),
+                    value: column_value(mapping.entry_type),
+                    on_input: move |event: FormEvent| {
+                        mapping.make_mut().entry_type = parse_column(&event.value);
+                    }
+                },
+                ColumnSelect {
+                    label: "Duration",
+                    headers: parsed.headers.clone(),
+                    value: column_value(mapping.duration),
+                    on_input: move |event: FormEvent| {
+                        mapping.make_mut().duration = parse_column(&event.value);
+                    }
+                },
+                DurationUnitSelect {
+                    selected: mapping.duration_unit,
+                    on_input: move |event: FormEvent| {
+                        if let Some(unit) = DurationUnit::from_name(&event.value) {
+                            mapping.make_mut().duration_unit = unit;
+                        }
+                    }
+                },
+                ColumnSelect {
+                    label: "Date",
+                    headers: parsed.headers.clone(),
+                    value: column_value(mapping.date),
+                    on_input: move |event: FormEvent| {
+                        mapping.make_mut().date = parse_column(&event.value);
+                    }
+                },
+                DateFormatSelect {
+                    on_input: move |event: FormEvent| {
+                        mapping.make_mut().date_format = import::DATE_FORMATS
+                            .iter()
+                            .find(|(_, format)| *format == event.value)
+                            .map(|(_, format)| *format);
+                    }
+                },
+                Preview {
+                    table: parsed.clone(),
+                    mapping: mapping.get().clone()
+                },
+                button {
+                    class: "col-span-1 col-end-5 {BUTTON_STYLE}",
+                    onclick: move |_| {
+                        let entries = csv::parse_rows(parsed, mapping.get())
+                            .into_iter()
+                            .filter_map(|row| row.result.ok())
+                            .collect();
+                        let summary = log_data.write().import_entries(entries);
+                        status.set(format!("Imported {} entries, skipped {} duplicates", summary.added, summary.duplicates));
+                    },
+                    "Import"
+                }
+            )),
+            p {
+                class: "col-span-4 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
+
+pub fn AnkiImport(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let status = use_state(cx, String::new);
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-4 text-gray-200",
+                "Import Anki Reviews"
+            },
+            files::OpenFile {
+                class: "col-span-4",
+                accept: ".apkg,.colpkg",
+                on_open: move |file: PickedFile| {
+                    let log_data = log_data.clone();
+                    let user = user.clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(package) = file.read().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        let settings = user.read().settings().clone();
+                        match anki::read_package(&package, |time| settings::local_day(&settings, time)) {
+                            Ok(imported) => {
+                                let summary = log_data.write().import_daily_entries(imported.entries);
+                                user.write().set_vocab_size(imported.mature_cards);
+                                status.set(format!(
+                                    "Imported {} days, updated {}, {} already imported. {} mature cards.",
+                                    summary.added, summary.updated, summary.duplicates, imported.mature_cards
+                                ));
+                            }
+                            Err(error) => status.set(format!("Could not import {name}: {error}")),
+                        }
+                    });
+                }
+            },
+            p {
+                class: "col-span-4 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
+
+pub fn LlDashboardImport(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let status = use_state(cx, String::new);
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-4 text-gray-200",
+                "Import from ll-dashboard"
+            },
+            files::OpenFile {
+                class: "col-span-4",
+                accept: ".json,application/json",
+                on_open: move |file: PickedFile| {
+                    let log_data = log_data.clone();
+                    let user = user.clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(raw) = file.read_to_string().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        let settings = user.read().settings().clone();
+                        match ll_dashboard::read_export(&raw, |time| settings::local_day(&settings, time)) {
+                            Ok(imported) => {
+                                let entries = log_data.write().import_entries(imported.entries);
+                                let goals = user.write().import_goals(imported.goals);
+                                status.set(format!(
+                                    "Imported {} entries and {} goals, skipped {} duplicates and {} unreadable activities",
+                                    entries.added, goals.added, entries.duplicates + goals.duplicates, imported.skipped
+                                ));
+                            }
+                            Err(error) => status.set(format!("Could not import {name}: {error}")),
+                        }
+                    });
+                }
+            },
+            p {
+                class: "col-span-4 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn RuleRow<'a>(cx: Scope, rule: MappingRule, on_change: EventHandler<'a, MappingRule>, on_remove: EventHandler<'a, ()>) -> Element<'a> {
+    cx.render(rsx!(
+        div {
+            class: "col-span-4 flex flex-row gap-2",
+            select {
+                value: "{rule.field.name()}",
+                oninput: move |evt: FormEvent| {
+                    let field = RULE_FIELDS
+                        .into_iter()
+                        .find(|field| field.name() == evt.value)
+                        .unwrap_or(RuleField::Project);
+                    on_change.call(MappingRule { field, ..rule.clone() });
+                },
+                RULE_FIELDS.iter().map(|field| rsx!(
+                    option {
+                        value: "{field.name()}",
+                        "{field.name()}"
+                    }
+                ))
+            },
+            input {
+                r#type: "text",
+                placeholder: if rule.field == RuleField::Tag { "is" } else { "contains" },
+                value: "{rule.pattern}",
+                oninput: move |evt: FormEvent| {
+                    on_change.call(MappingRule { pattern: evt.value.clone(), ..rule.clone() });
+                }
+            },
+            select {
+                value: "{rule.entry_type.name()}",
+                oninput: move |evt: FormEvent| {
+                    let entry_type = EntryType::from_name(&evt.value).unwrap_or(EntryType::Other);
+                    on_change.call(MappingRule { entry_type, ..rule.clone() });
+                },
+                ENTRY_TYPES.iter().map(|entry_type| rsx!(
+                    option {
+                        value: "{entry_type}",
+                        "{entry_type}"
+                    }
+                ))
+            },
+            input {
+                r#type: "text",
+                placeholder: "resource",
+                value: "{rule.resource}",
+                oninput: move |evt: FormEvent| {
+                    on_change.call(MappingRule { resource: evt.value.clone(), ..rule.clone() });
+                }
+            },
+            button {
+                class: "w-6 h-6",
+                onclick: move |_| {
+                    on_remove.call(());
+                },
+                TrashIcon {}
+            }
+        }
+    ))
+}
+
+pub fn TimeTrackerImport(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let report = use_state(cx, || Option::<(Tracker, CsvTable)>::None);
+    let status = use_state(cx, String::new);
+
+    let rules = settings.tracker_rules.clone();
+    let converted = report
+        .get()
+        .as_ref()
+        .map(|(tracker, table)| time_tracker::convert(*tracker, table, &rules, |start| settings.day_of(start)));
+    // Rules are kept with the settings, so they're ready for the next report
+    let update_rules = move |change: &dyn Fn(&mut Vec<MappingRule>)| {
+        let mut settings = user.read().settings().clone();
+        change(&mut settings.tracker_rules);
+        user.write().set_settings(settings);
+    };
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-4 text-gray-200",
+                "Import Toggl Track / Clockify"
+            },
+            files::OpenFile {
+                class: "col-span-4",
+                accept: ".csv,text/csv",
+                on_open: move |file: PickedFile| {
+                    let report = report.clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(raw) = file.read_to_string().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        match time_tracker::read_report(&raw) {
+                            Ok(parsed) => {
+                                status.set(format!("Read {} report", parsed.0.name()));
+                                report.set(Some(parsed));
+                            }
+                            Err(error) => {
+                                status.set(format!("Could not import {name}: {error}"));
+                                report.set(None);
+                            }
+                        }
+                    });
+                }
+            },
+            rules.iter().enumerate().map(|(index, rule)| rsx!(
+                RuleRow {
+                    key: "{index}",
+                    rule: rule.clone(),
+                    on_change: move |changed: MappingRule| {
+                        update_rules(&|rules| rules[index] = changed.clone());
+                    },
+                    on_remove: move |_| {
+                        update_rules(&|rules| {
+                            rules.remove(index);
+                        });
+                    }
+                }
+            )),
+            button {
+                class: "col-span-1 {BUTTON_STYLE}",
+                onclick: move |_| {
+                    update_rules(&|rules| {
+                        rules.push(MappingRule {
+                            field: RuleField::Project,
+                            pattern: String::new(),
+                            entry_type: EntryType::Other,
+                            resource: String::new(),
+                        })
+                    });
+                },
+                "+ Add Rule"
+            },
+            converted.map(|converted| {
+                let skipped = converted.skipped;
+                rsx!(
+                    div {
+                        class: "col-span-4 overflow-x-auto",
+                        p {
+                            class: "text-xs text-gray-200",
+                            "{converted.entries.len()} entries, {skipped} unreadable rows"
+                        },
+                        table {
+                            class: "w-full text-sm bg-white",
+                            tbody {
+                                converted.entries.iter().take(PREVIEW_ROWS).map(|entry| {
+                                    let span = entry
+                                        .span()
+                                        .map(|(start, end)| format!("{} – {}", start.format("%Y-%m-%d %H:%M"), end.format("%H:%M")))
+                                        .unwrap_or_default();
+                                    rsx!(
+                                        tr {
+                                            td { class: "px-1", "{entry.title()}" },
+                                            td { class: "px-1", "{entry.entry_type().name()}" },
+                                            td { class: "px-1", "{entry.resource().unwrap_or_default()}" },
+                                            td { class: "px-1", "{span}" },
+                                            td { class: "px-1", "{entry.minutes()} min" }
+                                        }
+                                    )
+                                })
+                            }
+                        }
+                    },
+                    button {
+                        class: "col-span-1 col-end-5 {BUTTON_STYLE}",
+                        onclick: move |_| {
+                            let summary = log_data.write().import_entries(converted.entries.clone());
+                            status.set(format!("Imported {} entries, skipped {} duplicates", summary.added, summary.duplicates));
+                        },
+                        "Import"
+                    }
+                )
+            }),
+            p {
+                class: "col-span-4 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
+
+pub fn TimeclockSection(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let status = use_state(cx, String::new);
+
+    let (export, unplaced) = timeclock::write(log_data.read().entries());
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-4 text-gray-200",
+                "Timeclock File"
+            },
+            files::OpenFile {
+                class: "col-span-3",
+                accept: ".timeclock,.journal,.txt",
+                on_open: move |file: PickedFile| {
+                    let log_data = log_data.clone();
+                    let settings = user.read().settings().clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(raw) = file.read_to_string().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        match timeclock::read(&raw, |start| settings.day_of(start)) {
+                            Ok(entries) => {
+                                let summary = log_data.write().import_entries(entries);
+                                status.set(format!("Imported {} entries, skipped {} duplicates", summary.added, summary.duplicates));
+                            }
+                            Err(error) => status.set(format!("Could not import {name}: {error}")),
+                        }
+                    });
+                }
+            },
+            files::SaveFile {
+                class: "col-span-1",
+                file_name: "nazad.timeclock",
+                contents: export
+            },
+            if unplaced > 0 {
+                rsx!(
+                    p {
+                        class: "col-span-4 text-xs text-gray-200",
+                        "{unplaced} entries without a date are left out of the export"
+                    }
+                )
+            },
+            p {
+                class: "col-span-4 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
+
+pub fn DataFileSection(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let status = use_state(cx, String::new);
+
+    let export = DataFile::new(log_data.read().clone(), user.read().clone()).to_json();
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-4 text-gray-200",
+                "Nazad Data File"
+            },
+            files::OpenFile {
+                class: "col-span-3",
+                accept: ".json,application/json",
+                on_open: move |file: PickedFile| {
+                    let log_data = log_data.clone();
+                    let user = user.clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(raw) = file.read_to_string().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        match DataFile::from_json(&raw) {
+                            Ok(imported) => {
+                                let entries = log_data.write().import_entries(imported.log.entries().to_vec());
+                                let goals = user.write().import_goals(imported.user.goals().to_vec());
+                                status.set(format!(
+                                    "Imported {} entries and {} goals, skipped {} duplicates",
+                                    entries.added, goals.added, entries.duplicates + goals.duplicates
+                                ));
+                            }
+                            Err(error) => status.set(format!("Could not import {name}: {error}")),
+                        }
+                    });
+                }
+            },
+            files::SaveFile {
+                class: "col-span-1",
+                file_name: "nazad.json",
+                contents: export
+            },
+            p {
+                class: "col-span-4 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
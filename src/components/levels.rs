@@ -0,0 +1,261 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use chrono::NaiveDate;
+
+use nazad_core::cefr::{self, CefrLevel, SKILLS};
+use nazad_core::log::{EntryType, LogData};
+use nazad_core::user::UserProfile;
+
+use crate::components::settings::{now, today};
+use crate::components::style::{BUTTON_STYLE, SELECTED_BUTTON_STYLE, PANEL_STYLE};
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 180.0;
+
+fn level_name(level: Option<CefrLevel>) -> &'static str {
+    level.map(|level| level.name()).unwrap_or("Below A1")
+}
+
+/// Draws `(day, value)` steps from `from` to `to` as polyline points, with
+/// values scaled so `max` reaches the top of the chart.
+fn step_points(steps: &[(NaiveDate, f64)], from: NaiveDate, to: NaiveDate, max: f64) -> String {
+    let span = (to - from).num_days().max(1) as f64;
+    let x = |day: NaiveDate| (day - from).num_days() as f64 / span * CHART_WIDTH;
+    let y = |value: f64| CHART_HEIGHT - value / max.max(f64::EPSILON) * CHART_HEIGHT;
+
+    let mut points = vec![];
+    let mut last = None;
+    for (day, value) in steps {
+        if let Some(previous) = last {
+            points.push(format!("{:.1},{:.1}", x(*day), y(previous)));
+        }
+        points.push(format!("{:.1},{:.1}", x(*day), y(*value)));
+        last = Some(*value);
+    }
+    if let Some(value) = last {
+        points.push(format!("{:.1},{:.1}", x(to), y(value)));
+    }
+    points.join(" ")
+}
+
+/// The estimated level over time next to the hours logged for the skill in
+/// the language, to see which hours a level jump took.
+#[inline_props]
+fn LevelChart(cx: Scope, skill: EntryType, language: Option<String>) -> Element<'a> {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = user.read();
+    let today = today(user.settings());
+
+    let language = language.as_deref();
+
+    let levels: Vec<(NaiveDate, f64)> = cefr::level_history(&cefr::statements(), &user.profile().can_do, language, skill)
+        .into_iter()
+        .map(|(at, level)| (at.date_naive(), level.map(|level| level.rank()).unwrap_or(0) as f64))
+        .collect();
+    let mut days: Vec<(NaiveDate, usize)> = log_data
+        .read()
+        .entries()
+        .iter()
+        .filter(|entry| entry.entry_type() == skill && entry.counts_for(language))
+        .filter_map(|entry| Some((entry.date().to_naive()?, entry.minutes())))
+        .collect();
+    days.sort();
+    let mut total = 0.0;
+    let hours: Vec<(NaiveDate, f64)> = days
+        .into_iter()
+        .map(|(day, minutes)| {
+            total += minutes as f64 / 60.0;
+            (day, total)
+        })
+        .collect();
+
+    // Nothing to draw before the first tick or entry
+    let from = levels.iter().chain(&hours).map(|(day, _)| *day).min()?;
+    let level_points = step_points(&levels, from, today, CefrLevel::ALL.len() as f64);
+    let hour_points = step_points(&hours, from, today, total);
+    let total = total.round();
+    let first_day = user.settings().format_date(from);
+    let last_day = user.settings().format_date(today);
+
+    cx.render(rsx!(
+        div {
+            class: "col-span-4 grid gap-1",
+            svg {
+                class: "w-full",
+                view_box: "-30 -10 {CHART_WIDTH + 70.0} {CHART_HEIGHT + 30.0}",
+                CefrLevel::ALL.iter().map(|level| {
+                    let y = CHART_HEIGHT - level.rank() as f64 / CefrLevel::ALL.len() as f64 * CHART_HEIGHT;
+                    rsx!(
+                        line {
+                            key: "{level.name()}",
+                            x1: "0",
+                            x2: "{CHART_WIDTH}",
+                            y1: "{y}",
+                            y2: "{y}",
+                            stroke: "#52525b"
+                        },
+                        text {
+                            x: "-28",
+                            y: "{y + 4.0}",
+                            fill: "currentColor",
+                            font_size: "11",
+                            "{level.name()}"
+                        }
+                    )
+                }),
+                polyline {
+                    points: "{hour_points}",
+                    fill: "none",
+                    stroke: "#3b82f6",
+                    stroke_width: "2"
+                },
+                polyline {
+                    points: "{level_points}",
+                    fill: "none",
+                    stroke: "#22c55e",
+                    stroke_width: "3"
+                },
+                text {
+                    x: "{CHART_WIDTH + 4.0}",
+                    y: "4",
+                    fill: "#3b82f6",
+                    font_size: "11",
+                    "{total}h"
+                },
+                text {
+                    x: "0",
+                    y: "{CHART_HEIGHT + 16.0}",
+                    fill: "currentColor",
+                    font_size: "11",
+                    "{first_day}"
+                },
+                text {
+                    x: "{CHART_WIDTH}",
+                    y: "{CHART_HEIGHT + 16.0}",
+                    fill: "currentColor",
+                    font_size: "11",
+                    text_anchor: "end",
+                    "{last_day}"
+                }
+            },
+            p {
+                class: "text-xs",
+                span { class: "text-green-500", "Level" },
+                " and ",
+                span { class: "text-blue-500", "hours logged" },
+                " over time"
+            }
+        }
+    ))
+}
+
+/// CEFR can-do checklists per language and skill, the level they add up to
+/// and how it developed next to the hours logged.
+pub fn LevelsPage(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let skill = use_state(cx, || EntryType::Listening);
+    let learned = user.read().profile().target_languages.clone();
+    let picked = use_state(cx, || None::<String>);
+    // The first language being learned until another is picked or when the picked one no longer is
+    let language = picked.get().clone().filter(|language| learned.contains(language)).or(learned.first().cloned());
+
+    let statements = cefr::statements();
+    let ticked = cefr::ticked(&user.read().profile().can_do, language.as_deref(), None);
+    let estimates: Vec<(EntryType, &str)> = SKILLS
+        .iter()
+        .map(|skill| (skill.clone(), level_name(cefr::estimated_level(&statements, &ticked, skill))))
+        .collect();
+    let level = level_name(cefr::estimated_level(&statements, &ticked, skill.get()));
+    let ticked = &ticked;
+    let language = &language;
+
+    cx.render(rsx!(
+        if learned.len() > 1 {
+            rsx!(
+                div {
+                    class: "flex flex-row gap-2 mx-4 mt-4",
+                    learned.clone().into_iter().map(|option| {
+                        let style = if Some(&option) == language.as_ref() { SELECTED_BUTTON_STYLE } else { BUTTON_STYLE };
+                        rsx!(
+                            button {
+                                key: "{option}",
+                                class: style,
+                                onclick: move |_| picked.set(Some(option.clone())),
+                                "{option}"
+                            }
+                        )
+                    })
+                }
+            )
+        },
+        div {
+            class: "flex flex-row gap-2 mx-4 mt-4",
+            estimates.into_iter().map(|(option, estimate)| {
+                let style = if option == *skill.get() { SELECTED_BUTTON_STYLE } else { BUTTON_STYLE };
+                let name = option.name();
+                rsx!(
+                    button {
+                        key: "{name}",
+                        class: style,
+                        onclick: move |_| skill.set(option.clone()),
+                        "{name} {estimate}"
+                    }
+                )
+            })
+        },
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4",
+                "Estimated level: {level}"
+            },
+            match language {
+                Some(language) => rsx!(
+                    LevelChart {
+                        skill: skill.get().clone(),
+                        language: language.clone()
+                    }
+                ),
+                None => rsx!(
+                    LevelChart {
+                        skill: skill.get().clone()
+                    }
+                ),
+            }
+        },
+        CefrLevel::ALL.iter().map(|level| {
+            let of_level: Vec<_> = statements.iter().filter(|can_do| can_do.skill == *skill.get() && can_do.level == *level).cloned().collect();
+            let done = of_level.iter().filter(|can_do| ticked.contains(&can_do.id)).count();
+            rsx!(
+                div {
+                    key: "{level.name()}",
+                    class: PANEL_STYLE,
+                    p {
+                        class: "col-span-4",
+                        "{level.name()} ({done} of {of_level.len()})"
+                    },
+                    of_level.into_iter().map(|can_do| {
+                        let checked = ticked.contains(&can_do.id);
+                        let language = language.clone();
+                        rsx!(
+                            label {
+                                key: "{can_do.id}",
+                                class: "col-span-4 flex flex-row gap-2 items-start",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: "{checked}",
+                                    onchange: move |evt: FormEvent| {
+                                        user.write().check_can_do(&can_do.id, language.as_deref(), evt.value == "true", now());
+                                    }
+                                },
+                                "{can_do.text}"
+                            }
+                        )
+                    })
+                }
+            )
+        })
+    ))
+}
@@ -0,0 +1,77 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use nazad_core::log::LogData;
+use nazad_core::stats;
+use nazad_core::user::UserProfile;
+
+const PANEL_STYLE: &str = "grid grid-cols-6 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600 text-gray-200";
+
+/// The books, shows, decks etc. that entries were logged against, with the
+/// time spent on each.
+pub fn LibraryPage(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let resources = stats::resources(&log_data.read());
+    let user = user.read();
+    let settings = user.settings();
+
+    if resources.is_empty() {
+        return cx.render(rsx!(
+            div {
+                class: PANEL_STYLE,
+                p {
+                    class: "col-span-6",
+                    "No resources yet. Entries logged with a resource, from the command line or a timeclock import, show up here."
+                }
+            }
+        ));
+    }
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            span { class: "col-span-2 font-bold", "Resource" },
+            span { class: "font-bold", "Type" },
+            span { class: "font-bold", "Time" },
+            span { class: "font-bold", "Last used" },
+            span { class: "font-bold", "Finished" },
+            resources.into_iter().map(|total| {
+                let minutes = settings.format_duration(total.minutes);
+                let type_name = total.entry_type.name();
+                let last_day = total.last_day.map(|day| settings.format_date(day)).unwrap_or_default();
+                let finished = total.finished_on.is_some();
+                let finished_on = total.finished_on.map(|day| settings.format_date(day)).unwrap_or_default();
+                let resource = total.resource.clone();
+                let entry_type = total.entry_type.clone();
+                rsx!(
+                    span {
+                        class: "col-span-2",
+                        "{total.resource}"
+                    },
+                    span {
+                        "{type_name}"
+                    },
+                    span {
+                        "{minutes} ({total.entries})"
+                    },
+                    span {
+                        "{last_day}"
+                    },
+                    label {
+                        class: "flex flex-row gap-2 items-center",
+                        input {
+                            r#type: "checkbox",
+                            checked: "{finished}",
+                            onchange: move |evt: FormEvent| {
+                                log_data.write().set_resource_finished(&resource, &entry_type, evt.value == "true");
+                            }
+                        },
+                        "{finished_on}"
+                    }
+                )
+            })
+        }
+    ))
+}
@@ -0,0 +1,273 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use nazad_core::data::DataFile;
+use nazad_core::log::LogData;
+use nazad_core::profiles::ProfileList;
+use nazad_core::user::{User, UserProfile};
+
+use crate::components::icons::TrashIcon;
+use crate::storage::{self, StorageError};
+use crate::components::style::BUTTON_STYLE;
+
+// How often the stored data is checked for changes made elsewhere
+const CHECK_INTERVAL_MS: u32 = 5_000;
+
+#[cfg(feature = "web")]
+async fn pause(ms: u32) {
+    gloo_timers::future::TimeoutFuture::new(ms).await;
+}
+
+#[cfg(feature = "desktop")]
+async fn pause(ms: u32) {
+    tokio::time::sleep(std::time::Duration::from_millis(ms.into())).await;
+}
+
+/// Saves the current state, or shows what it was merged with if the stored
+/// data changed elsewhere. Returns the error to show, if any.
+fn save(profile: usize, log_data: &UseSharedState<LogData>, user: &UseSharedState<UserProfile>) -> Result<(), StorageError> {
+    let file = DataFile::new(log_data.read().clone(), user.read().clone());
+    if let Some(merged) = storage::save(profile, &file)? {
+        *log_data.write() = merged.log;
+        *user.write() = merged.user;
+    }
+    Ok(())
+}
+
+/// Keeps the app's state on the device, so the dashboard passed as children
+/// survives restarts and works without a connection.
+#[inline_props]
+pub fn LocalMode<'a>(cx: Scope<'a>, children: Element<'a>) -> Element<'a> {
+    use_shared_state_provider(cx, ProfileList::default);
+    let profiles = use_shared_state::<ProfileList>(cx).unwrap();
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    // Nothing is saved before the stored state is shown, or the empty start would overwrite it
+    let ready = use_ref(cx, || false);
+    let status = use_state(cx, String::new);
+
+    use_future(cx, (), |_| {
+        let ready = ready.clone();
+        let status = status.clone();
+        let profiles = profiles.clone();
+        let log_data = log_data.clone();
+        let user = user.clone();
+        async move {
+            let loaded = storage::load_profiles().and_then(|list| {
+                let saved = storage::load(list.current())?;
+                Ok((list, saved))
+            });
+            match loaded {
+                Ok((list, saved)) => {
+                    if let Some(file) = saved {
+                        *log_data.write() = file.log;
+                        *user.write() = file.user;
+                    }
+                    *profiles.write() = list;
+                    ready.set(true);
+                }
+                // Saving now would replace data that might still be recovered
+                Err(error) => {
+                    status.set(format!(
+                        "Could not load the data kept in {}, changes are not saved: {error}",
+                        storage::location(profiles.read().current())
+                    ));
+                    return;
+                }
+            }
+
+            // Picks up e.g. `nazad log` writing the same file while the app is open
+            loop {
+                pause(CHECK_INTERVAL_MS).await;
+                let profile = profiles.read().current();
+                if *ready.read() && storage::changed_elsewhere(profile) {
+                    if let Err(error) = save(profile, &log_data, &user) {
+                        status.set(format!("Could not save to {}: {error}", storage::location(profile)));
+                    }
+                }
+            }
+        }
+    });
+
+    let current_log = log_data.read().clone();
+    let current_user = user.read().clone();
+    use_effect(cx, (&current_log, &current_user), |(_, current_user)| {
+        let ready = ready.clone();
+        let status = status.clone();
+        let profiles = profiles.clone();
+        let log_data = log_data.clone();
+        let user = user.clone();
+        async move {
+            if !*ready.read() {
+                return;
+            }
+            let profile = profiles.read().current();
+            let mut saved = save(profile, &log_data, &user);
+
+            // The switcher lists profiles by the name given in the profile editor
+            let name = &current_user.profile().name;
+            let listed = profiles.read().profiles().iter().any(|listed| listed.id == profile && listed.name == *name);
+            if saved.is_ok() && !listed {
+                profiles.write().rename(profile, name);
+                saved = storage::save_profiles(&profiles.read());
+            }
+
+            match saved {
+                Ok(()) if status.is_empty() => {}
+                Ok(()) => status.set(String::new()),
+                Err(error) => status.set(format!("Could not save to {}: {error}", storage::location(profile))),
+            }
+        }
+    });
+
+    cx.render(rsx!(
+        if !status.is_empty() {
+            rsx!(
+                div {
+                    class: "text-xs text-red-500 ml-2",
+                    "{status}"
+                }
+            )
+        },
+        children
+    ))
+}
+
+/// Loads another profile's data in place of the current one. The current
+/// data doesn't need saving, that happens after every change.
+fn switch_to(
+    id: usize,
+    new_name: Option<&str>,
+    profiles: &UseSharedState<ProfileList>,
+    log_data: &UseSharedState<LogData>,
+    user: &UseSharedState<UserProfile>,
+) -> Result<(), StorageError> {
+    let file = storage::load(id)?.unwrap_or_else(|| DataFile::new(LogData::new(), UserProfile::new()));
+    let mut list = profiles.read().clone();
+    list.select(id);
+    storage::save_profiles(&list)?;
+
+    *profiles.write() = list;
+    *log_data.write() = file.log;
+    *user.write() = file.user;
+    if let Some(name) = new_name {
+        user.write().set_profile(User {
+            name: name.to_string(),
+            ..User::default()
+        });
+    }
+    Ok(())
+}
+
+/// Switches between the people using this device, shown only in local mode.
+pub fn ProfileSwitcher(cx: Scope) -> Element {
+    let profiles = use_shared_state::<ProfileList>(cx)?;
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let new_name = use_state(cx, String::new);
+    let removing = use_state(cx, || None::<usize>);
+    let status = use_state(cx, String::new);
+
+    let list = profiles.read().clone();
+    let current = list.current();
+
+    cx.render(rsx!(
+        div {
+            class: "grid gap-1",
+            p {
+                class: "text-xs text-gray-500",
+                "Profiles on this device"
+            },
+            list.profiles().iter().map(|profile| {
+                let id = profile.id;
+                let name = if profile.name.is_empty() { format!("Profile {}", id + 1) } else { profile.name.clone() };
+                let weight = if id == current { "font-bold" } else { "" };
+                rsx!(
+                    div {
+                        key: "{id}",
+                        class: "flex flex-row gap-2 items-center",
+                        button {
+                            class: "grow text-left {weight}",
+                            onclick: move |_| {
+                                if let Err(error) = switch_to(id, None, profiles, log_data, user) {
+                                    status.set(format!("Could not switch profiles: {error}"));
+                                }
+                            },
+                            "{name}"
+                        },
+                        if id != current {
+                            rsx!(
+                                button {
+                                    class: "w-6 h-6",
+                                    onclick: move |_| removing.set(Some(id)),
+                                    TrashIcon {}
+                                }
+                            )
+                        }
+                    },
+                    if **removing == Some(id) {
+                        rsx!(
+                            div {
+                                class: "flex flex-row gap-2 items-center text-xs",
+                                span {
+                                    "Delete all of {name}'s data?"
+                                },
+                                button {
+                                    class: "underline",
+                                    onclick: move |_| {
+                                        let mut list = profiles.read().clone();
+                                        list.remove(id);
+                                        match storage::save_profiles(&list).and_then(|_| storage::remove(id)) {
+                                            Ok(()) => *profiles.write() = list,
+                                            Err(error) => status.set(format!("Could not delete the profile: {error}")),
+                                        }
+                                        removing.set(None);
+                                    },
+                                    "Delete"
+                                },
+                                button {
+                                    class: "underline",
+                                    onclick: move |_| removing.set(None),
+                                    "Keep"
+                                }
+                            }
+                        )
+                    }
+                )
+            }),
+            div {
+                class: "flex flex-row gap-2",
+                input {
+                    class: "w-32 text-zinc-900",
+                    r#type: "text",
+                    placeholder: "Name",
+                    value: "{new_name}",
+                    oninput: move |evt| new_name.set(evt.value.clone())
+                },
+                button {
+                    class: BUTTON_STYLE,
+                    disabled: "{new_name.trim().is_empty()}",
+                    onclick: move |_| {
+                        let name = new_name.trim().to_string();
+                        let mut list = profiles.read().clone();
+                        let id = list.add(&name);
+                        let added = storage::save_profiles(&list).and_then(|_| {
+                            *profiles.write() = list;
+                            switch_to(id, Some(&name), profiles, log_data, user)
+                        });
+                        match added {
+                            Ok(()) => new_name.set(String::new()),
+                            Err(error) => status.set(format!("Could not add the profile: {error}")),
+                        }
+                    },
+                    "Add"
+                }
+            },
+            p {
+                class: "text-xs text-red-500",
+                "{status}"
+            }
+        }
+    ))
+}
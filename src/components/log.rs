@@ -1,63 +1,17 @@
 #![allow(non_snake_case)]
 
 use dioxus::prelude::*;
+use chrono::NaiveDate;
 
-#[derive(PartialEq, Clone)]
-struct Date {
-    day: usize,
-    month: usize,
-    year: usize,
-}
-
-const ENTRY_TYPES:  [&str; 6] = ["FlashCard", "Listening", "Reading", "Speaking", "Writing", "Other"];
-
-#[derive(Clone)]
-enum EntryType {
-    FlashCard,
-    Listening,
-    Reading,
-    Speaking,
-    Writing,
-    Other
-}
-
-struct Entry {
-    title: String,
-    entry_type: EntryType,
-    hours: usize,
-    date: Date
-}
-
-impl Entry {
-    fn from_data(title: String, entry_type: EntryType, hours: usize, date: Date) -> Self {
-        Self {
-            title,
-            entry_type,
-            hours,
-            date,
-        }
-    }
-}
-
-pub struct LogData {
-    entries: Vec<Entry>
-}
+use nazad_core::date::Date;
+use nazad_core::log::{Entry, EntryType, LogData, ENTRY_TYPES};
+use nazad_core::settings::DurationUnits;
+use nazad_core::user::UserProfile;
 
-impl LogData {
-    pub fn new() -> Self {
-        Self {
-            entries: vec![]
-        }
-    }
-
-    fn add_entry(&mut self, title: String, entry_type: EntryType, hours: usize, date: Date) {
-        let entry = Entry::from_data(title, entry_type, hours, date);
-        self.entries.push(entry);
-    }
-}
+use crate::components::settings::{now, today};
 
 #[inline_props]
-fn LogTitleInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element {
+fn LogTitleInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
     cx.render(rsx!(
         input {
             class: "col-span-2",
@@ -71,7 +25,7 @@ fn LogTitleInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Elemen
 }
 
 #[inline_props]
-fn LogTypeSelect<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element {
+fn LogTypeSelect<'a>(cx: Scope, selected: EntryType, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
     cx.render(rsx!(
         select {
              oninput: move |evt| {
@@ -80,8 +34,9 @@ fn LogTypeSelect<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Elemen
             ENTRY_TYPES.iter().map(|entry_type| {
                 rsx!(
                     option {
-                        value: entry_type.clone(),
-                        entry_type.clone()
+                        value: "{entry_type}",
+                        selected: "{*entry_type == selected.name()}",
+                        "{entry_type}"
                     })
             })
         }
@@ -89,11 +44,38 @@ fn LogTypeSelect<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Elemen
 }
 
 #[inline_props]
-fn LogHoursInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element {
+fn LogLanguageSelect<'a>(cx: Scope, languages: Vec<String>, selected: String, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        select {
+            oninput: move |evt| {
+                on_input.call(evt)
+            },
+            languages.iter().map(|language| {
+                rsx!(
+                    option {
+                        key: "{language}",
+                        value: "{language}",
+                        selected: "{language == selected}",
+                        "{language}"
+                    })
+            })
+        }
+    ))
+}
+
+#[inline_props]
+fn LogHoursInput<'a>(cx: Scope, units: DurationUnits, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    let placeholder = match units {
+        DurationUnits::Minutes => "Minutes",
+        _ => "Hours",
+    };
+
+    // Text, so that `1:30` and `1h 30m` can be typed as well
     cx.render(rsx!(
         input {
             class: "",
-            r#type: "number",
+            r#type: "text",
+            placeholder: placeholder,
             oninput: move |evt| {
                 on_input.call(evt)
             }
@@ -102,10 +84,11 @@ fn LogHoursInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Elemen
 }
 
 #[inline_props]
-fn LogDateInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element {
+fn LogDateInput<'a>(cx: Scope, value: String, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
     cx.render(rsx!(
         input {
             r#type: "date",
+            value: "{value}",
             oninput: move |evt| {
                 on_input.call(evt)
             }
@@ -114,14 +97,29 @@ fn LogDateInput<'a>(cx: Scope, on_input: EventHandler<'a, FormEvent>) -> Element
 }
 
 pub fn Logger(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+
     let title = use_state(cx, || String::from(""));
-    let entry_type = use_state(cx, ||  EntryType::Other);
-    let hours = use_state(cx, || 0usize);
-    let date = use_state(cx, || Date{
-        day: 0,
-        month: 0,
-        year: 0
-    });
+    // Type and date follow the settings until they are picked, which may only load after this
+    let picked_type = use_state(cx, || None::<EntryType>);
+    let amount = use_state(cx, String::new);
+    let error = use_state(cx, || None::<String>);
+    let picked_date = use_state(cx, || None::<Date>);
+    let picked_language = use_state(cx, || None::<String>);
+
+    let entry_type = picked_type.get().clone().unwrap_or(settings.default_entry_type.clone());
+    let date = picked_date.get().clone().unwrap_or(Date::from_naive(today(&settings)));
+    let date_value = date.to_naive().map(|day| day.to_string()).unwrap_or_default();
+    let units = settings.duration_units;
+    // Entries count for the first target language unless another one is picked
+    let languages = user.read().profile().target_languages.clone();
+    let language = picked_language
+        .get()
+        .clone()
+        .filter(|picked| languages.contains(picked))
+        .or(languages.first().cloned());
+    let selected_language = language.clone().unwrap_or_default();
 
     cx.render(rsx!(
         div {
@@ -132,36 +130,67 @@ pub fn Logger(cx: Scope) -> Element {
                 }
             },
             LogTypeSelect {
+                selected: entry_type.clone(),
                 on_input: move |event: FormEvent| {
-                    let select = match event.value.as_str() {
-                        "FlashCard" => EntryType::FlashCard,
-                        "Listening" => EntryType::Listening,
-                        "Reading" => EntryType::Reading,
-                        "Speaking" => EntryType::Speaking,
-                        "Writing" => EntryType::Writing,
-                        _ => EntryType::Other
-                    };
-                    entry_type.set(select);
+                    let select = EntryType::from_name(&event.value).unwrap_or(EntryType::Other);
+                    picked_type.set(Some(select));
                 }
             },
             LogHoursInput {
+                units: units,
                 on_input: move |event: FormEvent| {
-                    hours.set(match event.value.parse::<usize>() {
-                        Ok(n) => n,
-                        Err(_) => 0
-                    });
+                    amount.set(event.value.clone());
                 }
             },
             LogDateInput {
+                value: date_value,
                 on_input: move |event: FormEvent| {
-
+                    if let Ok(parsed) = NaiveDate::parse_from_str(&event.value, "%Y-%m-%d") {
+                        picked_date.set(Some(Date::from_naive(parsed)));
+                    }
                 }
             },
+            if !languages.is_empty() {
+                rsx!(
+                    LogLanguageSelect {
+                        languages: languages,
+                        selected: selected_language,
+                        on_input: move |event: FormEvent| {
+                            picked_language.set(Some(event.value.clone()));
+                        }
+                    }
+                )
+            }
+            if let Some(error) = error.get() {
+                rsx!(
+                    p {
+                        class: "col-span-3 text-xs text-red-500 self-center",
+                        "{error}"
+                    }
+                )
+            }
             button {
                 class: "col-span-1 col-end-5 bg-blue-500 hover:bg-blue-400 text-white font-bold py-2 px-4 border-b-4 border-blue-700 hover:border-blue-500 rounded",
                 onclick: move |_| {
+                    let minutes = match units.parse_input(amount.get()) {
+                        Some(minutes) if minutes > 0 => minutes,
+                        _ => {
+                            let message = match amount.trim() {
+                                "" => "Enter how long the session took".to_string(),
+                                typed => format!("\"{typed}\" is not a duration, enter e.g. 1.5 or 1:30"),
+                            };
+                            error.set(Some(message));
+                            return;
+                        }
+                    };
+                    error.set(None);
+
                     let log_data = use_shared_state::<LogData>(cx).unwrap();
-                    log_data.write().add_entry(title.get().to_string(), entry_type.get().clone(), *hours.get(), date.get().clone());
+                    let mut entry = Entry::from_data(title.get().to_string(), entry_type.clone(), minutes, date.clone());
+                    if let Some(language) = &language {
+                        entry = entry.with_language(language.clone());
+                    }
+                    log_data.write().add(entry.with_logged_at(now()));
                 },
                 "Add Log"
             }
@@ -0,0 +1,111 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+
+use nazad_core::log::LogData;
+use nazad_core::projection::{self, Milestone, Outlook, Projection};
+use nazad_core::settings::Settings;
+use nazad_core::user::UserProfile;
+
+use crate::components::settings::today;
+use crate::components::style::PANEL_STYLE;
+
+fn describe(milestone: &Milestone, settings: &Settings) -> String {
+    match &milestone.outlook {
+        Outlook::Reached(day) => format!("Reached on {}", settings.format_date(*day)),
+        Outlook::Expected { date, earliest, latest } => {
+            let latest = latest.map(|day| settings.format_date(day)).unwrap_or("never at the slowest pace".to_string());
+            format!("Around {}, between {} and {latest}", settings.format_date(*date), settings.format_date(*earliest))
+        }
+        Outlook::OutOfSight => "Not in sight at the recent pace".to_string(),
+    }
+}
+
+#[inline_props]
+fn ProjectionRow(cx: Scope, projection: Projection, settings: Settings) -> Element<'a> {
+    let name = projection.track.name();
+    let total = settings.format_duration(projection.total_minutes);
+    let pace = settings.format_duration(projection.pace.minutes_per_day.round() as usize);
+
+    cx.render(rsx!(
+        div {
+            class: "col-span-4 grid grid-cols-4 gap-1",
+            span {
+                class: "col-span-2 font-bold",
+                "{name}"
+            },
+            span {
+                class: "col-span-2 text-xs text-gray-400",
+                "{total} so far, {pace} a day lately"
+            },
+            projection.milestones.iter().map(|milestone| {
+                let hours = milestone.hours;
+                let progress = (projection.total_minutes * 100 / (hours * 60).max(1)).min(100);
+                let outlook = describe(milestone, settings);
+                rsx!(
+                    span {
+                        key: "{hours}",
+                        class: "col-span-1 text-sm",
+                        "{hours}h"
+                    },
+                    div {
+                        class: "col-span-3 grid gap-1",
+                        div {
+                            class: "h-1 rounded bg-zinc-700",
+                            div {
+                                class: "h-1 rounded bg-blue-500",
+                                style: "width: {progress}%"
+                            }
+                        },
+                        span {
+                            class: "text-xs",
+                            "{outlook}"
+                        }
+                    }
+                )
+            })
+        }
+    ))
+}
+
+/// Expected dates of the hour milestones in the settings, overall, per
+/// target language and per skill, with the range the recent pace allows.
+pub fn MilestoneSection(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let user = user.read();
+    let settings = user.settings().clone();
+
+    let projections = projection::projections(
+        &log_data.read(),
+        &user.profile().target_languages,
+        today(&settings),
+        &settings.milestones,
+    );
+    let tracked = projections.into_iter().filter(|projection| projection.total_minutes > 0).collect::<Vec<_>>();
+
+    if tracked.is_empty() || settings.milestones.is_empty() {
+        return None;
+    }
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4",
+                "Milestones"
+            },
+            tracked.into_iter().map(|projection| {
+                let name = projection.track.name().to_string();
+                let settings = settings.clone();
+                rsx!(
+                    ProjectionRow {
+                        key: "{name}",
+                        projection: projection,
+                        settings: settings
+                    }
+                )
+            })
+        }
+    ))
+}
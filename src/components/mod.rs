@@ -2,4 +2,20 @@ pub mod actions;
 pub mod user;
 pub mod icons;
 pub mod dashboard;
-pub mod log;
\ No newline at end of file
+pub mod history;
+pub mod goals;
+pub mod library;
+pub mod statistics;
+pub mod balance;
+pub mod review;
+pub mod levels;
+pub mod wrapped;
+pub mod settings;
+pub mod files;
+pub mod style;
+pub mod log;
+pub mod milestones;
+pub mod import;
+pub mod local;
+#[cfg(feature = "web")]
+pub mod server;
@@ -0,0 +1,186 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use dioxus_router::Link;
+
+use nazad_core::log::LogData;
+use nazad_core::review::{self, Change, Comparison, Review};
+use nazad_core::settings::Settings;
+use nazad_core::user::UserProfile;
+
+use crate::components::settings::today;
+use crate::routes;
+use crate::components::style::{BUTTON_STYLE, SELECTED_BUTTON_STYLE, PANEL_STYLE};
+
+/// The change as `+1h 10m (+28%)`, formatted with `format`.
+fn describe_change(change: &Change, format: impl Fn(usize) -> String) -> (String, &'static str) {
+    let sign = if change.delta() < 0 { "-" } else { "+" };
+    let amount = format(change.delta().unsigned_abs() as usize);
+    let relative = change
+        .relative()
+        .map(|relative| format!(" ({sign}{:.0}%)", relative.abs() * 100.0))
+        .unwrap_or_default();
+    let color = match change.delta() {
+        0 => "text-gray-400",
+        delta if delta > 0 => "text-green-500",
+        _ => "text-red-500",
+    };
+    (format!("{sign}{amount}{relative}"), color)
+}
+
+#[inline_props]
+fn ChangeRow<'a>(cx: Scope, label: &'a str, change: Change, minutes: bool, settings: Settings) -> Element<'a> {
+    let format = |value: usize| if *minutes { settings.format_duration(value) } else { value.to_string() };
+    let current = format(change.current);
+    let previous = format(change.previous);
+    let (difference, color) = describe_change(change, format);
+
+    cx.render(rsx!(
+        span {
+            "{label}"
+        },
+        span {
+            "{current}"
+        },
+        span {
+            "{previous}"
+        },
+        span {
+            class: "{color}",
+            "{difference}"
+        }
+    ))
+}
+
+/// The two periods of a review side by side.
+#[inline_props]
+fn ReviewTable(cx: Scope, comparison: Comparison) -> Element<'a> {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let user = user.read();
+    let settings = user.settings().clone();
+    let review = Review::of(&log_data.read(), &user, *comparison, today(&settings));
+
+    let period = |report: &review::PeriodReport| {
+        format!("{} to {}", settings.format_date(report.summary.from), settings.format_date(report.summary.to))
+    };
+    let current = period(&review.current);
+    let previous = period(&review.previous);
+    let goals = review.current.goals_completed.join(", ");
+    let resources = review.current.resources_finished.join(", ");
+    let settings = &settings;
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            span { class: "font-bold", "" },
+            span { class: "font-bold", "{current}" },
+            span { class: "font-bold", "{previous}" },
+            span { class: "font-bold", "Change" },
+            ChangeRow { label: "Total", change: review.total_minutes(), minutes: true, settings: settings.clone() },
+            review.per_type().into_iter().filter(|(_, change)| change.current + change.previous > 0).map(|(entry_type, change)| rsx!(
+                ChangeRow {
+                    key: "{entry_type.name()}",
+                    label: entry_type.name(),
+                    change: change,
+                    minutes: true,
+                    settings: settings.clone()
+                }
+            )),
+            ChangeRow { label: "Active days", change: review.active_days(), minutes: false, settings: settings.clone() },
+            ChangeRow { label: "Goals completed", change: review.goals_completed(), minutes: false, settings: settings.clone() },
+            ChangeRow { label: "Resources finished", change: review.resources_finished(), minutes: false, settings: settings.clone() },
+            if !goals.is_empty() {
+                rsx!(
+                    p {
+                        class: "col-span-4 text-sm",
+                        "Completed: {goals}"
+                    }
+                )
+            }
+            if !resources.is_empty() {
+                rsx!(
+                    p {
+                        class: "col-span-4 text-sm",
+                        "Finished: {resources}"
+                    }
+                )
+            }
+        }
+    ))
+}
+
+/// Compares this week, month or year with an earlier one.
+pub fn ReviewPage(cx: Scope) -> Element {
+    let comparison = use_state(cx, || Comparison::LastWeek);
+
+    cx.render(rsx!(
+        div {
+            class: "flex flex-row flex-wrap gap-2 mx-4 mt-4",
+            Comparison::ALL.iter().map(|option| {
+                let style = if option == comparison.get() { SELECTED_BUTTON_STYLE } else { BUTTON_STYLE };
+                rsx!(
+                    button {
+                        key: "{option.name()}",
+                        class: style,
+                        onclick: move |_| comparison.set(*option),
+                        "{option.name()}"
+                    }
+                )
+            })
+        },
+        ReviewTable {
+            comparison: *comparison.get()
+        }
+    ))
+}
+
+/// Last week's review, shown on the dashboard from the first day of a new
+/// week until it is dismissed.
+pub fn WeeklyReview(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let today = today(&settings);
+    let week = review::weekly_review_due(&settings, today)?;
+
+    let review = Review::of(&log_data.read(), &user.read(), Comparison::LastWeek, today);
+    let total = review.total_minutes();
+    if total.current + total.previous == 0 {
+        return None;
+    }
+    let time = settings.format_duration(total.current);
+    let (difference, color) = describe_change(&total, |minutes| settings.format_duration(minutes));
+    let days = review.current.summary.active_days;
+    let goals = review.current.goals_completed.len();
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4 font-bold",
+                "Your week in review"
+            },
+            p {
+                class: "col-span-4",
+                "{time} on {days} days, ",
+                span { class: "{color}", "{difference}" },
+                " on the week before, {goals} goals completed."
+            },
+            Link {
+                class: "col-span-2 underline",
+                to: routes::REVIEW,
+                "See the full review"
+            },
+            button {
+                class: "col-span-2 underline text-right",
+                onclick: move |_| {
+                    let mut settings = user.read().settings().clone();
+                    settings.reviewed_week = Some(week);
+                    user.write().set_settings(settings);
+                },
+                "Dismiss"
+            }
+        }
+    ))
+}
@@ -0,0 +1,261 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use gloo_timers::future::TimeoutFuture;
+
+use nazad_core::api::{self, Credentials, Session};
+use nazad_core::log::LogData;
+use nazad_core::sync::Replica;
+use nazad_core::user::UserProfile;
+
+use crate::backend::{now_ms, ApiClient, RequestError};
+use crate::components::style::BUTTON_STYLE;
+
+const SYNC_INTERVAL_MS: u32 = 30_000;
+
+/// Shows the login screen until there is a session, then the dashboard
+/// passed as children together with the sync status.
+#[inline_props]
+pub fn ServerMode<'a>(cx: Scope<'a>, client: ApiClient, children: Element<'a>) -> Element<'a> {
+    let session = use_state(cx, || client.saved_session());
+
+    match session.get() {
+        Some(current) => cx.render(rsx!(
+            ServerSync {
+                client: client.with_session(current.clone()),
+                on_logout: move |_| session.set(None)
+            },
+            children
+        )),
+        None => cx.render(rsx!(
+            Login {
+                client: client.clone(),
+                session: session
+            }
+        )),
+    }
+}
+
+#[inline_props]
+fn Login<'a>(cx: Scope<'a>, client: ApiClient, session: &'a UseState<Option<Session>>) -> Element<'a> {
+    let username = use_state(cx, String::new);
+    let password = use_state(cx, String::new);
+    let status = use_state(cx, String::new);
+
+    let submit = move |register: bool| {
+        let client = client.clone();
+        let credentials = Credentials {
+            username: username.get().clone(),
+            password: password.get().clone(),
+        };
+        let status = status.clone();
+        let session = (*session).clone();
+
+        if register && credentials.password.chars().count() < api::MIN_PASSWORD_LENGTH {
+            status.set(format!("Passwords need at least {} characters", api::MIN_PASSWORD_LENGTH));
+            return;
+        }
+
+        cx.spawn(async move {
+            let result = if register {
+                client.register(&credentials).await
+            } else {
+                client.login(&credentials).await
+            };
+            match result {
+                Ok(new_session) => {
+                    client.save_session(&new_session);
+                    session.set(Some(new_session));
+                }
+                Err(RequestError::Unauthorized) => status.set("Wrong username or password".to_string()),
+                Err(RequestError::Status(429, _)) => status.set("Too many failed logins, wait a moment and try again".to_string()),
+                Err(error) => status.set(format!("Could not reach {}: {error}", client.name())),
+            }
+        });
+    };
+
+    cx.render(rsx!(
+        div {
+            class: "grid grid-cols-2 gap-2 bg-zinc-600 m-auto mt-20 p-2 w-96 rounded-md border-8 border-zinc-600",
+            p {
+                class: "col-span-2 text-gray-200",
+                "Log in to {client.name()}"
+            },
+            input {
+                class: "col-span-2",
+                r#type: "text",
+                placeholder: "Username",
+                autocomplete: "username",
+                value: "{username}",
+                oninput: move |evt| username.set(evt.value.clone())
+            },
+            input {
+                class: "col-span-2",
+                r#type: "password",
+                placeholder: "Password",
+                autocomplete: "current-password",
+                value: "{password}",
+                oninput: move |evt| password.set(evt.value.clone())
+            },
+            button {
+                class: BUTTON_STYLE,
+                onclick: move |_| submit(false),
+                "Log in"
+            },
+            button {
+                class: BUTTON_STYLE,
+                onclick: move |_| submit(true),
+                "Create account"
+            },
+            p {
+                class: "col-span-2 text-gray-200",
+                "{status}"
+            }
+        }
+    ))
+}
+
+// Pushes the replica's queued edits and pulls what changed elsewhere, until nothing is left to push
+async fn sync(
+    client: ApiClient,
+    replica: UseRef<Replica>,
+    syncing: UseRef<bool>,
+    log_data: UseSharedState<LogData>,
+    user: UseSharedState<UserProfile>,
+    status: UseState<String>,
+) {
+    if *syncing.read() {
+        return;
+    }
+    syncing.set(true);
+
+    loop {
+        let request = replica.write().request();
+        match client.sync(&request).await {
+            Ok(response) => {
+                Replica::apply(&mut replica.write(), response);
+                let saved = client.save_replica(&replica.read());
+
+                let (synced_log, synced_user) = replica.read().state();
+                if *log_data.read() != synced_log {
+                    *log_data.write() = synced_log;
+                }
+                if *user.read() != synced_user {
+                    *user.write() = synced_user;
+                }
+                match saved {
+                    Ok(()) => status.set(format!("Synced with {}", client.name())),
+                    Err(error) => status.set(format!("Synced with {}, but could not keep the data on this device: {error}", client.name())),
+                }
+
+                if !replica.read().has_pending() {
+                    break;
+                }
+            }
+            Err(error) => {
+                replica.write().requeue(&request);
+                let saved = client.save_replica(&replica.read());
+                match (error, saved) {
+                    (RequestError::Unauthorized, _) => status.set("Your session has ended, log out and in again".to_string()),
+                    (_, Ok(())) => status.set(format!("Offline, changes are saved on this device until {} can be reached", client.name())),
+                    (_, Err(error)) => status.set(format!("Offline, and changes could not be kept on this device: {error}")),
+                }
+                break;
+            }
+        }
+    }
+
+    syncing.set(false);
+}
+
+/// Keeps the app's state in the device's replica and syncs it with the
+/// server: right after every edit and every half minute for changes made on
+/// other devices.
+#[inline_props]
+fn ServerSync<'a>(cx: Scope<'a>, client: ApiClient, on_logout: EventHandler<'a, ()>) -> Element<'a> {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let replica = use_ref(cx, || client.saved_replica());
+    // Edits are only committed once the app shows the replica's state
+    let ready = use_ref(cx, || false);
+    let syncing = use_ref(cx, || false);
+    let status = use_state(cx, || format!("Connecting to {}", client.name()));
+
+    use_future(cx, (), |_| {
+        let client = client.clone();
+        let replica = replica.clone();
+        let ready = ready.clone();
+        let syncing = syncing.clone();
+        let log_data = log_data.clone();
+        let user = user.clone();
+        let status = status.clone();
+        async move {
+            let (saved_log, saved_user) = replica.read().state();
+            *log_data.write() = saved_log;
+            *user.write() = saved_user;
+            ready.set(true);
+
+            loop {
+                sync(client.clone(), replica.clone(), syncing.clone(), log_data.clone(), user.clone(), status.clone()).await;
+                TimeoutFuture::new(SYNC_INTERVAL_MS).await;
+            }
+        }
+    });
+
+    let current_log = log_data.read().clone();
+    let current_user = user.read().clone();
+    use_effect(cx, (&current_log, &current_user), |(current_log, current_user)| {
+        let client = client.clone();
+        let replica = replica.clone();
+        let ready = ready.clone();
+        let syncing = syncing.clone();
+        let log_data = log_data.clone();
+        let user = user.clone();
+        let status = status.clone();
+        async move {
+            if !*ready.read() {
+                return;
+            }
+            if !replica.write().commit(&current_log, &current_user, now_ms()) {
+                return;
+            }
+            if let Err(error) = client.save_replica(&replica.read()) {
+                status.set(format!("Could not keep changes on this device: {error}"));
+            }
+            sync(client, replica, syncing, log_data, user, status).await;
+        }
+    });
+
+    let username = client.session().map(|session| session.username.clone()).unwrap_or_default();
+
+    cx.render(rsx!(
+        div {
+            class: "flex flex-row gap-2 text-xs text-gray-500 ml-2",
+            span {
+                "{status}"
+            },
+            span {
+                class: "ml-auto",
+                "{username}"
+            },
+            button {
+                class: "mr-2 underline",
+                onclick: move |_| {
+                    // Stop committing before clearing the state, or clearing it would be synced as deletes.
+                    // The replica stays on the device with any edits that are still queued.
+                    ready.set(false);
+                    *log_data.write() = LogData::new();
+                    *user.write() = UserProfile::new();
+
+                    let client = client.clone();
+                    client.forget_session();
+                    cx.spawn(async move {
+                        let _ = client.logout().await;
+                    });
+                    on_logout.call(());
+                },
+                "Log out"
+            }
+        }
+    ))
+}
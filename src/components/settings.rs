@@ -0,0 +1,490 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Utc, Weekday};
+
+use nazad_core::data::DataFile;
+use nazad_core::ladder::Ladder;
+use nazad_core::log::{EntryType, LogData, ENTRY_TYPES};
+use nazad_core::profiles::ProfileList;
+use nazad_core::projection::parse_milestones;
+use nazad_core::settings::{DateFormat, DurationUnits, Settings, Theme};
+use nazad_core::user::UserProfile;
+
+use crate::components::files::{self, PickedFile};
+use crate::components::import;
+use crate::components::style::{BUTTON_STYLE, PANEL_STYLE};
+use crate::storage;
+use crate::webdav::{self, WebDavAccount, WebDavSetup};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Choices for `Settings::neglect_days`, 0 turns the warning off.
+const NEGLECT_DAYS: [usize; 6] = [0, 3, 7, 14, 30, 60];
+
+fn neglect_name(days: usize) -> String {
+    match days {
+        0 => "Never warn".to_string(),
+        days => format!("{days} days"),
+    }
+}
+
+/// The current time in the device's time zone, with its offset to UTC.
+pub fn now() -> DateTime<FixedOffset> {
+    Local::now().fixed_offset()
+}
+
+/// The day something that happened at `time` counts for, in the device's
+/// time zone and going by the day boundary in `settings`.
+pub fn local_day(settings: &Settings, time: DateTime<Utc>) -> NaiveDate {
+    settings.day_of(time.with_timezone(&Local).naive_local())
+}
+
+/// The day it is now, going by the day boundary in `settings`.
+pub fn today(settings: &Settings) -> NaiveDate {
+    settings.day_of(now().naive_local())
+}
+
+/// Colors the app passed as children according to the theme setting.
+#[inline_props]
+pub fn Themed<'a>(cx: Scope<'a>, children: Element<'a>) -> Element<'a> {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let class = match user.read().settings().theme {
+        Theme::System => "bg-white text-zinc-900 dark:bg-zinc-900 dark:text-gray-100",
+        Theme::Light => "bg-white text-zinc-900",
+        Theme::Dark => "bg-zinc-900 text-gray-100",
+    };
+
+    cx.render(rsx!(
+        div {
+            class: "{class}",
+            children
+        }
+    ))
+}
+
+#[inline_props]
+fn SettingSelect<'a>(cx: Scope, label: &'a str, options: Vec<String>, value: String, on_input: EventHandler<'a, FormEvent>) -> Element<'a> {
+    cx.render(rsx!(
+        label {
+            class: "col-span-2",
+            "{label}"
+        },
+        select {
+            class: "col-span-2 text-zinc-900",
+            oninput: move |evt| on_input.call(evt),
+            options.iter().map(|option| rsx!(
+                option {
+                    key: "{option}",
+                    value: "{option}",
+                    selected: "{option == value}",
+                    "{option}"
+                }
+            ))
+        }
+    ))
+}
+
+fn PreferencesSection(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let milestones = settings.milestones.iter().map(usize::to_string).collect::<Vec<_>>().join(", ");
+
+    // Applies one change to the stored settings
+    let update = move |change: &dyn Fn(&mut Settings)| {
+        let mut settings = user.read().settings().clone();
+        change(&mut settings);
+        user.write().set_settings(settings);
+    };
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4",
+                "Preferences"
+            },
+            SettingSelect {
+                label: "Week starts on",
+                options: WEEKDAYS.iter().map(Weekday::to_string).collect(),
+                value: settings.week_start.to_string(),
+                on_input: move |evt: FormEvent| {
+                    if let Ok(day) = evt.value.parse::<Weekday>() {
+                        update(&|settings| settings.week_start = day);
+                    }
+                }
+            },
+            SettingSelect {
+                label: "New day starts at",
+                options: (0..24).map(|hour| format!("{hour:02}:00")).collect(),
+                value: format!("{:02}:00", settings.day_boundary_hour),
+                on_input: move |evt: FormEvent| {
+                    if let Some(Ok(hour)) = evt.value.split(':').next().map(str::parse::<u32>) {
+                        update(&|settings| settings.day_boundary_hour = hour);
+                    }
+                }
+            },
+            SettingSelect {
+                label: "Dates",
+                options: DateFormat::ALL.iter().map(|option| option.name().to_string()).collect(),
+                value: settings.date_format.name().to_string(),
+                on_input: move |evt: FormEvent| {
+                    if let Some(format) = DateFormat::from_name(&evt.value) {
+                        update(&|settings| settings.date_format = format);
+                    }
+                }
+            },
+            SettingSelect {
+                label: "Durations",
+                options: DurationUnits::ALL.iter().map(|option| option.name().to_string()).collect(),
+                value: settings.duration_units.name().to_string(),
+                on_input: move |evt: FormEvent| {
+                    if let Some(units) = DurationUnits::from_name(&evt.value) {
+                        update(&|settings| settings.duration_units = units);
+                    }
+                }
+            },
+            SettingSelect {
+                label: "Type of new entries",
+                options: ENTRY_TYPES.iter().map(|name| name.to_string()).collect(),
+                value: settings.default_entry_type.name().to_string(),
+                on_input: move |evt: FormEvent| {
+                    if let Some(entry_type) = EntryType::from_name(&evt.value) {
+                        update(&|settings| settings.default_entry_type = entry_type.clone());
+                    }
+                }
+            },
+            SettingSelect {
+                label: "Theme",
+                options: Theme::ALL.iter().map(|option| option.name().to_string()).collect(),
+                value: settings.theme.name().to_string(),
+                on_input: move |evt: FormEvent| {
+                    if let Some(theme) = Theme::from_name(&evt.value) {
+                        update(&|settings| settings.theme = theme);
+                    }
+                }
+            },
+            SettingSelect {
+                label: "Input share target",
+                options: (0..=10).map(|tenth| format!("{}%", tenth * 10)).collect(),
+                value: format!("{}%", settings.input_share_target),
+                on_input: move |evt: FormEvent| {
+                    if let Ok(percent) = evt.value.trim_end_matches('%').parse::<u32>() {
+                        update(&|settings| settings.input_share_target = percent);
+                    }
+                }
+            },
+            SettingSelect {
+                label: "Warn about skills left alone for",
+                options: NEGLECT_DAYS.iter().map(|days| neglect_name(*days)).collect(),
+                value: neglect_name(settings.neglect_days),
+                on_input: move |evt: FormEvent| {
+                    if let Some(days) = NEGLECT_DAYS.iter().find(|days| neglect_name(**days) == evt.value) {
+                        update(&|settings| settings.neglect_days = *days);
+                    }
+                }
+            },
+            label {
+                class: "col-span-2",
+                "Milestones in hours"
+            },
+            input {
+                class: "col-span-2 text-zinc-900",
+                r#type: "text",
+                value: "{milestones}",
+                onchange: move |evt: FormEvent| {
+                    let milestones = parse_milestones(&evt.value);
+                    update(&|settings| settings.milestones = milestones.clone());
+                }
+            },
+            label {
+                class: "col-span-4 flex flex-row gap-2 items-center",
+                input {
+                    r#type: "checkbox",
+                    checked: "{settings.visible_at_startup}",
+                    onchange: move |evt: FormEvent| {
+                        let visible = evt.value == "true";
+                        update(&|settings| settings.visible_at_startup = visible);
+                    }
+                },
+                "Show the profile sidebar at startup"
+            }
+        }
+    ))
+}
+
+/// Share of the time meant for each entry type, drawn over the skill
+/// balance chart on the statistics page.
+fn SkillTargetSection(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let total: u32 = settings.skill_targets.iter().map(|(_, percent)| percent).sum();
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4",
+                "Skill Targets"
+            },
+            EntryType::ALL.iter().map(|entry_type| {
+                let name = entry_type.name();
+                let percent = settings.skill_target(entry_type);
+                rsx!(
+                    label {
+                        key: "{name}",
+                        class: "col-span-2",
+                        "{name} %"
+                    },
+                    input {
+                        class: "col-span-2 text-zinc-900",
+                        r#type: "number",
+                        min: "0",
+                        max: "100",
+                        value: "{percent}",
+                        onchange: move |evt: FormEvent| {
+                            let percent = evt.value.parse::<u32>().unwrap_or_default().min(100);
+                            let mut settings = user.read().settings().clone();
+                            settings.set_skill_target(entry_type, percent);
+                            user.write().set_settings(settings);
+                        }
+                    }
+                )
+            }),
+            if total > 0 && total != 100 {
+                rsx!(
+                    p {
+                        class: "col-span-4 text-xs text-yellow-500",
+                        "The targets add up to {total}%, they are compared as given"
+                    }
+                )
+            }
+        }
+    ))
+}
+
+/// Picks the level ladder shown with the profile, or adds one from a JSON
+/// file shaped like those in `nazad-core/ladders`.
+fn LadderSection(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let status = use_state(cx, String::new);
+    let settings = user.read().settings().clone();
+    let ladder = settings.current_ladder();
+    let levels = ladder
+        .levels
+        .iter()
+        .map(|level| format!("{} from {}h", level.name, level.hours))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let skills = ladder.skills.iter().map(EntryType::name).collect::<Vec<_>>().join(" and ");
+    let learned = user.read().profile().target_languages.clone();
+    let language = settings.ladder_language_of(&learned).unwrap_or_default().to_string();
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4",
+                "Level Ladder"
+            },
+            SettingSelect {
+                label: "Levels by",
+                options: settings.ladders().into_iter().map(|ladder| ladder.name).collect(),
+                value: ladder.name.clone(),
+                on_input: move |evt: FormEvent| {
+                    let mut settings = user.read().settings().clone();
+                    settings.ladder = evt.value.clone();
+                    user.write().set_settings(settings);
+                }
+            },
+            if !learned.is_empty() {
+                rsx!(
+                    SettingSelect {
+                        label: "Counting hours in",
+                        options: learned.clone(),
+                        value: language,
+                        on_input: move |evt: FormEvent| {
+                            let mut settings = user.read().settings().clone();
+                            settings.ladder_language = Some(evt.value.clone());
+                            user.write().set_settings(settings);
+                        }
+                    }
+                )
+            }
+            p {
+                class: "col-span-4 text-xs",
+                "Counts {skills}: {levels}"
+            },
+            files::OpenFile {
+                class: "col-span-4",
+                accept: ".json,application/json",
+                on_open: move |file: PickedFile| {
+                    let user = user.clone();
+                    let status = status.clone();
+
+                    cx.spawn(async move {
+                        let name = file.name();
+                        let Some(raw) = file.read_to_string().await else {
+                            status.set(format!("Could not read {name}"));
+                            return;
+                        };
+
+                        match Ladder::from_json(&raw) {
+                            Ok(ladder) => {
+                                status.set(format!("Added {}", ladder.name));
+                                let mut settings = user.read().settings().clone();
+                                settings.add_ladder(ladder);
+                                user.write().set_settings(settings);
+                            }
+                            Err(error) => status.set(format!("Could not add {name}: {error}")),
+                        }
+                    });
+                }
+            },
+            p {
+                class: "col-span-4",
+                "{status}"
+            }
+        }
+    ))
+}
+
+#[inline_props]
+fn AccountInput<'a>(cx: Scope, label: &'a str, kind: &'a str, value: String, on_change: EventHandler<'a, String>) -> Element<'a> {
+    cx.render(rsx!(
+        label {
+            class: "col-span-2",
+            "{label}"
+        },
+        input {
+            class: "col-span-2 text-zinc-900",
+            r#type: "{kind}",
+            value: "{value}",
+            onchange: move |evt: FormEvent| on_change.call(evt.value.clone())
+        }
+    ))
+}
+
+/// Syncs the current profile with a data file on a WebDAV server, like
+/// `nazad sync`. Only shown in local mode, a Nazad server syncs on its own.
+fn WebDavSection(cx: Scope) -> Element {
+    let profiles = use_shared_state::<ProfileList>(cx)?;
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let status = use_state(cx, String::new);
+    let profile = profiles.read().current();
+    let setup = use_ref(cx, || (profile, storage::load_webdav(profile).unwrap_or_default()));
+
+    // Another profile was picked in the sidebar
+    if setup.read().0 != profile {
+        *setup.write_silent() = (profile, storage::load_webdav(profile).unwrap_or_default());
+    }
+    let account = setup.read().1.account.clone();
+
+    // Keeps an edit of the account on the device
+    let change = move |edit: &dyn Fn(&mut WebDavAccount)| {
+        edit(&mut setup.write().1.account);
+        if let Err(error) = storage::save_webdav(profile, &setup.read().1) {
+            status.set(format!("Could not keep the WebDAV account: {error}"));
+        }
+    };
+
+    cx.render(rsx!(
+        div {
+            class: PANEL_STYLE,
+            p {
+                class: "col-span-4",
+                "WebDAV Sync"
+            },
+            AccountInput {
+                label: "URL of the data file",
+                kind: "url",
+                value: account.url.clone(),
+                on_change: move |url: String| change(&|account| account.url = url.trim().to_string())
+            },
+            AccountInput {
+                label: "Username",
+                kind: "text",
+                value: account.username.clone(),
+                on_change: move |username: String| change(&|account| account.username = username.clone())
+            },
+            AccountInput {
+                label: "Password",
+                kind: "password",
+                value: account.password.clone(),
+                on_change: move |password: String| change(&|account| account.password = password.clone())
+            },
+            button {
+                class: "col-span-2 {BUTTON_STYLE}",
+                onclick: move |_| {
+                    let setup = setup.clone();
+                    let status = status.clone();
+                    let log_data = log_data.clone();
+                    let user = user.clone();
+                    let WebDavSetup { account, state } = setup.read().1.clone();
+
+                    cx.spawn(async move {
+                        if account.url.is_empty() {
+                            status.set("Enter the URL of the data file first".to_string());
+                            return;
+                        }
+                        status.set(format!("Syncing with {}", account.url));
+
+                        let local = DataFile::new(log_data.read().clone(), user.read().clone());
+                        let synced = match webdav::sync(&account, &local, &state).await {
+                            Ok(synced) => synced,
+                            Err(error) => {
+                                status.set(format!("Could not sync: {error}"));
+                                return;
+                            }
+                        };
+
+                        let message = synced.describe(&account.url);
+                        let setup_now = WebDavSetup {
+                            account,
+                            state: synced.state,
+                        };
+                        if synced.pulled {
+                            *log_data.write() = synced.data.log;
+                            *user.write() = synced.data.user;
+                        }
+                        match storage::save_webdav(profile, &setup_now) {
+                            Ok(()) => status.set(message),
+                            Err(error) => status.set(format!("{message}, but could not keep the sync state: {error}")),
+                        }
+                        setup.write().1 = setup_now;
+                    });
+                },
+                "Sync now"
+            },
+            p {
+                class: "col-span-4 text-xs",
+                "{status}"
+            }
+        }
+    ))
+}
+
+/// Preferences, plus importing and exporting the app's data.
+pub fn SettingsPage(cx: Scope) -> Element {
+    cx.render(rsx!(
+        PreferencesSection {},
+        SkillTargetSection {},
+        LadderSection {},
+        WebDavSection {},
+        import::DataFileSection {},
+        import::CsvImport {},
+        import::AnkiImport {},
+        import::LlDashboardImport {},
+        import::TimeTrackerImport {},
+        import::TimeclockSection {}
+    ))
+}
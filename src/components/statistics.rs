@@ -0,0 +1,102 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use chrono::NaiveDate;
+
+use nazad_core::log::LogData;
+use nazad_core::settings::Settings;
+use nazad_core::stats::{self, Summary};
+use nazad_core::user::UserProfile;
+
+use crate::components::balance::BalanceSection;
+use crate::components::settings::today;
+use crate::components::style::{BUTTON_STYLE, SELECTED_BUTTON_STYLE, PANEL_STYLE};
+
+const PERIODS: [&str; 4] = ["Week", "Month", "Year", "All"];
+
+fn period_of(period: &str, log: &LogData, settings: &Settings, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match period {
+        "Week" => settings.week_of(today),
+        "Month" => stats::month_of(today),
+        "Year" => stats::year_of(today),
+        _ => {
+            let first = log
+                .entries()
+                .iter()
+                .filter_map(|entry| entry.date().to_naive())
+                .min()
+                .unwrap_or(today);
+            (first, today)
+        }
+    }
+}
+
+#[inline_props]
+fn Row<'a>(cx: Scope, label: &'a str, value: String) -> Element<'a> {
+    cx.render(rsx!(
+        span {
+            class: "col-span-2",
+            "{label}"
+        },
+        span {
+            class: "col-span-2",
+            "{value}"
+        }
+    ))
+}
+
+/// Totals for the current week, month, year or the whole log.
+pub fn StatisticsPage(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let period = use_state(cx, || "Week");
+
+    let user = user.read();
+    let settings = user.settings();
+    let (from, to) = period_of(period.get(), &log_data.read(), settings, today(settings));
+    let summary = Summary::of(&log_data.read(), from, to);
+    let from = settings.format_date(summary.from);
+    let to = settings.format_date(summary.to);
+    let goals = user.goals().iter().filter(|goal| !goal.title().is_empty());
+    let completed = goals.clone().filter(|goal| goal.completed()).count();
+    let goal_count = goals.count();
+
+    cx.render(rsx!(
+        div {
+            class: "flex flex-row gap-2 mx-4 mt-4",
+            PERIODS.iter().map(|name| {
+                let style = if name == period.get() { SELECTED_BUTTON_STYLE } else { BUTTON_STYLE };
+                rsx!(
+                    button {
+                        key: "{name}",
+                        class: style,
+                        onclick: move |_| period.set(name),
+                        "{name}"
+                    }
+                )
+            })
+        },
+        div {
+            class: PANEL_STYLE,
+            span {
+                class: "col-span-4 font-bold",
+                "{from} to {to}"
+            },
+            Row { label: "Total", value: settings.format_duration(summary.total_minutes) },
+            Row { label: "Daily average", value: settings.format_duration(summary.daily_average()) },
+            Row { label: "Active days", value: format!("{} of {}", summary.active_days, summary.days()) },
+            Row { label: "Entries", value: summary.entries.to_string() },
+            summary.per_type.iter().filter(|(_, minutes)| *minutes > 0).map(|(entry_type, minutes)| rsx!(
+                Row {
+                    key: "{entry_type.name()}",
+                    label: entry_type.name(),
+                    value: settings.format_duration(*minutes)
+                }
+            )),
+            Row { label: "Goals done", value: format!("{completed} of {goal_count}") }
+        },
+        BalanceSection {
+            summary: summary.clone()
+        }
+    ))
+}
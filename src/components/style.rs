@@ -0,0 +1,7 @@
+//! Tailwind classes shared by the components.
+
+pub const BUTTON_STYLE: &str = "bg-blue-500 hover:bg-blue-400 text-white font-bold py-2 px-4 border-b-4 border-blue-700 hover:border-blue-500 rounded";
+/// A button that shows the option currently picked.
+pub const SELECTED_BUTTON_STYLE: &str = "bg-blue-700 text-white font-bold py-2 px-4 border-b-4 border-blue-900 rounded";
+/// The boxes pages and settings sections are laid out in.
+pub const PANEL_STYLE: &str = "grid grid-cols-4 gap-2 bg-zinc-600 m-4 p-2 rounded-md border-8 border-zinc-600 text-gray-200";
@@ -1,162 +1,18 @@
 #![allow(non_snake_case)]
-#![allow(dead_code)]
 // import the prelude to get access to the `rsx!` macro and the `Scope` and `Element` types
 use dioxus::prelude::*;
 
-use serde::{Serialize, Deserialize};
-use std::{
-    fs,
-};
+use nazad_core::avatar;
+use nazad_core::log::LogData;
+use nazad_core::stats::{RollingAverage, AVERAGE_WINDOWS};
+use nazad_core::user::{User, UserProfile};
 
-use crate::components::icons::{
-    ListBulletIcon,
-    TrashIcon,
-    PencilIcon
-};
-
-#[derive(PartialEq, Clone)]
-struct Date {
-    day: usize,
-    month: usize,
-    year: usize,
-}
-
-#[derive(PartialEq, Clone)]
-struct Goal {
-    id: usize,
-    completed: bool,
-    title: String,
-    date: Date,
-}
-
-#[derive(Serialize, Deserialize, PartialEq, Clone, Props)]
-struct UserDiagnostics {
-    total_study_time: usize,
-    daily_average: usize,
-    vocab_size: usize,
-    chapters_read: usize,
-    books_read: usize,
-    videos_watched: usize,
-    goals_completed: usize,
-}
-
-#[derive(PartialEq, Clone, Props)]
-struct User {
-    picture_path: String,
-    name: String,
-}
-
-#[derive(PartialEq, Clone)]
-pub struct UserProfile {
-    profile: User,
-    diagnostics: UserDiagnostics,
-    goals: Vec<Goal>,
-    visible_at_startup: bool,
-}
+use crate::components::files::{self, PickedFile};
+use crate::components::{local, settings};
+use crate::components::icons::{ListBulletIcon, PencilIcon};
+use crate::components::style::BUTTON_STYLE;
 
 struct Visible(bool);
-struct Edit(bool);
-
-const BUTTON_STYLE: &str = "bg-blue-500 hover:bg-blue-400 text-white font-bold py-2 px-4 border-b-4 border-blue-700 hover:border-blue-500 rounded";
-const NUMBER_INPUT_STYLE: &str = "peer w-20 rounded-[7px] border border-blue-gray-200 border-t-transparent bg-transparent px-3 py-2.5 font-sans text-sm font-normal text-blue-gray-700 outline outline-0 transition-all placeholder-shown:border placeholder-shown:border-blue-gray-200 placeholder-shown:border-t-blue-gray-200 focus:border-2 focus:border-pink-500 focus:border-t-transparent focus:outline-0 disabled:border-0 disabled:bg-blue-gray-50";
-const NUMBER_LABEL_STYLE: &str = "before:content[' '] after:content[' '] pointer-events-none absolute left-0 -top-1.5 flex w-20 select-none text-[11px] font-normal leading-tight text-blue-gray-400 transition-all before:pointer-events-none before:mt-[6.5px] before:mr-1 before:box-border before:block before:h-1.5 before:w-2.5 before:rounded-tl-md before:border-t before:border-l before:border-blue-gray-200 before:transition-all after:pointer-events-none after:mt-[6.5px] after:ml-1 after:box-border after:block after:h-1.5 after:w-2.5 after:flex-grow after:rounded-tr-md after:border-t after:border-r after:border-blue-gray-200 after:transition-all peer-placeholder-shown:text-sm peer-placeholder-shown:leading-[3.75] peer-placeholder-shown:text-blue-gray-500 peer-placeholder-shown:before:border-transparent peer-placeholder-shown:after:border-transparent peer-focus:text-[11px] peer-focus:leading-tight peer-focus:text-pink-500 peer-focus:before:border-t-2 peer-focus:before:border-l-2 peer-focus:before:border-pink-500 peer-focus:after:border-t-2 peer-focus:after:border-r-2 peer-focus:after:border-pink-500 peer-disabled:text-transparent peer-disabled:before:border-transparent peer-disabled:after:border-transparent peer-disabled:peer-placeholder-shown:text-blue-gray-500";
-
-impl UserProfile {
-    pub fn new() -> Self {
-        Self {
-            profile: User{
-                picture_path: String::new(),
-                name: String::new(),
-            },
-            goals: vec![Goal {
-                id: 0,
-                completed: false,
-                title: String::new(),
-                date: Date {
-                    day: 0,
-                    month: 0,
-                    year: 0,
-                },
-            }],
-            visible_at_startup: true,
-            diagnostics: UserDiagnostics {
-                total_study_time: 0,
-                daily_average: 0,
-                vocab_size: 0,
-                chapters_read: 0,
-                books_read: 0,
-                videos_watched: 0,
-                goals_completed: 0,
-            },
-        }
-    }
-
-    pub fn with_data(&mut self, picture_path: &str, user_name: &str) {
-        self.profile.picture_path = picture_path.to_string();
-        self.profile.name = user_name.to_string();
-    }
-
-    pub fn test(&mut self) {
-        self.diagnostics.goals_completed += 1;
-
-        self.profile.name = self.goals.iter().fold(String::from(""), |acc: String, goal| acc + goal.title.as_str());
-        self.diagnostics.goals_completed = self.goals.iter().fold(0, |acc, goal| {if goal.completed { acc + 1} else {acc}});
-    }
-
-    pub fn generate_diagnostic_data(&mut self, data_path: &str) {
-        let raw_diagnostic_data = UserProfile::get_diagnostics(data_path).unwrap();
-        let diagnostics = UserProfile::deserialize_diagnostics(&raw_diagnostic_data).unwrap();
-
-        self.diagnostics = UserProfile::process_diagnostics(diagnostics);
-    }
-
-    fn get_diagnostics(data_path: &str) -> std::io::Result<String> {
-        fs::read_to_string(data_path)
-    }
-
-    fn deserialize_diagnostics(diagnostic_data: &String) -> serde_json::Result<UserDiagnostics> {
-        serde_json::from_str(diagnostic_data)
-    }
-
-    fn process_diagnostics(diagnostics: UserDiagnostics) -> UserDiagnostics {
-        diagnostics
-    }
-
-    fn mark_goal_as_completed(&mut self, target_goal: &Goal) {
-        let pos = self.goals.iter().position(|goal| goal.id == target_goal.id).unwrap();
-        self.goals[pos].completed  = !self.goals[pos].completed;
-    }
-
-    fn remove_goal(&mut self, target_goal: &Goal) {
-        let pos = self.goals.iter().position(|goal| goal.id == target_goal.id).unwrap();
-        self.goals.remove(pos);
-    }
-
-    fn update_goal(&mut self, target_goal: &Goal, new_goal: Goal) {
-        let pos = self.goals.iter().position(|goal| goal.id == target_goal.id).unwrap();
-        self.goals[pos] = new_goal;
-    }
-
-    fn add_goal(&mut self) {
-        let id = match self.goals.last() {
-            Some(goal) => goal.id + 1,
-            _ => 0
-        };
-
-        let goal = Goal{
-            id,
-            completed: false,
-            title: "".to_string(),
-            date: Date {
-                day: 0,
-                month: 0,
-                year: 0,
-            },
-        };
-
-        self.goals.push(goal);
-    }
-}
 
 fn HideUserButton(cx: Scope) -> Element {
     let visible = use_shared_state::<Visible>(cx).unwrap();
@@ -173,118 +29,227 @@ fn HideUserButton(cx: Scope) -> Element {
     ))
 }
 
-fn ProfileSection(cx:Scope) -> Element {
-    let user = use_shared_state::<UserProfile>(cx).unwrap();
-    let profile = user.read().clone().profile;
-
-    cx.render(rsx!(
-        div {
+#[inline_props]
+fn Avatar(cx: Scope, profile: User) -> Element<'a> {
+    if !profile.picture_path.is_empty() {
+        return cx.render(rsx!(
             img {
+                class: "w-16 h-16 rounded-full",
                 src: "{profile.picture_path}",
-                alt: "Profile Picture"
-            },
-            p {
-                profile.name
+                alt: "Profile picture of {profile.name}"
             }
-        }
-    ))
-}
+        ));
+    }
 
-#[inline_props]
-fn DataParagraph<'a>(cx: Scope, head: &'a str, data: String) -> Element {
+    let initials = avatar::initials(&profile.name);
+    let color = avatar::initials_color(&profile.name);
     cx.render(rsx!(
         div {
-            p {
-                class: "text-xs text-gray-500",
-                "{head}"
-            },
-            p {
-                "{data}"
-            }
+            class: "w-16 h-16 rounded-full flex items-center justify-center text-2xl font-bold text-white",
+            style: "background-color: {color}",
+            "{initials}"
         }
     ))
 }
 
-fn DiagnosticsSection(cx:Scope) -> Element {
+#[inline_props]
+fn ProfileEditor<'a>(cx: Scope, profile: User, on_done: EventHandler<'a, ()>) -> Element<'a> {
     let user = use_shared_state::<UserProfile>(cx).unwrap();
-    let diagnostics = user.read().clone().diagnostics;
+    let edited = use_ref(cx, || profile.clone());
+    let status = use_state(cx, String::new);
+    let current = edited.read().clone();
+    let targets = current.target_languages.join(", ");
 
     cx.render(rsx!(
         div {
-            class: "grid grid-rows-4 grid-flow-col gap-4",
-            DataParagraph{
-                head: "Total Study Time",
-                data: diagnostics.total_study_time.to_string()
+            class: "grid gap-2",
+            Avatar {
+                profile: current.clone()
             },
-            DataParagraph{
-                head: "Daily Average",
-                data: diagnostics.daily_average.to_string()
+            files::OpenFile {
+                class: "",
+                accept: ".png,.jpg,.jpeg,.gif,.webp,image/*",
+                on_open: move |file: PickedFile| {
+                    let edited = edited.clone();
+                    let status = status.clone();
+                    cx.spawn(async move {
+                        let Some(bytes) = file.read().await else {
+                            status.set(format!("Could not read {}", file.name()));
+                            return;
+                        };
+                        match avatar::avatar_data_url(&bytes) {
+                            Ok(url) => {
+                                edited.write().picture_path = url;
+                                status.set(String::new());
+                            }
+                            Err(error) => status.set(format!("{}: {error}", file.name())),
+                        }
+                    });
+                }
             },
-            DataParagraph{
-                head: "Vocab Size",
-                data: diagnostics.vocab_size.to_string()
+            if !current.picture_path.is_empty() {
+                rsx!(
+                    button {
+                        class: "text-xs underline text-left",
+                        onclick: move |_| edited.write().picture_path.clear(),
+                        "Remove picture"
+                    }
+                )
             },
-            DataParagraph{
-                head: "Chapters Read",
-                data: diagnostics.chapters_read.to_string()
+            p {
+                class: "text-xs text-red-500",
+                "{status}"
             },
-            DataParagraph{
-                head: "Books Read",
-                data: diagnostics.books_read.to_string()
+            label {
+                class: "text-xs text-gray-500",
+                "Name"
             },
-            DataParagraph{
-                head: "Videos Watched",
-                data: diagnostics.videos_watched.to_string()
+            input {
+                class: "text-zinc-900",
+                r#type: "text",
+                value: "{current.name}",
+                oninput: move |evt| edited.write().name = evt.value.clone()
             },
-            DataParagraph{
-                head: "Goals Completed",
-                data: diagnostics.goals_completed.to_string()
+            label {
+                class: "text-xs text-gray-500",
+                "Native language"
+            },
+            input {
+                class: "text-zinc-900",
+                r#type: "text",
+                value: "{current.native_language}",
+                oninput: move |evt| edited.write().native_language = evt.value.clone()
+            },
+            label {
+                class: "text-xs text-gray-500",
+                "Learning, separated by commas"
+            },
+            input {
+                class: "text-zinc-900",
+                r#type: "text",
+                value: "{targets}",
+                oninput: move |evt| {
+                    edited.write().target_languages = evt
+                        .value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|language| !language.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+            },
+            div {
+                class: "flex flex-row gap-2",
+                button {
+                    class: BUTTON_STYLE,
+                    onclick: move |_| {
+                        user.write().set_profile(edited.read().clone());
+                        on_done.call(());
+                    },
+                    "Save"
+                },
+                button {
+                    class: BUTTON_STYLE,
+                    onclick: move |_| on_done.call(()),
+                    "Cancel"
+                }
             }
         }
     ))
 }
 
-fn ReadDataSection(cx: Scope) -> Element {
+fn ProfileSection(cx:Scope) -> Element {
     let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let profile = user.read().profile().clone();
+    let editing = use_state(cx, || false);
+
+    if **editing {
+        return cx.render(rsx!(
+            ProfileEditor {
+                profile: profile,
+                on_done: move |_| editing.set(false)
+            }
+        ));
+    }
+
+    let languages = match (profile.native_language.is_empty(), profile.target_languages.is_empty()) {
+        (_, true) => profile.native_language.clone(),
+        (true, false) => format!("Learning {}", profile.target_languages.join(", ")),
+        (false, false) => format!("{}, learning {}", profile.native_language, profile.target_languages.join(", ")),
+    };
 
     cx.render(rsx!(
         div {
-            class: "grid grid-rows 2 grid-flow-col gap-4 h-8",
-            p {
-                "Log Data from File"
+            class: "flex flex-row gap-2 items-center",
+            Avatar {
+                profile: profile.clone()
             },
-            button {
-                class: BUTTON_STYLE,
-                onclick: move |_| {
-                   user.write().test()
+            div {
+                class: "grow",
+                p {
+                    "{profile.name}"
                 },
-                "Read Data"
+                p {
+                    class: "text-xs text-gray-500",
+                    "{languages}"
+                }
+            },
+            button {
+                class: "w-6 h-6",
+                onclick: move |_| editing.set(true),
+                PencilIcon {}
             }
-        }
+        },
+        LevelSection {}
     ))
 }
 
-#[inline_props]
-fn NumberInput<'a>(cx: Scope, value: String, kind: &'a str, on_input: EventHandler<'a, FormEvent>) -> Element {
+/// The level reached on the ladder picked in the settings and the way to
+/// the next one.
+fn LevelSection(cx: Scope) -> Element {
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let ladder = settings.current_ladder();
+    let learned = user.read().profile().target_languages.clone();
+    let language = settings.ladder_language_of(&learned);
+    let level = ladder.progress(&log_data.read(), language);
+    let title = match language {
+        Some(language) => format!("{}, {language}", ladder.name),
+        None => ladder.name.clone(),
+    };
+
+    let current = level.current.as_ref().map(|level| level.name.clone()).unwrap_or("No level yet".to_string());
+    let description = level.current.as_ref().map(|level| level.description.clone()).unwrap_or_default();
+    let percent = (level.progress * 100.0).round();
+    let next = match &level.next {
+        Some(next) => format!("{} to {}", settings.format_duration(next.minutes() - level.minutes), next.name),
+        None => "Top of the ladder".to_string(),
+    };
+
     cx.render(rsx!(
         div {
-            class: "w-20",
+            class: "grid gap-1",
+            title: "{description}",
+            p {
+                class: "text-xs text-gray-500",
+                "{title}"
+            },
             div {
-                class: "relative h-10 w-full",
-                input {
-                    class: NUMBER_INPUT_STYLE,
-                    placeholder: " ",
-                    r#type: "number",
-                    min: "0",
-                    max: "10000",
-                    value: "{value}",
-                    oninput: move |evt| {
-                        on_input.call(evt)
-                    }
+                class: "flex flex-row gap-2 items-baseline",
+                span {
+                    "{current}"
                 },
-                label {
-                    class: NUMBER_LABEL_STYLE,
-                    "{kind}"
+                span {
+                    class: "text-xs text-gray-500",
+                    "{next}"
+                }
+            },
+            div {
+                class: "h-1 rounded bg-zinc-700",
+                div {
+                    class: "h-1 rounded bg-blue-500",
+                    style: "width: {percent}%"
                 }
             }
         }
@@ -292,202 +257,129 @@ fn NumberInput<'a>(cx: Scope, value: String, kind: &'a str, on_input: EventHandl
 }
 
 #[inline_props]
-fn StringInput<'a>(cx: Scope, value: String, on_input: EventHandler<'a, FormEvent>) -> Element {
+fn DataParagraph<'a>(cx: Scope, head: &'a str, data: String) -> Element<'a> {
     cx.render(rsx!(
         div {
-            class: "w-20",
-            div {
-                class: "relative h-10 w-full",
-                input {
-                    class: NUMBER_INPUT_STYLE,
-                    placeholder: " ",
-                    r#type: "text",
-                    value: "{value}",
-                    oninput: move |evt| {
-                        on_input.call(evt)
-                    }
-                },
-                label {
-                    class: NUMBER_LABEL_STYLE,
-                    "{value}"
-                }
+            p {
+                class: "text-xs text-gray-500",
+                "{head}"
+            },
+            p {
+                "{data}"
             }
         }
     ))
 }
 
-#[inline_props]
-fn GoalParagraph(cx: Scope, goal: Goal) -> Element {
+/// Rolling averages over a window picked from `stats::AVERAGE_WINDOWS`,
+/// compared with the window before it.
+fn AverageSection(cx: Scope) -> Element {
     let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let window = settings.average_window;
+
+    let average = RollingAverage::of(&log_data.read(), settings::today(&settings), window);
+    let per_day = settings.format_duration(average.current.daily_average());
+    let per_active_day = settings.format_duration(average.current.active_day_average());
+    let (trend, trend_color) = match average.trend() {
+        Some(change) if change >= 0.0 => (format!("▲ {:.0}% on the {window} days before", change * 100.0), "text-green-500"),
+        Some(change) => (format!("▼ {:.0}% on the {window} days before", -change * 100.0), "text-red-500"),
+        None => (format!("Nothing logged in the {window} days before"), "text-gray-500"),
+    };
 
-    let edit = use_state(cx, || Edit(false));
-
-    if !edit.0 {
-    return cx.render(rsx!(
-       div {
-            class: "flex flex-row gap-2 group-checked:opacity-0",
-            if goal.completed {
-                rsx!(
-            input {
-                id: "{goal.id}",
-                class: "checked:text-blue-600 transition-colors will-change-auto",
-                r#type: "checkbox",
-                checked: "",
-                name: "{goal.id}",
-                onchange: move |_| {
-                    user.write().mark_goal_as_completed(goal);
-                }
-            })
-            } else {
-                rsx!(
-                     input {
-                id: "{goal.id}",
-                class: "checked:text-blue-600 transition-colors will-change-auto",
-                r#type: "checkbox",
-                name: "{goal.id}",
-                onchange: move |_| {
-                    user.write().mark_goal_as_completed(goal);
-                }
-            })
-            },
-            label {
-                    class: "self-center",
-                    r#for: "{goal.id}",
-                div {
-                    class: "flex flex-row gap-2",
-                    span {
-                    "{goal.title}"
-                },
-                span {
-                    "{goal.date.day}"
-                },
-                span {
-                    "{goal.date.month}"
-                },
-                span {
-                    "{goal.date.year}"
-                }
-                },
-                },
+    cx.render(rsx!(
+        div {
+            class: "grid gap-1",
             div {
-                class: "flex items-center",
-            button {
-                class: "w-6 h-6",
-                onclick: move |_| {
-                    edit.set(Edit(true));
-                },
-                PencilIcon {}
-            }
+                class: "flex flex-row gap-1",
+                AVERAGE_WINDOWS.iter().map(|days| {
+                    let days = *days;
+                    let style = if days == window { "bg-blue-700 text-white" } else { "bg-zinc-600 text-gray-200 hover:bg-zinc-500" };
+                    rsx!(
+                        button {
+                            key: "{days}",
+                            class: "px-2 rounded text-xs {style}",
+                            onclick: move |_| {
+                                let mut settings = user.read().settings().clone();
+                                settings.average_window = days;
+                                user.write().set_settings(settings);
+                            },
+                            "{days}d"
+                        }
+                    )
+                })
             },
             div {
-                class: "flex items-center",
-            button {
-                class: "w-6 h-6" ,
-                onclick: move |_| {
-                    user.write().remove_goal(goal);
+                class: "grid grid-cols-2 gap-4",
+                DataParagraph {
+                    head: "Daily Average",
+                    data: per_day
                 },
-                TrashIcon {},
-            },
-            }
-            }
-    ));
-    }
-
-    let pos = user.read().goals.iter().position(|in_goal| in_goal.id == goal.id).unwrap();
-
-    cx.render(rsx!(
-         div {
-            class: "flex flex-row gap-2 group-checked:opacity-0",
-            input {
-                id: "{goal.id}",
-                class: "checked:opacity-0 transition-opacity will-change-auto",
-                r#type: "checkbox",
-                disabled: "disabled",
-                name: "{goal.id}",
-            },
-            label {
-                    class: "self-center",
-                    r#for: "{goal.id}",
+                DataParagraph {
+                    head: "Per Active Day",
+                    data: per_active_day
+                }
             },
-                StringInput {
-                value: user.read().goals[pos].title.clone(),
-                    on_input: move |event: FormEvent| {
-                    user.write().goals[pos].title = event.value.clone();
-                    }
-                },
-                 NumberInput {
-                value: user.read().goals[pos].date.day.to_string(),
-                        kind: "Day",
-                        on_input:  move |event: FormEvent| {
-                    let val = match event.value.parse::<usize>() {
-                        Ok(x) => x,
-                        _ => 0
-                    };
-                    user.write().goals[pos].date.day = val;
-                        }
-                },
-                NumberInput {
-                value: user.read().goals[pos].date.month.to_string(),
-                        kind: "Month",
-                        on_input: move |event: FormEvent| {
-                     let val = match event.value.parse::<usize>() {
-                        Ok(x) => x,
-                        _ => 0
-                    };
-                    user.write().goals[pos].date.month = val;
-                        }
-                },
-                NumberInput {
-                value: user.read().goals[pos].date.year.to_string(),
-                        kind: "Year",
-                        on_input: move |event: FormEvent| {
-                     let val = match event.value.parse::<usize>() {
-                        Ok(x) => x,
-                        _ => 0
-                    };
-                    user.write().goals[pos].date.year = val;
-                        }
-                },
-              div {
-                class: "flex items-center",
-              button {
-                        class: "w-6 h-6",
-                        onclick: move |_| {
-                            edit.set(Edit(false));
-                        },
-                PencilIcon {}
-            }
+            p {
+                class: "text-xs {trend_color}",
+                "{trend}"
             }
         }
     ))
 }
 
-fn GoalsSection(cx: Scope) -> Element {
+fn DiagnosticsSection(cx:Scope) -> Element {
     let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let diagnostics = user.read().diagnostics().clone();
 
     cx.render(rsx!(
+        AverageSection {},
         div {
-            user.read().goals.iter().map(|goal| rsx!(
-                GoalParagraph {
-                    goal: goal.clone()
-                }
-            ))
+            class: "grid grid-rows-3 grid-flow-col gap-4",
+            DataParagraph{
+                head: "Total Study Time",
+                data: diagnostics.total_study_time.to_string()
+            },
+            DataParagraph{
+                head: "Vocab Size",
+                data: diagnostics.vocab_size.to_string()
+            },
+            DataParagraph{
+                head: "Chapters Read",
+                data: diagnostics.chapters_read.to_string()
+            },
+            DataParagraph{
+                head: "Books Read",
+                data: diagnostics.books_read.to_string()
+            },
+            DataParagraph{
+                head: "Videos Watched",
+                data: diagnostics.videos_watched.to_string()
+            },
+            DataParagraph{
+                head: "Goals Completed",
+                data: diagnostics.goals_completed.to_string()
+            }
         }
     ))
 }
 
-fn AddGoalSection(cx: Scope) -> Element {
+fn ReadDataSection(cx: Scope) -> Element {
     let user = use_shared_state::<UserProfile>(cx).unwrap();
 
     cx.render(rsx!(
         div {
-            class: "flex w-full h-full items-end",
+            class: "grid grid-rows 2 grid-flow-col gap-4 h-8",
+            p {
+                "Log Data from File"
+            },
             button {
-                class: "w-full text-white font-bold bg-green-400 py-2 px-4 border-b-4 border-green-700 rounded hover:border-green-500 hover:bg-green-300",
+                class: BUTTON_STYLE,
                 onclick: move |_| {
-                    user.write().add_goal()
+                   user.write().test()
                 },
-                "+ Add Goal"
+                "Read Data"
             }
         }
     ))
@@ -497,23 +389,30 @@ fn AddGoalSection(cx: Scope) -> Element {
 pub fn UserBox(cx:Scope) -> Element {
     let user = use_shared_state::<UserProfile>(cx).unwrap();
 
-    use_shared_state_provider(cx, || Visible(user.read().visible_at_startup));
+    let at_startup = user.read().settings().visible_at_startup;
+    use_shared_state_provider(cx, || Visible(at_startup));
     let visible = use_shared_state::<Visible>(cx).unwrap();
 
+    // The stored settings may arrive after the first render, and can be changed in the settings page
+    use_effect(cx, (&at_startup,), |(at_startup,)| {
+        let visible = visible.clone();
+        async move {
+            visible.write().0 = at_startup;
+        }
+    });
+
     if visible.read().0 {
-        return cx.render(rsx!(
+        cx.render(rsx!(
                 div {
                     class: "grid gap-2",
                     HideUserButton {},
+                    local::ProfileSwitcher {},
                     ProfileSection {},
                     DiagnosticsSection {},
                     hr {},
-                    ReadDataSection {},
-                    hr {},
-                    GoalsSection {},
-                    AddGoalSection {}
+                    ReadDataSection {}
                 }
-            ));
+            ))
     } else {
         cx.render(rsx!(
             div {
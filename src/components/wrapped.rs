@@ -0,0 +1,72 @@
+#![allow(non_snake_case)]
+
+use chrono::Datelike;
+use dioxus::prelude::*;
+
+use nazad_core::log::LogData;
+use nazad_core::user::UserProfile;
+use nazad_core::wrapped::{self, YearInReview};
+
+use crate::components::files;
+use crate::components::settings::today;
+use crate::components::style::{BUTTON_STYLE, SELECTED_BUTTON_STYLE, PANEL_STYLE};
+
+/// A year summed up in one image, to keep or share as an SVG image or an
+/// HTML page. Both are generated in the app, nothing is uploaded.
+pub fn YearPage(cx: Scope) -> Element {
+    let log_data = use_shared_state::<LogData>(cx).unwrap();
+    let user = use_shared_state::<UserProfile>(cx).unwrap();
+    let settings = user.read().settings().clone();
+    let today = today(&settings);
+    let year = use_state(cx, || today.year());
+
+    let years = wrapped::years(&log_data.read(), today);
+    let review = YearInReview::of(&log_data.read(), &user.read(), *year.get());
+    let svg = review.to_svg(&settings);
+    let html = review.to_html(&settings);
+    let svg_name = cx.bump().alloc_str(&format!("nazad-{}.svg", year.get()));
+    let html_name = cx.bump().alloc_str(&format!("nazad-{}.html", year.get()));
+
+    cx.render(rsx!(
+        div {
+            class: "flex flex-row flex-wrap gap-2 mx-4 mt-4",
+            years.into_iter().map(|option| {
+                let style = if option == *year.get() { SELECTED_BUTTON_STYLE } else { BUTTON_STYLE };
+                rsx!(
+                    button {
+                        key: "{option}",
+                        class: style,
+                        onclick: move |_| year.set(option),
+                        "{option}"
+                    }
+                )
+            })
+        },
+        div {
+            class: PANEL_STYLE,
+            span {
+                class: "col-span-1 self-center",
+                "Image (SVG)"
+            },
+            files::SaveFile {
+                class: "col-span-1",
+                file_name: svg_name,
+                contents: svg.clone()
+            },
+            span {
+                class: "col-span-1 self-center",
+                "Web page (HTML)"
+            },
+            files::SaveFile {
+                class: "col-span-1",
+                file_name: html_name,
+                contents: html
+            },
+            // Generated from escaped log data, the same markup as the export
+            div {
+                class: "col-span-4 flex justify-center",
+                dangerous_inner_html: "{svg}"
+            }
+        }
+    ))
+}
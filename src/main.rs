@@ -1,33 +1,103 @@
 #![allow(non_snake_case)]
 
+#[cfg(all(feature = "web", feature = "desktop"))]
+compile_error!("the web and desktop features can't be enabled together, build the desktop app with --no-default-features");
+
+#[cfg(feature = "web")]
+mod backend;
 mod components;
+mod routes;
+mod storage;
+mod webdav;
 
 use components::{
     actions,
     user,
     dashboard,
-    log
+    history,
+    goals,
+    library,
+    statistics,
+    review,
+    levels,
+    wrapped,
+    settings,
+    local,
 };
+use nazad_core::{log::LogData, user::UserProfile};
 
 use dioxus::prelude::*;
+use dioxus_router::{Redirect, Route, Router};
 
+#[cfg(feature = "web")]
 fn main() {
     dioxus_web::launch(
         App,
     );
 }
 
+// Trunk bundles the stylesheets for the browser, the desktop window gets them
+// inlined. build.rs leaves the generated Tailwind stylesheet in OUT_DIR.
+#[cfg(feature = "desktop")]
+fn main() {
+    let styles = format!(
+        "<style>{}</style><style>{}</style>",
+        include_str!(concat!(env!("OUT_DIR"), "/tailwind.css")),
+        include_str!("../styles/input.css")
+    );
+
+    dioxus_desktop::launch_cfg(
+        App,
+        dioxus_desktop::Config::new()
+            .with_window(dioxus_desktop::WindowBuilder::new().with_title("Nazad"))
+            .with_custom_head(styles),
+    );
+}
+
 fn App(cx: Scope) -> Element {
-    use_shared_state_provider(cx, || user::UserProfile::new());
-    use_shared_state_provider(cx, || actions::SelectedCategory::new());
-    use_shared_state_provider(cx, || log::LogData::new());
-
-    cx.render(rsx! (
-        div {
-            class: "grid grid-cols-6 h-[100vh]",
-            actions::QuickActions {},
-            dashboard::View {},
-            user::UserBox {}
+    use_shared_state_provider(cx, UserProfile::new);
+    use_shared_state_provider(cx, actions::SelectedCategory::new);
+    use_shared_state_provider(cx, LogData::new);
+
+    let dashboard = rsx!(
+        settings::Themed {
+            Router {
+                div {
+                    class: "grid grid-cols-6 h-[100vh]",
+                    actions::QuickActions {},
+                    div {
+                        class: "col-span-4",
+                        dashboard::Header {},
+                        Route { to: routes::DASHBOARD, dashboard::View {} },
+                        Route { to: routes::HISTORY, history::HistoryPage {} },
+                        Route { to: routes::GOALS, goals::GoalsPage {} },
+                        Route { to: routes::LIBRARY, library::LibraryPage {} },
+                        Route { to: routes::STATISTICS, statistics::StatisticsPage {} },
+                        Route { to: routes::REVIEW, review::ReviewPage {} },
+                        Route { to: routes::LEVELS, levels::LevelsPage {} },
+                        Route { to: routes::YEAR, wrapped::YearPage {} },
+                        Route { to: routes::SETTINGS, settings::SettingsPage {} },
+                        Redirect { from: "", to: routes::DASHBOARD }
+                    },
+                    user::UserBox {}
+                }
+            }
+        }
+    );
+
+    #[cfg(feature = "web")]
+    if let backend::Backend::Server(client) = cx.use_hook(backend::Backend::from_location) {
+        return cx.render(rsx!(
+            components::server::ServerMode {
+                client: client.clone(),
+                dashboard
+            }
+        ));
+    }
+
+    cx.render(rsx!(
+        local::LocalMode {
+            dashboard
         }
     ))
 }
@@ -0,0 +1,25 @@
+//! The pages of the app. Each has its own URL, so they can be bookmarked
+//! and opened directly.
+
+pub const DASHBOARD: &str = "/";
+pub const HISTORY: &str = "/history";
+pub const GOALS: &str = "/goals";
+pub const LIBRARY: &str = "/library";
+pub const STATISTICS: &str = "/statistics";
+pub const REVIEW: &str = "/review";
+pub const LEVELS: &str = "/levels";
+pub const YEAR: &str = "/year";
+pub const SETTINGS: &str = "/settings";
+
+/// Paths and titles, in the order they are listed in the navigation.
+pub const PAGES: [(&str, &str); 9] = [
+    (DASHBOARD, "Dashboard"),
+    (HISTORY, "History"),
+    (GOALS, "Goals"),
+    (LIBRARY, "Library"),
+    (STATISTICS, "Statistics"),
+    (REVIEW, "Review"),
+    (LEVELS, "Levels"),
+    (YEAR, "Year"),
+    (SETTINGS, "Settings"),
+];
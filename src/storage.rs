@@ -0,0 +1,282 @@
+//! Keeps the data file of local mode on the device: in the browser's local
+//! storage on the web, and on the desktop in the platform data directory,
+//! the same `nazad.json` the command line client writes
+//! (e.g. `~/.local/share/nazad/nazad.json`). Every local profile has its own
+//! data file, the list of profiles is kept next to them, and so is the
+//! WebDAV account a profile syncs through.
+//!
+//! The data may change behind the app's back, by `nazad log` writing the
+//! same file or another tab saving to the same storage. Saving notices that
+//! and merges the two instead of overwriting what was added elsewhere.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use nazad_core::data::{DataFile, DataFileError};
+use nazad_core::profiles::ProfileList;
+
+use crate::webdav::WebDavSetup;
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// No local storage in this browser, or no data directory on this platform.
+    Unavailable,
+    #[cfg(feature = "desktop")]
+    Io(std::io::Error),
+    DataFile(DataFileError),
+    ProfileList(serde_json::Error),
+    WebDav(serde_json::Error),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Unavailable => write!(f, "there is no place to keep data on this device"),
+            #[cfg(feature = "desktop")]
+            StorageError::Io(error) => write!(f, "{error}"),
+            StorageError::DataFile(error) => write!(f, "{error}"),
+            StorageError::ProfileList(error) => write!(f, "invalid list of profiles: {error}"),
+            StorageError::WebDav(error) => write!(f, "invalid WebDAV settings: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<DataFileError> for StorageError {
+    fn from(error: DataFileError) -> Self {
+        StorageError::DataFile(error)
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl From<std::io::Error> for StorageError {
+    fn from(error: std::io::Error) -> Self {
+        StorageError::Io(error)
+    }
+}
+
+// Each profile's data as this app last read or wrote it
+static KNOWN: Mutex<BTreeMap<usize, String>> = Mutex::new(BTreeMap::new());
+
+fn known(profile: usize) -> Option<String> {
+    KNOWN.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(&profile).cloned()
+}
+
+fn remember(profile: usize, raw: Option<String>) {
+    let mut known = KNOWN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match raw {
+        Some(raw) => known.insert(profile, raw),
+        None => known.remove(&profile),
+    };
+}
+
+/// The saved data file of a profile, or `None` before anything was saved.
+pub fn load(profile: usize) -> Result<Option<DataFile>, StorageError> {
+    let Some(raw) = read_data(profile)? else {
+        return Ok(None);
+    };
+    let file = DataFile::from_json(&raw)?;
+    remember(profile, Some(raw));
+    Ok(Some(file))
+}
+
+/// Saves `file` as the profile's data. If the stored data changed since it
+/// was last loaded or saved here, both are merged like a sync would and the
+/// merged data, which is what got saved, is returned to be shown instead.
+pub fn save(profile: usize, file: &DataFile) -> Result<Option<DataFile>, StorageError> {
+    let known = known(profile);
+    let merged = match read_data(profile)? {
+        Some(stored) if Some(&stored) != known.as_ref() => {
+            let base = known.as_deref().map(DataFile::from_json).transpose()?;
+            Some(file.reconcile(base.as_ref(), &DataFile::from_json(&stored)?)).filter(|merged| merged != file)
+        }
+        _ => None,
+    };
+
+    let raw = merged.as_ref().unwrap_or(file).to_json();
+    write_data(profile, &raw)?;
+    remember(profile, Some(raw));
+    Ok(merged)
+}
+
+/// Whether the profile's data was changed by someone else since this app last read or wrote it.
+pub fn changed_elsewhere(profile: usize) -> bool {
+    matches!(read_data(profile), Ok(stored) if stored != known(profile))
+}
+
+pub fn remove(profile: usize) -> Result<(), StorageError> {
+    remove_data(profile)?;
+    remember(profile, None);
+    Ok(())
+}
+
+#[cfg(feature = "web")]
+const DATA_KEY: &str = "nazad-data";
+#[cfg(feature = "web")]
+const PROFILES_KEY: &str = "nazad-profiles";
+#[cfg(feature = "web")]
+const WEBDAV_KEY: &str = "nazad-webdav";
+
+// Profile 0 keeps the key it had before there were profiles
+#[cfg(feature = "web")]
+fn data_key(profile: usize) -> String {
+    match profile {
+        0 => DATA_KEY.to_string(),
+        profile => format!("{DATA_KEY}-{profile}"),
+    }
+}
+
+#[cfg(feature = "web")]
+fn webdav_key(profile: usize) -> String {
+    format!("{WEBDAV_KEY}-{profile}")
+}
+
+#[cfg(feature = "web")]
+fn local_storage() -> Result<web_sys::Storage, StorageError> {
+    crate::backend::local_storage().ok_or(StorageError::Unavailable)
+}
+
+/// Where the data is kept, shown to the user.
+#[cfg(feature = "web")]
+pub fn location(_profile: usize) -> String {
+    "this browser".to_string()
+}
+
+#[cfg(feature = "web")]
+fn read_data(profile: usize) -> Result<Option<String>, StorageError> {
+    Ok(local_storage()?.get_item(&data_key(profile)).ok().flatten())
+}
+
+#[cfg(feature = "web")]
+fn write_data(profile: usize, raw: &str) -> Result<(), StorageError> {
+    local_storage()?.set_item(&data_key(profile), raw).map_err(|_| StorageError::Unavailable)
+}
+
+#[cfg(feature = "web")]
+fn remove_data(profile: usize) -> Result<(), StorageError> {
+    let storage = local_storage()?;
+    storage.remove_item(&webdav_key(profile)).map_err(|_| StorageError::Unavailable)?;
+    storage.remove_item(&data_key(profile)).map_err(|_| StorageError::Unavailable)
+}
+
+#[cfg(feature = "web")]
+pub fn load_profiles() -> Result<ProfileList, StorageError> {
+    match local_storage()?.get_item(PROFILES_KEY).ok().flatten() {
+        Some(raw) => serde_json::from_str(&raw).map_err(StorageError::ProfileList),
+        None => Ok(ProfileList::default()),
+    }
+}
+
+#[cfg(feature = "web")]
+pub fn save_profiles(profiles: &ProfileList) -> Result<(), StorageError> {
+    let raw = serde_json::to_string(profiles).expect("profile lists serialize");
+    local_storage()?.set_item(PROFILES_KEY, &raw).map_err(|_| StorageError::Unavailable)
+}
+
+/// The WebDAV account of a profile, empty before one was set up.
+#[cfg(feature = "web")]
+pub fn load_webdav(profile: usize) -> Result<WebDavSetup, StorageError> {
+    match local_storage()?.get_item(&webdav_key(profile)).ok().flatten() {
+        Some(raw) => serde_json::from_str(&raw).map_err(StorageError::WebDav),
+        None => Ok(WebDavSetup::default()),
+    }
+}
+
+#[cfg(feature = "web")]
+pub fn save_webdav(profile: usize, setup: &WebDavSetup) -> Result<(), StorageError> {
+    let raw = serde_json::to_string(setup).expect("WebDAV settings serialize");
+    local_storage()?.set_item(&webdav_key(profile), &raw).map_err(|_| StorageError::Unavailable)
+}
+
+#[cfg(feature = "desktop")]
+fn data_directory() -> Result<std::path::PathBuf, StorageError> {
+    Ok(dirs::data_dir().ok_or(StorageError::Unavailable)?.join("nazad"))
+}
+
+// Profile 0 is the file the command line client uses as well
+#[cfg(feature = "desktop")]
+fn data_file(profile: usize) -> Result<std::path::PathBuf, StorageError> {
+    let name = match profile {
+        0 => "nazad.json".to_string(),
+        profile => format!("nazad-{profile}.json"),
+    };
+    Ok(data_directory()?.join(name))
+}
+
+#[cfg(feature = "desktop")]
+fn webdav_file(profile: usize) -> Result<std::path::PathBuf, StorageError> {
+    Ok(data_directory()?.join(format!("webdav-{profile}.json")))
+}
+
+// The command line client may read the file at any time, so it is never seen half written
+#[cfg(feature = "desktop")]
+fn write(path: &std::path::Path, contents: &str) -> Result<(), StorageError> {
+    nazad_core::data::write_atomically(path, contents)?;
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub fn location(profile: usize) -> String {
+    match data_file(profile) {
+        Ok(path) => path.display().to_string(),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[cfg(feature = "desktop")]
+fn read_data(profile: usize) -> Result<Option<String>, StorageError> {
+    let path = data_file(profile)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(std::fs::read_to_string(path)?))
+}
+
+#[cfg(feature = "desktop")]
+fn write_data(profile: usize, raw: &str) -> Result<(), StorageError> {
+    write(&data_file(profile)?, raw)
+}
+
+#[cfg(feature = "desktop")]
+fn remove_data(profile: usize) -> Result<(), StorageError> {
+    for path in [data_file(profile)?, webdav_file(profile)?] {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "desktop")]
+pub fn load_profiles() -> Result<ProfileList, StorageError> {
+    let path = data_directory()?.join("profiles.json");
+    if !path.exists() {
+        return Ok(ProfileList::default());
+    }
+    let raw = std::fs::read_to_string(path)?;
+    serde_json::from_str(&raw).map_err(StorageError::ProfileList)
+}
+
+#[cfg(feature = "desktop")]
+pub fn save_profiles(profiles: &ProfileList) -> Result<(), StorageError> {
+    let raw = serde_json::to_string(profiles).expect("profile lists serialize");
+    write(&data_directory()?.join("profiles.json"), &raw)
+}
+
+#[cfg(feature = "desktop")]
+pub fn load_webdav(profile: usize) -> Result<WebDavSetup, StorageError> {
+    let path = webdav_file(profile)?;
+    if !path.exists() {
+        return Ok(WebDavSetup::default());
+    }
+    let raw = std::fs::read_to_string(path)?;
+    serde_json::from_str(&raw).map_err(StorageError::WebDav)
+}
+
+#[cfg(feature = "desktop")]
+pub fn save_webdav(profile: usize, setup: &WebDavSetup) -> Result<(), StorageError> {
+    let raw = serde_json::to_string(setup).expect("WebDAV settings serialize");
+    write(&webdav_file(profile)?, &raw)
+}
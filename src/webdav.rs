@@ -0,0 +1,127 @@
+//! Syncs a local profile's data file through a WebDAV server, the same way
+//! `nazad sync` does, see `nazad_core::webdav`. The browser talks to the
+//! server with `fetch`, the desktop app with reqwest.
+
+use serde::{Deserialize, Serialize};
+
+use nazad_core::data::DataFile;
+use nazad_core::webdav::{self, Precondition, Response, SyncState, Synced, Transport};
+
+/// Where the data file is synced to, kept on the device and never synced itself.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct WebDavAccount {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl WebDavAccount {
+    fn authorization(&self) -> Option<String> {
+        (!self.username.is_empty()).then(|| webdav::basic_auth(&self.username, &self.password))
+    }
+}
+
+/// The account of a profile and what its last sync agreed on.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct WebDavSetup {
+    pub account: WebDavAccount,
+    pub state: SyncState,
+}
+
+/// Network errors are kept as their message, they differ between the platforms.
+pub type WebDavError = webdav::WebDavError<String>;
+
+#[cfg(feature = "web")]
+impl Transport for WebDavAccount {
+    type Error = String;
+
+    async fn get(&self, known_etag: Option<&str>) -> Result<Response, String> {
+        let mut request = gloo_net::http::Request::get(&self.url);
+        if let Some(authorization) = self.authorization() {
+            request = request.header("Authorization", &authorization);
+        }
+        if let Some(etag) = known_etag {
+            request = request.header("If-None-Match", etag);
+        }
+        response_of(request.send().await.map_err(|error| error.to_string())?).await
+    }
+
+    async fn put(&self, body: String, precondition: Precondition) -> Result<Response, String> {
+        let mut request = gloo_net::http::Request::put(&self.url).header("Content-Type", "application/json");
+        if let Some(authorization) = self.authorization() {
+            request = request.header("Authorization", &authorization);
+        }
+        request = match &precondition {
+            Precondition::Matches(etag) => request.header("If-Match", etag),
+            Precondition::Missing => request.header("If-None-Match", "*"),
+        };
+        let request = request.body(body).map_err(|error| error.to_string())?;
+        response_of(request.send().await.map_err(|error| error.to_string())?).await
+    }
+}
+
+// The ETag header is only readable cross-origin when the server exposes it
+#[cfg(feature = "web")]
+async fn response_of(response: gloo_net::http::Response) -> Result<Response, String> {
+    let status = response.status();
+    let etag = response.headers().get("ETag");
+    let body = match status {
+        200..=299 => Some(response.text().await.map_err(|error| error.to_string())?),
+        _ => None,
+    };
+    Ok(Response { status, etag, body })
+}
+
+#[cfg(feature = "desktop")]
+impl WebDavAccount {
+    fn request(&self, method: reqwest::Method) -> reqwest::RequestBuilder {
+        let request = reqwest::Client::new().request(method, &self.url);
+        match self.authorization() {
+            Some(authorization) => request.header(reqwest::header::AUTHORIZATION, authorization),
+            None => request,
+        }
+    }
+}
+
+#[cfg(feature = "desktop")]
+impl Transport for WebDavAccount {
+    type Error = String;
+
+    async fn get(&self, known_etag: Option<&str>) -> Result<Response, String> {
+        let mut request = self.request(reqwest::Method::GET);
+        if let Some(etag) = known_etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        response_of(request.send().await.map_err(|error| error.to_string())?).await
+    }
+
+    async fn put(&self, body: String, precondition: Precondition) -> Result<Response, String> {
+        use reqwest::header::{CONTENT_TYPE, IF_MATCH, IF_NONE_MATCH};
+
+        let request = self.request(reqwest::Method::PUT).header(CONTENT_TYPE, "application/json").body(body);
+        let request = match precondition {
+            Precondition::Matches(etag) => request.header(IF_MATCH, etag),
+            Precondition::Missing => request.header(IF_NONE_MATCH, "*"),
+        };
+        response_of(request.send().await.map_err(|error| error.to_string())?).await
+    }
+}
+
+#[cfg(feature = "desktop")]
+async fn response_of(response: reqwest::Response) -> Result<Response, String> {
+    let status = response.status().as_u16();
+    let etag = response.headers().get(reqwest::header::ETAG).and_then(|etag| etag.to_str().ok()).map(str::to_string);
+    let body = match status {
+        200..=299 => Some(response.text().await.map_err(|error| error.to_string())?),
+        _ => None,
+    };
+    Ok(Response { status, etag, body })
+}
+
+/// Pulls what changed on the server, merges it with `local` and pushes the
+/// result if the server doesn't have it yet.
+pub async fn sync(account: &WebDavAccount, local: &DataFile, state: &SyncState) -> Result<Synced, WebDavError> {
+    webdav::sync(account, &account.url, local, state).await
+}
@@ -0,0 +1,98 @@
+// Service worker making the installed app work offline. Trunk gives the wasm
+// bundle, its JS glue and the CSS hashed file names, so they're found in the
+// page itself: caching a build caches index.html and everything it links to.
+// A new build comes with new hashes and a new index.html, but this file stays
+// the same, so pages are fetched from the network first and every build is
+// cached under a name made from its hashed files when its page first shows up.
+
+const PREFIX = "nazad-";
+const SHELL = ["./manifest.webmanifest", "./icons/nazad_logo.png", "./icons/nazad_192.png", "./icons/nazad_512.png"];
+
+// Same-origin files the page links to
+function assetsOf(page) {
+  const urls = [...page.matchAll(/(?:href|src)="([^"]+)"/g)]
+    .map((match) => new URL(match[1], self.registration.scope))
+    .filter((url) => url.origin === self.location.origin)
+    .map((url) => url.href);
+  return [...new Set(urls)].sort();
+}
+
+function cacheName(assets) {
+  let hash = 0;
+  for (const char of assets.join(" ")) {
+    hash = (hash * 31 + char.codePointAt(0)) | 0;
+  }
+  return PREFIX + (hash >>> 0).toString(16);
+}
+
+// Caches the build `page` belongs to unless it already is, then drops the caches of other builds
+async function cacheBuild(page) {
+  const assets = assetsOf(page);
+  const name = cacheName(assets);
+  if (!(await caches.has(name))) {
+    const cache = await caches.open(name);
+    try {
+      await cache.addAll([...new Set([...SHELL, ...assets])]);
+    } catch (error) {
+      // A half cached build must not count as cached
+      await caches.delete(name);
+      throw error;
+    }
+  }
+
+  const cache = await caches.open(name);
+  await cache.put("./index.html", new Response(page, { headers: { "Content-Type": "text/html" } }));
+  const names = await caches.keys();
+  await Promise.all(names.filter((other) => other !== name).map((other) => caches.delete(other)));
+}
+
+async function currentCache() {
+  const name = (await caches.keys()).find((name) => name.startsWith(PREFIX));
+  return name && caches.open(name);
+}
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    fetch("./index.html", { cache: "no-cache" })
+      .then((response) => response.text())
+      .then(cacheBuild)
+  );
+  self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(self.clients.claim());
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  // The server's API is synced by the app itself, which queues edits while offline
+  if (request.method !== "GET" || url.origin !== self.location.origin || url.pathname.startsWith("/api/")) {
+    return;
+  }
+
+  if (request.mode === "navigate") {
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          if (response.ok) {
+            event.waitUntil(response.clone().text().then(cacheBuild).catch(() => {}));
+          }
+          return response;
+        })
+        .catch(() => caches.match("./index.html"))
+    );
+    return;
+  }
+
+  event.respondWith(
+    caches.match(request).then((cached) => cached || fetch(request).then((response) => {
+      if (response.ok) {
+        const copy = response.clone();
+        event.waitUntil(currentCache().then((cache) => cache && cache.put(request, copy)));
+      }
+      return response;
+    }))
+  );
+});